@@ -0,0 +1,264 @@
+use crate::render::styling::ChartStyle;
+use anyhow::{Context, Result};
+use plotters::coord::types::RangedCoordusize;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use std::fmt::Display;
+
+/// Highest and lowest points of a plotted series
+pub struct Extremes<X> {
+    pub max: (X, f32),
+    pub min: (X, f32),
+}
+
+/// Find the maximum and minimum y values of a series (first occurrence wins on ties)
+pub fn find_extremes<X: Copy>(points: &[(X, f32)]) -> Option<Extremes<X>> {
+    let first = *points.first()?;
+    let mut extremes = Extremes {
+        max: first,
+        min: first,
+    };
+
+    for &(x, y) in points.iter().skip(1) {
+        if y > extremes.max.1 {
+            extremes.max = (x, y);
+        }
+        if y < extremes.min.1 {
+            extremes.min = (x, y);
+        }
+    }
+
+    Some(extremes)
+}
+
+/// Build the callout text for an annotated point, e.g. "max (3, 150)"
+pub fn callout_text(kind: &str, x: impl Display, y: f32) -> String {
+    format!("{} ({}, {})", kind, x, y)
+}
+
+/// Mean of the plotted y values
+pub fn mean_value<X>(points: &[(X, f32)]) -> Option<f32> {
+    if points.is_empty() {
+        return None;
+    }
+    Some(points.iter().map(|(_, y)| *y).sum::<f32>() / points.len() as f32)
+}
+
+/// End points of a horizontal reference line at the mean, spanning `start..end`
+pub fn mean_line<X: Copy>(points: &[(X, f32)], start: X, end: X) -> Option<[(X, f32); 2]> {
+    let mean = mean_value(points)?;
+    Some([(start, mean), (end, mean)])
+}
+
+/// Draw a dashed horizontal line at the mean of the plotted values, labeled with the value
+pub fn draw_mean_line<DB, X, Y>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<X, Y>>,
+    points: &[(X::ValueType, f32)],
+    span: (X::ValueType, X::ValueType),
+    style: &ChartStyle,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+    X: Ranged,
+    X::ValueType: Copy,
+    Y: Ranged<ValueType = f32>,
+{
+    let Some(line) = mean_line(points, span.0, span.1) else {
+        return Ok(());
+    };
+
+    let line_color = style.colors.text.title;
+    chart
+        .draw_series(DashedLineSeries::new(
+            line,
+            8,
+            4,
+            line_color.stroke_width(2),
+        ))
+        .context("Failed to draw mean line")?;
+
+    chart
+        .draw_series(std::iter::once(
+            EmptyElement::at(line[0])
+                + Text::new(
+                    format!("mean {}", line[0].1),
+                    (6, -18),
+                    style.axis_label_font(),
+                ),
+        ))
+        .context("Failed to draw mean line label")?;
+
+    Ok(())
+}
+
+/// Draw "max"/"min" callouts at the extreme points of a plotted series
+pub fn draw_extremes<DB, X, Y>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<X, Y>>,
+    points: &[(X::ValueType, f32)],
+    style: &ChartStyle,
+    format_x: impl Fn(&X::ValueType) -> String,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+    X: Ranged,
+    X::ValueType: Copy,
+    Y: Ranged<ValueType = f32>,
+{
+    let Some(extremes) = find_extremes(points) else {
+        return Ok(());
+    };
+
+    let mut callouts = vec![("max", extremes.max, -22)];
+    if extremes.min.1 < extremes.max.1 {
+        callouts.push(("min", extremes.min, 8));
+    }
+
+    let marker_color = style.colors.text.title;
+    for (kind, (x, y), text_offset) in callouts {
+        let text = callout_text(kind, format_x(&x), y);
+        chart
+            .draw_series(std::iter::once(
+                EmptyElement::at((x, y))
+                    + Circle::new((0, 0), 5, marker_color.stroke_width(2))
+                    + Text::new(text, (8, text_offset), style.axis_label_font()),
+            ))
+            .context("Failed to draw extreme point callout")?;
+    }
+
+    Ok(())
+}
+
+/// The first and last point of a series (just one for a single point)
+pub fn endpoints<X: Copy>(points: &[(X, f32)]) -> Vec<(X, f32)> {
+    match points {
+        [] => Vec::new(),
+        [only] => vec![*only],
+        [first, .., last] => vec![*first, *last],
+    }
+}
+
+/// Label the first and last point of a series with its value; the first label reads rightwards
+/// and the last leftwards so both stay over the plot
+pub fn draw_endpoint_labels<DB, X, Y>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<X, Y>>,
+    points: &[(X::ValueType, f32)],
+    color: RGBColor,
+    style: &ChartStyle,
+    format_y: impl Fn(f32) -> String,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+    X: Ranged,
+    X::ValueType: Copy,
+    Y: Ranged<ValueType = f32>,
+{
+    let labels = endpoints(points);
+    let last = labels.len().saturating_sub(1);
+    for (index, (x, y)) in labels.into_iter().enumerate() {
+        let (anchor, dx) = if index == last && index > 0 {
+            (HPos::Right, -4)
+        } else {
+            (HPos::Left, 4)
+        };
+        let font = style
+            .axis_label_font()
+            .color(&color)
+            .pos(Pos::new(anchor, VPos::Bottom));
+        chart
+            .draw_series(std::iter::once(
+                EmptyElement::at((x, y)) + Text::new(format_y(y), (dx, -6), font),
+            ))
+            .context("Failed to draw endpoint label")?;
+    }
+
+    Ok(())
+}
+
+/// Where a bar's value label sits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BarLabelPlacement {
+    /// Past the end of the bar: above a positive bar, below a negative one
+    Outside,
+    /// Centred on the given point, for stacked segments
+    Inside,
+}
+
+/// Write each `(slot, y, text)` label centred on the bar filling `slot..slot + 1`, at `y`
+pub fn draw_bar_labels<DB, Y>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordusize, Y>>,
+    labels: &[(usize, f32, String)],
+    placement: BarLabelPlacement,
+    style: &ChartStyle,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+    Y: Ranged<ValueType = f32>,
+{
+    for (slot, y, text) in labels {
+        // Integer x coordinates are bar edges, so shift half a bar to its centre
+        let left = chart.backend_coord(&(*slot, *y)).0;
+        let right = chart.backend_coord(&(slot + 1, *y)).0;
+        let dx = (right - left) / 2;
+        let (vpos, dy) = match placement {
+            BarLabelPlacement::Outside if *y < 0.0 => (VPos::Top, 4),
+            BarLabelPlacement::Outside => (VPos::Bottom, -4),
+            BarLabelPlacement::Inside => (VPos::Center, 0),
+        };
+        let font = style.axis_label_font().pos(Pos::new(HPos::Center, vpos));
+        chart
+            .draw_series(std::iter::once(
+                EmptyElement::at((*slot, *y)) + Text::new(text.clone(), (dx, dy), font),
+            ))
+            .context("Failed to draw bar label")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_extremes() {
+        let points = vec![(0usize, 10.0), (1, 150.0), (2, 5.0), (3, 150.0)];
+        let extremes = find_extremes(&points).unwrap();
+        assert_eq!(extremes.max, (1, 150.0));
+        assert_eq!(extremes.min, (2, 5.0));
+    }
+
+    #[test]
+    fn test_find_extremes_empty() {
+        let points: Vec<(f32, f32)> = Vec::new();
+        assert!(find_extremes(&points).is_none());
+    }
+
+    #[test]
+    fn test_mean_line_at_mean_of_bars() {
+        let bars = vec![(0usize, 10.0), (1, 20.0), (2, 30.0), (3, 40.0)];
+        let line = mean_line(&bars, 0, bars.len()).unwrap();
+        assert_eq!(mean_value(&bars), Some(25.0));
+        assert_eq!(line, [(0, 25.0), (4, 25.0)]);
+    }
+
+    #[test]
+    fn test_endpoints_are_first_and_last() {
+        let points = vec![(0.0f32, 10.0), (1.0, 50.0), (2.0, 30.0)];
+        assert_eq!(endpoints(&points), vec![(0.0, 10.0), (2.0, 30.0)]);
+        assert_eq!(endpoints(&points[..1]), vec![(0.0, 10.0)]);
+        assert!(endpoints::<f32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_callout_text_contains_max_value() {
+        let points = vec![(1.0f32, 20.0), (2.0, 1234.5), (3.0, 40.0)];
+        let extremes = find_extremes(&points).unwrap();
+        let text = callout_text("max", extremes.max.0, extremes.max.1);
+        assert!(text.contains("1234.5"));
+        assert_eq!(text, "max (2, 1234.5)");
+    }
+}
@@ -1,5 +1,9 @@
-use crate::render::styling::get_chart_style;
+use crate::chart::axis::{TickCountExt, YAxisSideExt, YCoord, drop_non_positive};
+use crate::chart::stack::{order_groups, stack_extent, stack_segments};
+use crate::render::format::select_label_format;
+use crate::render::styling::{get_chart_style_for, group_color_slots};
 use crate::spec::{ChartConfig, LegendPosition};
+use crate::warnings::warn_row_cap;
 use anyhow::{Context, Result};
 use plotters::prelude::*;
 use polars::prelude::*;
@@ -14,10 +18,18 @@ pub fn render<DB: DrawingBackend>(
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    let style = get_chart_style();
+    let style = get_chart_style_for(config);
+
+    // Wide data: each listed column is its own band
+    if let Some(ys) = &config.ys {
+        return render_wide_stacked_area_chart(df, config, root, title, ys, &style);
+    }
 
     // Check if we have grouped data
     if let Some(group_by) = &config.group_by {
+        if config.stacked.unwrap_or(true) && df.column(config.x.as_ref().unwrap()).is_ok() {
+            return render_stacked_area_chart(df, config, root, title, group_by, &style);
+        }
         render_grouped_area_chart(df, config, root, title, group_by, &style)
     } else {
         render_simple_area_chart(df, config, root, title, &style)
@@ -43,6 +55,8 @@ where
 
     let mut data_points = Vec::new();
 
+    warn_row_cap("Area", df.height(), 100)?;
+
     for i in 0..df.height().min(100) {
         // Limit points for performance
         if let (Ok(_x_val), Ok(y_val)) = (x_col.get(i), y_col.get(i)) {
@@ -50,6 +64,7 @@ where
             data_points.push((i as f32, y));
         }
     }
+    drop_non_positive(config, &mut data_points)?;
 
     if data_points.is_empty() {
         return Ok(());
@@ -57,20 +72,29 @@ where
 
     let x_range = 0f32..data_points.len() as f32;
     let y_max = data_points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max);
-    let y_range = 0f32..(y_max * 1.1); // Add 10% padding
+    let y_values: Vec<f32> = data_points.iter().map(|(_, y)| *y).collect();
+    let y_range = YCoord::for_chart(config, 0f32..(y_max * 1.1), &y_values); // Add 10% padding
+    let baseline = y_range.baseline();
+    let y_labels = select_label_format(
+        config.y_format.as_ref(),
+        config.y.as_ref().unwrap(),
+        &y_values,
+    );
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
         .build_cartesian_2d(x_range, y_range)
         .context("Failed to build chart")?;
 
     chart
         .configure_mesh()
-        .x_desc(config.x.as_ref().unwrap())
-        .y_desc(config.y.as_ref().unwrap())
+        .ticks_for(config)
+        .x_desc(config.axis_label(config.x.as_ref().unwrap()))
+        .y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .y_label_formatter(&|v| y_labels.format(*v))
         .axis_desc_style(style.axis_desc_font())
         .label_style(style.axis_label_font())
         .draw()
@@ -79,7 +103,7 @@ where
     // Get the primary color and create a semi-transparent fill
     let line_color = style.get_primary_color(0);
 
-    // Create area data points (filled down to zero)
+    // Create area data points (filled down to zero, or the bottom of a log axis)
     let area_points: Vec<(f32, f32)> = data_points.to_vec();
     let area_fill = RGBColor(line_color.0, line_color.1, line_color.2).mix(0.3);
 
@@ -88,7 +112,10 @@ where
         .draw_series(area_points.windows(2).map(|window| {
             let (x1, y1) = window[0];
             let (x2, y2) = window[1];
-            Polygon::new(vec![(x1, 0.0), (x1, y1), (x2, y2), (x2, 0.0)], area_fill)
+            Polygon::new(
+                vec![(x1, baseline), (x1, y1), (x2, y2), (x2, baseline)],
+                area_fill,
+            )
         }))
         .context("Failed to draw area series")?
         .label(config.y.as_ref().unwrap())
@@ -110,6 +137,7 @@ where
     Ok(())
 }
 
+/// Overlapping areas, one per `group_by` value in sorted order, over the x categories
 fn render_grouped_area_chart<DB: DrawingBackend>(
     df: &DataFrame,
     config: &ChartConfig,
@@ -126,69 +154,392 @@ where
     let value_col = df
         .column(config.y.as_ref().unwrap())
         .context("Value column not found")?;
+    let x_col = df.column(config.x.as_ref().unwrap()).ok();
 
-    let mut data_points = Vec::new();
+    warn_row_cap("Area", df.height(), 100)?;
 
-    for i in 0..df.height().min(100) {
-        // Limit points for performance
-        if let (Ok(_group_val), Ok(value_val)) = (group_col.get(i), value_col.get(i)) {
-            let y = extract_numeric_value(value_val).unwrap_or(0.0);
-            data_points.push((i as f32, y));
+    // Without an x column each group's rows are laid out in turn
+    let rows = match x_col {
+        Some(x_col) => rows_by_x(x_col, df.height().min(100)),
+        None => (0..df.height().min(100)).collect(),
+    };
+
+    // Points carry the index of their group in `groups` alongside x
+    let mut points = Vec::new();
+    let mut groups: Vec<String> = Vec::new();
+    let mut group_lengths: Vec<usize> = Vec::new();
+    let mut categories: Vec<String> = Vec::new();
+    for i in rows {
+        let (Ok(group_val), Ok(value_val)) = (group_col.get(i), value_col.get(i)) else {
+            continue;
+        };
+        let group = value_label(group_val);
+        let group_idx = groups.iter().position(|g| *g == group).unwrap_or_else(|| {
+            groups.push(group);
+            group_lengths.push(0);
+            groups.len() - 1
+        });
+        let x = match x_col.and_then(|x_col| x_col.get(i).ok()) {
+            Some(x_val) => {
+                let label = value_label(x_val);
+                categories
+                    .iter()
+                    .position(|existing| *existing == label)
+                    .unwrap_or_else(|| {
+                        categories.push(label);
+                        categories.len() - 1
+                    })
+            }
+            None => group_lengths[group_idx],
+        };
+        group_lengths[group_idx] += 1;
+        let y = extract_numeric_value(value_val).unwrap_or(0.0);
+        points.push(((x as f32, group_idx), y));
+    }
+    points.retain(|((_, group_idx), _)| config.shows_series(&groups[*group_idx]));
+    drop_non_positive(config, &mut points)?;
+
+    if points.is_empty() {
+        return Ok(());
+    }
+
+    let x_max = points.iter().map(|((x, _), _)| *x).fold(0.0f32, f32::max);
+    let x_range = 0f32..x_max.max(1.0);
+    let y_max = points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max);
+    let y_values: Vec<f32> = points.iter().map(|(_, y)| *y).collect();
+    let y_range = YCoord::for_chart(config, 0f32..(y_max * 1.1), &y_values); // Add 10% padding
+    let baseline = y_range.baseline();
+    let y_labels = select_label_format(
+        config.y_format.as_ref(),
+        config.y.as_ref().unwrap(),
+        &y_values,
+    );
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, style.title_font())
+        .margin(style.layout.margins.chart as i32)
+        .x_label_area_size(style.layout.areas.x_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
+        .build_cartesian_2d(x_range, y_range)
+        .context("Failed to build chart")?;
+
+    // Areas sit at category positions; label them with the x values they stand for
+    let x_label = |v: &f32| {
+        if v.fract() == 0.0 && *v >= 0.0 {
+            categories.get(*v as usize).cloned().unwrap_or_default()
+        } else {
+            String::new()
         }
+    };
+    let y_label = |v: &f32| y_labels.format(*v);
+    let mut mesh = chart.configure_mesh();
+    mesh.ticks_for(config)
+        .y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .y_label_formatter(&y_label)
+        .axis_desc_style(style.axis_desc_font())
+        .label_style(style.axis_label_font());
+    if categories.is_empty() {
+        mesh.x_desc(config.axis_label(group_by));
+    } else {
+        mesh.x_desc(config.axis_label(config.x.as_ref().unwrap()))
+            .x_label_formatter(&x_label);
     }
+    mesh.draw().context("Failed to draw mesh")?;
 
-    if data_points.is_empty() {
+    // Draw groups in sorted order, each colored by that position
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+    order.sort_by(|&a, &b| groups[a].cmp(&groups[b]));
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    for (slot, &group_idx) in order.iter().enumerate() {
+        let group = &groups[group_idx];
+        if !config.shows_series(group) {
+            continue;
+        }
+        let line_color = *style.get_group_color(slot, group, &color_mode);
+        let area_fill = line_color.mix(0.3);
+        let area_points: Vec<(f32, f32)> = points
+            .iter()
+            .filter(|((_, g), _)| *g == group_idx)
+            .map(|((x, _), y)| (*x, *y))
+            .collect();
+
+        // Filled down to zero, or the bottom of a log axis
+        chart
+            .draw_series(area_points.windows(2).map(|window| {
+                let (x1, y1) = window[0];
+                let (x2, y2) = window[1];
+                Polygon::new(
+                    vec![(x1, baseline), (x1, y1), (x2, y2), (x2, baseline)],
+                    area_fill,
+                )
+            }))
+            .context("Failed to draw area series")?
+            .label(group)
+            .legend(move |(x, y)| Rectangle::new([(x, y), (x + 10, y + 10)], area_fill));
+
+        // Draw the line on top of the area for better definition
+        chart
+            .draw_series(LineSeries::new(area_points, line_color))
+            .context("Failed to draw line series")?;
+    }
+
+    // Legend is now handled externally
+
+    root.present().context("Failed to present chart")?;
+    Ok(())
+}
+
+fn render_stacked_area_chart<DB: DrawingBackend>(
+    df: &DataFrame,
+    config: &ChartConfig,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+    group_by: &str,
+    style: &crate::render::styling::ChartStyle,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let bands = grouped_bands(df, config, group_by)?;
+    if bands.categories.is_empty() {
         return Ok(());
     }
+    draw_stacked_bands(
+        config,
+        root,
+        title,
+        config.y.as_ref().unwrap(),
+        &bands,
+        style,
+    )
+}
 
-    let x_range = 0f32..data_points.len() as f32;
-    let y_max = data_points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max);
-    let y_range = 0f32..(y_max * 1.1); // Add 10% padding
+/// Long data as bands: one per `group_by` value, over the union of every group's x values
+///
+/// A group missing at some x has no entry there and is stacked as zero.
+fn grouped_bands(df: &DataFrame, config: &ChartConfig, group_by: &str) -> Result<Bands> {
+    let x_col = df
+        .column(config.x.as_ref().unwrap())
+        .context("X column not found")?;
+    let y_col = df
+        .column(config.y.as_ref().unwrap())
+        .context("Y column not found")?;
+    let group_col = df.column(group_by).context("Group column not found")?;
+
+    // Collect values by x category and group
+    let mut category_data: std::collections::HashMap<
+        String,
+        std::collections::HashMap<String, f32>,
+    > = std::collections::HashMap::new();
+    let mut groups = Vec::new();
+    let mut categories = Vec::new();
+
+    warn_row_cap("Area", df.height(), 100)?;
+
+    // Limit points for performance
+    for i in rows_by_x(x_col, df.height().min(100)) {
+        if let (Ok(x_val), Ok(y_val), Ok(group_val)) =
+            (x_col.get(i), y_col.get(i), group_col.get(i))
+        {
+            let x_str = value_label(x_val);
+            let group_str = value_label(group_val);
+            let y = extract_numeric_value(y_val).unwrap_or(0.0);
+
+            category_data
+                .entry(x_str.clone())
+                .or_default()
+                .insert(group_str.clone(), y);
+            if !groups.contains(&group_str) {
+                groups.push(group_str);
+            }
+            if !categories.contains(&x_str) {
+                categories.push(x_str);
+            }
+        }
+    }
+
+    // Order groups bottom-to-top (stack_order first, then alphabetical)
+    let groups = order_groups(groups, config.stack_order.as_deref());
+    Ok(Bands {
+        categories,
+        slots: group_color_slots(&groups),
+        groups,
+        values: category_data,
+    })
+}
+
+/// Stacked bands from wide data, one per `ys` column, bottom-to-top in the listed order
+fn render_wide_stacked_area_chart<DB: DrawingBackend>(
+    df: &DataFrame,
+    config: &ChartConfig,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+    ys: &[String],
+    style: &crate::render::styling::ChartStyle,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let x_col = df
+        .column(config.x.as_ref().unwrap())
+        .context("X column not found")?;
+    let y_cols = ys
+        .iter()
+        .map(|y| {
+            df.column(y)
+                .with_context(|| format!("Y column '{}' not found", y))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut category_data: std::collections::HashMap<
+        String,
+        std::collections::HashMap<String, f32>,
+    > = std::collections::HashMap::new();
+    let mut categories = Vec::new();
+
+    warn_row_cap("Area", df.height(), 100)?;
+
+    for i in rows_by_x(x_col, df.height().min(100)) {
+        let Ok(x_val) = x_col.get(i) else {
+            continue;
+        };
+        let x_str = value_label(x_val);
+        let values = category_data.entry(x_str.clone()).or_default();
+        for (y, y_col) in ys.iter().zip(&y_cols) {
+            let value = y_col.get(i).ok().and_then(extract_numeric_value);
+            values.insert(y.clone(), value.unwrap_or(0.0));
+        }
+        if !categories.contains(&x_str) {
+            categories.push(x_str);
+        }
+    }
+
+    if categories.is_empty() {
+        return Ok(());
+    }
+
+    let bands = Bands {
+        categories,
+        groups: ys.to_vec(),
+        slots: (0..ys.len()).collect(),
+        values: category_data,
+    };
+    draw_stacked_bands(config, root, title, &ys.join(", "), &bands, style)
+}
+
+/// Values of each band (group) per x category, with groups in bottom-to-top order
+struct Bands {
+    categories: Vec<String>,
+    groups: Vec<String>,
+    /// Palette slot of each group; `ys` columns keep their listed order
+    slots: Vec<usize>,
+    values: std::collections::HashMap<String, std::collections::HashMap<String, f32>>,
+}
+
+fn draw_stacked_bands<DB: DrawingBackend>(
+    config: &ChartConfig,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+    y_name: &str,
+    bands: &Bands,
+    style: &crate::render::styling::ChartStyle,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let Bands {
+        categories,
+        groups,
+        slots,
+        values: category_data,
+    } = bands;
+    // Hidden bands drop out of the stack; the rest keep their colors
+    let (groups, slots): (Vec<String>, Vec<usize>) = groups
+        .iter()
+        .cloned()
+        .zip(slots.iter().copied())
+        .filter(|(group, _)| config.shows_series(group))
+        .unzip();
+    let stacks = stack_segments(categories, &groups, category_data);
+
+    let (y_min, y_max) = stack_extent(stacks.iter().flatten());
+    let x_range = 0f32..(categories.len().max(2) - 1) as f32;
+
+    let y_values: Vec<f32> = category_data
+        .values()
+        .flat_map(|group_values| {
+            group_values
+                .iter()
+                .filter(|(group, _)| config.shows_series(group))
+                .map(|(_, value)| *value)
+        })
+        .collect();
+    // A log axis spans the stack edges, which is where the bands are drawn
+    let edges: Vec<f32> = stacks
+        .iter()
+        .flatten()
+        .flat_map(|&(lower, upper)| [lower, upper])
+        .collect();
+    let y_range = YCoord::for_chart(config, (y_min * 1.1)..(y_max * 1.1), &edges); // Add 10% padding
+    if config.log_scale.unwrap_or(false) && y_values.iter().any(|y| *y <= 0.0) {
+        crate::warnings::warn(
+            "log_scale: non-positive values are drawn at the bottom of the axis",
+        )?;
+    }
+    let floor = y_range.floor();
+    let y_labels = select_label_format(config.y_format.as_ref(), y_name, &y_values);
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
         .build_cartesian_2d(x_range, y_range)
         .context("Failed to build chart")?;
 
+    // Bands sit at category positions; label them with the x values they stand for
+    let x_label = |v: &f32| {
+        if v.fract() == 0.0 && *v >= 0.0 {
+            categories.get(*v as usize).cloned().unwrap_or_default()
+        } else {
+            String::new()
+        }
+    };
     chart
         .configure_mesh()
-        .x_desc(group_by)
-        .y_desc(config.y.as_ref().unwrap())
+        .ticks_for(config)
+        .x_desc(config.axis_label(config.x.as_ref().unwrap()))
+        .x_label_formatter(&x_label)
+        .y_desc(config.axis_label(y_name))
+        .y_label_formatter(&|v| y_labels.format(*v))
         .axis_desc_style(style.axis_desc_font())
         .label_style(style.axis_label_font())
         .draw()
         .context("Failed to draw mesh")?;
 
-    // Get the primary color and create a semi-transparent fill
-    let line_color = style.get_primary_color(0);
-
-    // Create area data points (filled down to zero)
-    let area_points: Vec<(f32, f32)> = data_points.to_vec();
-    let area_fill = RGBColor(line_color.0, line_color.1, line_color.2).mix(0.3);
+    // Draw each band between the top of the previous group and its own top
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    for (group_idx, group) in groups.iter().enumerate() {
+        let color = style.get_group_color(slots[group_idx], group, &color_mode);
+        let fill = RGBColor(color.0, color.1, color.2).mix(0.6);
 
-    // Draw the filled area using polygon
-    chart
-        .draw_series(area_points.windows(2).map(|window| {
-            let (x1, y1) = window[0];
-            let (x2, y2) = window[1];
-            Polygon::new(vec![(x1, 0.0), (x1, y1), (x2, y2), (x2, 0.0)], area_fill)
-        }))
-        .context("Failed to draw area series")?
-        .label(config.y.as_ref().unwrap())
-        .legend(|(x, y)| {
-            Rectangle::new(
-                [(x, y), (x + 10, y + 10)],
-                RGBColor(line_color.0, line_color.1, line_color.2).mix(0.3),
-            )
-        });
+        let upper = stacks
+            .iter()
+            .enumerate()
+            .map(|(i, segments)| (i as f32, segments[group_idx].1.max(floor)));
+        let lower = stacks
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, segments)| (i as f32, segments[group_idx].0.max(floor)));
+        let band: Vec<(f32, f32)> = upper.chain(lower).collect();
 
-    // Draw the line on top of the area for better definition
-    chart
-        .draw_series(LineSeries::new(data_points.iter().cloned(), line_color))
-        .context("Failed to draw line series")?;
+        chart
+            .draw_series(std::iter::once(Polygon::new(band, fill)))
+            .context("Failed to draw stacked area series")?
+            .label(group)
+            .legend(move |(x, y)| Rectangle::new([(x, y), (x + 10, y + 10)], fill));
+    }
 
     // Legend is now handled externally
 
@@ -196,6 +547,35 @@ where
     Ok(())
 }
 
+/// The first `rows` row indices, in ascending x order when every x is a number or a date so
+/// bands share one sorted axis; other columns keep the data's order
+fn rows_by_x(x_col: &Series, rows: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..rows).collect();
+    let keys: Option<Vec<f64>> = order
+        .iter()
+        .map(|&i| x_col.get(i).ok().and_then(x_sort_key))
+        .collect();
+    if let Some(keys) = keys {
+        order.sort_by(|&a, &b| keys[a].total_cmp(&keys[b]));
+    }
+    order
+}
+
+fn x_sort_key(value: AnyValue) -> Option<f64> {
+    match value {
+        AnyValue::Date(days) => Some(days as f64),
+        AnyValue::Datetime(timestamp, _, _) => Some(timestamp as f64),
+        other => extract_numeric_value(other).map(f64::from),
+    }
+}
+
+fn value_label(value: AnyValue) -> String {
+    match value {
+        AnyValue::Utf8(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
+
 fn extract_numeric_value(value: AnyValue) -> Option<f32> {
     match value {
         AnyValue::Int32(i) => Some(i as f32),
@@ -207,3 +587,37 @@ fn extract_numeric_value(value: AnyValue) -> Option<f32> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::ChartType;
+
+    #[test]
+    fn test_groups_align_on_sorted_union_of_x() {
+        // Paid has no day 1 and organic no day 3; rows arrive out of order
+        let df = df! {
+            "day" => &[3i64, 1, 4, 2, 2, 4],
+            "channel" => &["paid", "organic", "organic", "paid", "organic", "paid"],
+            "users" => &[30, 10, 40, 20, 15, 35],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Area,
+            x: Some("day".to_string()),
+            y: Some("users".to_string()),
+            group_by: Some("channel".to_string()),
+            ..Default::default()
+        };
+
+        let bands = grouped_bands(&df, &config, "channel").unwrap();
+        assert_eq!(bands.categories, vec!["1", "2", "3", "4"]);
+        assert_eq!(bands.groups, vec!["organic", "paid"]);
+
+        // Missing points stack as zero-height segments
+        let stacks = stack_segments(&bands.categories, &bands.groups, &bands.values);
+        assert_eq!(stacks[0], vec![(0.0, 10.0), (10.0, 10.0)]);
+        assert_eq!(stacks[1], vec![(0.0, 15.0), (15.0, 35.0)]);
+        assert_eq!(stacks[2], vec![(0.0, 0.0), (0.0, 30.0)]);
+    }
+}
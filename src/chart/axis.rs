@@ -0,0 +1,275 @@
+use crate::spec::{ChartConfig, YAxisSide};
+use anyhow::Result;
+use plotters::coord::ranged1d::{DefaultFormatting, KeyPointHint, ValueFormatter};
+use plotters::coord::types::RangedCoordf32;
+use plotters::prelude::*;
+use std::ops::Range;
+
+/// Place the y label area on the side chosen by the chart's `y_axis_side`
+pub trait YAxisSideExt {
+    fn y_label_area_for(&mut self, config: &ChartConfig, size: u32) -> &mut Self;
+}
+
+impl<DB: DrawingBackend> YAxisSideExt for ChartBuilder<'_, '_, DB> {
+    fn y_label_area_for(&mut self, config: &ChartConfig, size: u32) -> &mut Self {
+        // The primary mesh labels whichever y label area is allocated
+        match config.y_axis_side.clone().unwrap_or_default() {
+            YAxisSide::Left => self.y_label_area_size(size),
+            YAxisSide::Right => self.right_y_label_area_size(size),
+        }
+    }
+}
+
+/// Cap the tick labels at the chart's `x_ticks`/`y_ticks`; plotters still rounds the step to
+/// a 1, 2 or 5 multiple, so the count can come out lower
+pub trait TickCountExt {
+    fn ticks_for(&mut self, config: &ChartConfig) -> &mut Self;
+}
+
+impl<X, Y, XT, YT, DB> TickCountExt for MeshStyle<'_, '_, X, Y, DB>
+where
+    X: Ranged<ValueType = XT> + ValueFormatter<XT>,
+    Y: Ranged<ValueType = YT> + ValueFormatter<YT>,
+    DB: DrawingBackend,
+{
+    fn ticks_for(&mut self, config: &ChartConfig) -> &mut Self {
+        if let Some(ticks) = config.x_ticks {
+            self.x_labels(ticks);
+        }
+        if let Some(ticks) = config.y_ticks {
+            self.y_labels(ticks);
+        }
+        self
+    }
+}
+
+/// A chart's y coordinate: linear, or logarithmic when the chart sets `log_scale`
+pub enum YCoord {
+    Linear(RangedCoordf32),
+    Log(LogCoord<f32>),
+}
+
+impl YCoord {
+    /// `linear` as given, or for a log-scaled chart a log axis padded around the positive `values`;
+    /// either way with the ends the chart fixes with `y_min`/`y_max`
+    pub fn for_chart(config: &ChartConfig, linear: Range<f32>, values: &[f32]) -> Self {
+        if !config.log_scale.unwrap_or(false) {
+            return YCoord::Linear(fixed_y_range(config, linear).into());
+        }
+
+        let (min, max) = values
+            .iter()
+            .copied()
+            .filter(|value| *value > 0.0 && value.is_finite())
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+        let (min, max) = if min.is_finite() {
+            (min, max)
+        } else {
+            (1.0, 10.0)
+        };
+        let range = fixed_y_range(config, min / 1.5..max * 1.5);
+        YCoord::Log(range.log_scale().into())
+    }
+
+    /// Where bars and areas start: zero on a linear axis, or the nearer end when the axis doesn't
+    /// reach zero; the bottom of a log axis
+    pub fn baseline(&self) -> f32 {
+        match self {
+            YCoord::Linear(coord) => {
+                let range = coord.range();
+                0f32.max(range.start).min(range.end)
+            }
+            YCoord::Log(coord) => coord.range().start,
+        }
+    }
+
+    /// Lowest y the axis can place: unbounded on a linear axis, the bottom of a log axis,
+    /// where values at or below zero are drawn
+    pub fn floor(&self) -> f32 {
+        match self {
+            YCoord::Linear(_) => f32::NEG_INFINITY,
+            YCoord::Log(_) => self.baseline(),
+        }
+    }
+}
+
+/// `range` with its ends replaced by the chart's `y_min`/`y_max` where set
+///
+/// With one end fixed and the data entirely beyond it, the other end sits a step past the fixed
+/// one so the range never inverts.
+pub fn fixed_y_range(config: &ChartConfig, range: Range<f32>) -> Range<f32> {
+    let start = config.y_min.map_or(range.start, |y_min| y_min as f32);
+    let end = config.y_max.map_or(range.end, |y_max| y_max as f32);
+    if start < end {
+        start..end
+    } else if config.y_min.is_some() {
+        start..start + start.abs().max(1.0)
+    } else {
+        end - end.abs().max(1.0)..end
+    }
+}
+
+impl Ranged for YCoord {
+    type FormatOption = DefaultFormatting;
+    type ValueType = f32;
+
+    fn map(&self, value: &f32, limit: (i32, i32)) -> i32 {
+        match self {
+            YCoord::Linear(coord) => coord.map(value, limit),
+            YCoord::Log(coord) => coord.map(value, limit),
+        }
+    }
+
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<f32> {
+        match self {
+            YCoord::Linear(coord) => coord.key_points(hint),
+            YCoord::Log(coord) => coord.key_points(hint),
+        }
+    }
+
+    fn range(&self) -> Range<f32> {
+        match self {
+            YCoord::Linear(coord) => coord.range(),
+            YCoord::Log(coord) => coord.range(),
+        }
+    }
+}
+
+/// Zero and negative values have no place on a log axis; on a log-scaled chart, drop those
+/// points with a warning
+pub fn drop_non_positive<X>(config: &ChartConfig, points: &mut Vec<(X, f32)>) -> Result<()> {
+    if !config.log_scale.unwrap_or(false) {
+        return Ok(());
+    }
+    let total = points.len();
+    points.retain(|(_, y)| *y > 0.0);
+    let dropped = total - points.len();
+    if dropped > 0 {
+        crate::warnings::warn(format!(
+            "log_scale: dropped {} point(s) with non-positive y values",
+            dropped
+        ))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plot_x_range(config: &ChartConfig) -> std::ops::Range<i32> {
+        let mut buffer = vec![0u8; 400 * 300 * 3];
+        let root = BitMapBackend::with_buffer(&mut buffer, (400, 300)).into_drawing_area();
+        let chart = ChartBuilder::on(&root)
+            .y_label_area_for(config, 60)
+            .build_cartesian_2d(0f32..1f32, 0f32..1f32)
+            .unwrap();
+        chart.plotting_area().get_pixel_range().0
+    }
+
+    #[test]
+    fn test_right_y_axis_allocates_label_area_on_right() {
+        let left = plot_x_range(&ChartConfig::default());
+        assert_eq!((left.start, left.end), (60, 400));
+
+        let config = ChartConfig {
+            y_axis_side: Some(YAxisSide::Right),
+            ..Default::default()
+        };
+        let right = plot_x_range(&config);
+        assert_eq!((right.start, right.end), (0, 340));
+    }
+
+    /// Text of the y-axis tick labels drawn for values 0..100 with `config`
+    fn y_tick_labels(config: &ChartConfig) -> Vec<String> {
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (400, 300)).into_drawing_area();
+            let mut chart = ChartBuilder::on(&root)
+                .y_label_area_size(60)
+                .build_cartesian_2d(0f32..1f32, 0f32..100f32)
+                .unwrap();
+            chart
+                .configure_mesh()
+                .ticks_for(config)
+                .disable_x_axis()
+                .y_label_formatter(&|v| format!("y{}", v))
+                .draw()
+                .unwrap();
+        }
+        svg.split('>')
+            .filter_map(|part| part.strip_suffix("</text"))
+            .filter(|text| text.starts_with('y'))
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_log_scale_spaces_decades_evenly() {
+        let config = ChartConfig {
+            log_scale: Some(true),
+            ..Default::default()
+        };
+        let values = [1.0f32, 10.0, 100.0, 1000.0, 10000.0];
+        let coord = YCoord::for_chart(&config, 0.0..11000.0, &values);
+        assert!(coord.baseline() > 0.0 && coord.baseline() < 1.0);
+
+        let pixels: Vec<i32> = values.iter().map(|y| coord.map(y, (0, 800))).collect();
+        let gaps: Vec<i32> = pixels.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(gaps[0] > 0);
+        assert!(gaps.iter().all(|gap| (gap - gaps[0]).abs() <= 1));
+
+        // Without log_scale the linear range is used unchanged
+        let linear = YCoord::for_chart(&ChartConfig::default(), 0.0..11000.0, &values);
+        assert_eq!(linear.range(), 0.0..11000.0);
+        assert_eq!(linear.baseline(), 0.0);
+    }
+
+    #[test]
+    fn test_y_min_and_y_max_fix_the_domain() {
+        let values = [80.0f32, 120.0, 95.0];
+        let fixed = ChartConfig {
+            y_min: Some(50.0),
+            y_max: Some(200.0),
+            ..Default::default()
+        };
+        let coord = YCoord::for_chart(&fixed, 0.0..132.0, &values);
+        assert_eq!(coord.range(), 50.0..200.0);
+        // Bars and areas rise from the bottom of an axis above zero
+        assert_eq!(coord.baseline(), 50.0);
+
+        // The end left unset follows the data
+        let top_only = ChartConfig {
+            y_max: Some(500.0),
+            ..Default::default()
+        };
+        let coord = YCoord::for_chart(&top_only, 0.0..132.0, &values);
+        assert_eq!(coord.range(), 0.0..500.0);
+        assert_eq!(coord.baseline(), 0.0);
+
+        // A bottom above all the data still leaves a non-empty range
+        let above = ChartConfig {
+            y_min: Some(300.0),
+            ..Default::default()
+        };
+        assert_eq!(fixed_y_range(&above, 0.0..132.0), 300.0..600.0);
+    }
+
+    #[test]
+    fn test_y_ticks_sets_label_count() {
+        // By default plotters labels 0..100 every 20
+        assert_eq!(
+            y_tick_labels(&ChartConfig::default()),
+            vec!["y0", "y20", "y40", "y60", "y80", "y100"]
+        );
+
+        // At most 3 labels: the step widens to the next round number
+        let config = ChartConfig {
+            y_ticks: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(y_tick_labels(&config), vec!["y0", "y50", "y100"]);
+    }
+}
@@ -1,5 +1,10 @@
-use crate::render::styling::get_chart_style;
-use crate::spec::{ChartConfig, LegendPosition};
+use crate::chart::annotate::{BarLabelPlacement, draw_bar_labels, draw_extremes, draw_mean_line};
+use crate::chart::axis::{TickCountExt, YAxisSideExt, YCoord};
+use crate::chart::hierarchy::{X2_LABEL_AREA, column_labels, draw_x2_labels, x2_groups};
+use crate::render::format::select_label_format;
+use crate::render::styling::{get_chart_style_for, group_color_slots};
+use crate::spec::{ChartConfig, LegendPosition, SeriesSpec};
+use crate::warnings::warn_row_cap;
 use anyhow::{Context, Result};
 use plotters::prelude::*;
 use polars::prelude::*;
@@ -14,10 +19,20 @@ pub fn render<DB: DrawingBackend>(
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    let style = get_chart_style();
+    let style = get_chart_style_for(config);
+
+    if let Some(series) = config.series.as_deref().filter(|series| !series.is_empty()) {
+        return render_combo_chart(df, config, series, root, title, &style);
+    }
 
     // Check if we have grouped data
     if let Some(group_by) = &config.group_by {
+        // Horizontal bars with exactly two groups are drawn back to back
+        if config.horizontal.unwrap_or(false)
+            && let Some(pyramid) = collect_pyramid(df, config, group_by)?
+        {
+            return render_population_pyramid(&pyramid, config, root, title, &style);
+        }
         render_grouped_bar_chart(df, config, root, title, group_by, &style)
     } else {
         render_simple_bar_chart(df, config, root, title, &style)
@@ -42,14 +57,14 @@ where
         .context("Y column not found")?;
 
     let mut data_points = Vec::new();
-    let mut _x_labels = Vec::new();
+
+    warn_row_cap("Bar", df.height(), 20)?;
 
     for i in 0..df.height().min(20) {
         // Limit to 20 bars for readability
-        if let (Ok(x_val), Ok(y_val)) = (x_col.get(i), y_col.get(i)) {
+        if let (Ok(_), Ok(y_val)) = (x_col.get(i), y_col.get(i)) {
             let y = extract_numeric_value(y_val).unwrap_or(0.0);
             data_points.push((i, y));
-            _x_labels.push(format!("{:?}", x_val));
         }
     }
 
@@ -58,36 +73,208 @@ where
     }
 
     let y_max = data_points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max);
-    let y_range = 0f32..(y_max * 1.1);
+    let y_values: Vec<f32> = data_points.iter().map(|(_, y)| *y).collect();
+    let y_range = YCoord::for_chart(config, 0f32..(y_max * 1.1), &y_values);
+    let (baseline, floor) = (y_range.baseline(), y_range.floor());
+    warn_flat_bars(config, &y_values)?;
+    let y_labels = select_label_format(
+        config.y_format.as_ref(),
+        config.y.as_ref().unwrap(),
+        &y_values,
+    );
+
+    // With x2, bars are labelled by their x value and grouped under x2 brackets
+    let x2_labels = match &config.x2 {
+        Some(x2) => Some(column_labels(df, x2, data_points.len())?),
+        None => None,
+    };
+    let mut x_labels = column_labels(df, config.x.as_ref().unwrap(), data_points.len())?;
+    if let Some(max_len) = config.label_max_len {
+        for label in &mut x_labels {
+            *label = truncate_label(label, max_len);
+        }
+    }
+
+    if config.horizontal.unwrap_or(false) {
+        return render_horizontal_bars(
+            &root,
+            config,
+            title,
+            style,
+            &data_points,
+            &x_labels,
+            y_range,
+            &y_labels,
+        );
+    }
+
+    let x_label_area = style.layout.areas.x_label_area
+        + if x2_labels.is_some() {
+            X2_LABEL_AREA
+        } else {
+            0
+        };
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
-        .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
+        .x_label_area_size(x_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
         .build_cartesian_2d(0usize..data_points.len(), y_range)
         .context("Failed to build chart")?;
 
+    // Each bar is labelled with its category, centred beneath it and cut to the bar's width
+    let slot_width = chart.plotting_area().dim_in_pixel().0 / data_points.len() as u32;
+    let x_labels = x_labels
+        .iter()
+        .map(|label| fit_label(&root, label, slot_width, &style.axis_label_font()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let y_label = |v: &f32| y_labels.format(*v);
+    let x_label = |i: &usize| x_labels.get(*i).cloned().unwrap_or_default();
+    let mut mesh = chart.configure_mesh();
+    mesh.ticks_for(config);
+    mesh.y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .y_label_formatter(&y_label)
+        .x_desc(config.axis_label(config.x.as_ref().unwrap()))
+        .x_label_formatter(&x_label)
+        .x_label_offset(slot_width as i32 / 2)
+        .axis_desc_style(style.axis_desc_font())
+        .label_style(style.axis_label_font());
+    if config.x_ticks.is_none() {
+        mesh.x_labels(data_points.len() + 1);
+    }
+    mesh.draw().context("Failed to draw mesh")?;
+
+    if let Some(x2_labels) = &x2_labels {
+        let spans: Vec<(String, i32, i32)> = x2_groups(x2_labels)
+            .into_iter()
+            .map(|group| {
+                let left = chart.backend_coord(&(group.first, baseline)).0;
+                let right = chart.backend_coord(&(group.last + 1, baseline)).0;
+                (group.label, left, right)
+            })
+            .collect();
+        let plot_bottom = chart.plotting_area().get_pixel_range().1.end;
+        draw_x2_labels(&root, &spans, plot_bottom, style)?;
+    }
+
+    chart
+        .draw_series(data_points.iter().enumerate().map(|(i, (_, y))| {
+            let color = style.get_primary_color(i);
+            Rectangle::new([(i, baseline), (i + 1, y.max(floor))], color.filled())
+        }))
+        .context("Failed to draw bar series")?
+        .label(config.y.as_ref().unwrap())
+        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], style.get_primary_color(0)));
+
+    if config.mark_extremes.unwrap_or(false) {
+        draw_extremes(&mut chart, &data_points, style, |i| i.to_string())?;
+    }
+
+    if config.mean_line.unwrap_or(false) {
+        draw_mean_line(&mut chart, &data_points, (0, data_points.len()), style)?;
+    }
+
+    if config.data_labels.unwrap_or(false) {
+        let labels = bar_value_labels(&data_points, floor, &y_labels);
+        draw_bar_labels(&mut chart, &labels, BarLabelPlacement::Outside, style)?;
+    }
+
+    // Legend is now handled externally
+
+    root.present().context("Failed to present chart")?;
+    Ok(())
+}
+
+/// Bars running left to right with categories down the y axis, first row at the top
+///
+/// The label area is sized to the longest category so names are shown in full.
+#[allow(clippy::too_many_arguments)]
+fn render_horizontal_bars<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    config: &ChartConfig,
+    title: &str,
+    style: &crate::render::styling::ChartStyle,
+    data_points: &[(usize, f32)],
+    labels: &[String],
+    value_range: YCoord,
+    value_labels: &crate::render::format::LabelFormat,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    for (field, set) in [
+        ("mark_extremes", config.mark_extremes.unwrap_or(false)),
+        ("mean_line", config.mean_line.unwrap_or(false)),
+        ("x2", config.x2.is_some()),
+        ("data_labels", config.data_labels.unwrap_or(false)),
+    ] {
+        if set {
+            crate::warnings::warn(format!("'{}' is not drawn on horizontal bars", field))?;
+        }
+    }
+
+    let (baseline, floor) = (value_range.baseline(), value_range.floor());
+    let rows = data_points.len();
+    // Row `i` is drawn at position `rows - 1 - i`, so the first category sits at the top
+    let position = |i: usize| rows - 1 - i;
+
+    let label_font = style.axis_label_font();
+    let mut longest = 0;
+    for label in labels {
+        let (width, _) = root
+            .estimate_text_size(label, &label_font)
+            .context("Failed to measure category label")?;
+        longest = longest.max(width);
+    }
+    // Room for the labels plus the rotated axis description beside them
+    let label_area = (longest + 2 * style.typography.sizes.axis_description + 20)
+        .max(style.layout.areas.y_label_area);
+
+    let mut chart = ChartBuilder::on(root)
+        .caption(title, style.title_font())
+        .margin(style.layout.margins.chart as i32)
+        .x_label_area_size(style.layout.areas.x_label_area)
+        .y_label_area_size(label_area)
+        .build_cartesian_2d(value_range, 0usize..rows)
+        .context("Failed to build chart")?;
+
+    let value_label = |v: &f32| value_labels.format(*v);
+    let category_label = |row: &usize| {
+        if *row < rows {
+            labels.get(position(*row)).cloned().unwrap_or_default()
+        } else {
+            String::new()
+        }
+    };
     chart
         .configure_mesh()
-        .y_desc(config.y.as_ref().unwrap())
-        .x_desc(config.x.as_ref().unwrap())
+        .ticks_for(config)
+        .disable_y_mesh()
+        .y_labels(rows + 1)
+        .y_label_formatter(&category_label)
+        .x_label_formatter(&value_label)
+        .x_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .y_desc(config.axis_label(config.x.as_ref().unwrap()))
         .axis_desc_style(style.axis_desc_font())
         .label_style(style.axis_label_font())
         .draw()
         .context("Failed to draw mesh")?;
 
     chart
-        .draw_series(data_points.iter().enumerate().map(|(i, (_, y))| {
+        .draw_series(data_points.iter().enumerate().map(|(i, (_, value))| {
             let color = style.get_primary_color(i);
-            Rectangle::new([(i, 0.0), (i + 1, *y)], color.filled())
+            let row = position(i);
+            Rectangle::new(
+                [(baseline, row), (value.max(floor), row + 1)],
+                color.filled(),
+            )
         }))
         .context("Failed to draw bar series")?
         .label(config.y.as_ref().unwrap())
         .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], style.get_primary_color(0)));
 
-    // Legend is now handled externally
-
     root.present().context("Failed to present chart")?;
     Ok(())
 }
@@ -110,14 +297,25 @@ where
         .context("Value column not found")?;
 
     let mut data_points = Vec::new();
-    let mut _x_labels = Vec::new();
+    let mut bar_groups = Vec::new();
+    // Every group, hidden ones included, so a bar's color doesn't depend on what is hidden
+    let mut groups: Vec<String> = Vec::new();
+
+    warn_row_cap("Bar", df.height(), 20)?;
 
     for i in 0..df.height().min(20) {
         // Limit to 20 bars for readability
-        if let (Ok(_group_val), Ok(value_val)) = (group_col.get(i), value_col.get(i)) {
+        if let (Ok(group_val), Ok(value_val)) = (group_col.get(i), value_col.get(i)) {
+            let group = value_label(group_val);
+            if !groups.contains(&group) {
+                groups.push(group.clone());
+            }
+            if !config.shows_series(&group) {
+                continue;
+            }
             let y = extract_numeric_value(value_val).unwrap_or(0.0);
-            data_points.push((i, y));
-            _x_labels.push(format!("Group {}", i));
+            data_points.push((data_points.len(), y));
+            bar_groups.push(group);
         }
     }
 
@@ -126,40 +324,343 @@ where
     }
 
     let y_max = data_points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max);
-    let y_range = 0f32..(y_max * 1.1);
+    let y_values: Vec<f32> = data_points.iter().map(|(_, y)| *y).collect();
+    let y_range = YCoord::for_chart(config, 0f32..(y_max * 1.1), &y_values);
+    let (baseline, floor) = (y_range.baseline(), y_range.floor());
+    warn_flat_bars(config, &y_values)?;
+    let y_labels = select_label_format(
+        config.y_format.as_ref(),
+        config.y.as_ref().unwrap(),
+        &y_values,
+    );
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
         .build_cartesian_2d(0usize..data_points.len(), y_range)
         .context("Failed to build chart")?;
 
     chart
         .configure_mesh()
-        .y_desc(config.y.as_ref().unwrap())
-        .x_desc(group_by)
+        .ticks_for(config)
+        .y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .y_label_formatter(&|v| y_labels.format(*v))
+        .x_desc(config.axis_label(group_by))
         .axis_desc_style(style.axis_desc_font())
         .label_style(style.axis_label_font())
         .draw()
         .context("Failed to draw mesh")?;
 
+    // A bar's color comes from its group's place among the sorted groups
+    let slots = group_color_slots(&groups);
+    let color_mode = config.color_mode.clone().unwrap_or_default();
     chart
         .draw_series(data_points.iter().enumerate().map(|(i, (_, y))| {
-            let color = style.get_primary_color(i);
-            Rectangle::new([(i, 0.0), (i + 1, *y)], color.filled())
+            let group = &bar_groups[i];
+            let slot = slots[groups.iter().position(|g| g == group).unwrap()];
+            let color = style.get_group_color(slot, group, &color_mode);
+            Rectangle::new([(i, baseline), (i + 1, y.max(floor))], color.filled())
         }))
         .context("Failed to draw bar series")?
         .label(config.y.as_ref().unwrap())
         .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], style.get_primary_color(0)));
 
+    if config.mark_extremes.unwrap_or(false) {
+        draw_extremes(&mut chart, &data_points, style, |i| i.to_string())?;
+    }
+
+    if config.mean_line.unwrap_or(false) {
+        draw_mean_line(&mut chart, &data_points, (0, data_points.len()), style)?;
+    }
+
+    if config.data_labels.unwrap_or(false) {
+        let labels = bar_value_labels(&data_points, floor, &y_labels);
+        draw_bar_labels(&mut chart, &labels, BarLabelPlacement::Outside, style)?;
+    }
+
+    // Legend is now handled externally
+
+    root.present().context("Failed to present chart")?;
+    Ok(())
+}
+
+/// Each bar's value, to be written past its end
+fn bar_value_labels(
+    data_points: &[(usize, f32)],
+    floor: f32,
+    y_labels: &crate::render::format::LabelFormat,
+) -> Vec<(usize, f32, String)> {
+    let format = y_labels.with_separators();
+    data_points
+        .iter()
+        .enumerate()
+        .map(|(i, (_, y))| (i, y.max(floor), format.format(*y)))
+        .collect()
+}
+
+/// On a log scale a bar at or below zero has no height; it keeps its slot (and label) but
+/// is flat, so say so
+fn warn_flat_bars(config: &ChartConfig, values: &[f32]) -> Result<()> {
+    let flat = values.iter().filter(|value| **value <= 0.0).count();
+    if config.log_scale.unwrap_or(false) && flat > 0 {
+        crate::warnings::warn(format!(
+            "log_scale: {} bar(s) with non-positive values are drawn flat",
+            flat
+        ))?;
+    }
+    Ok(())
+}
+
+/// Back-to-back bars: the first group extends left of center, the second right
+struct Pyramid {
+    bands: Vec<String>,
+    groups: [String; 2],
+    /// (group index, band index, signed value)
+    bars: Vec<(usize, usize, f32)>,
+}
+
+fn collect_pyramid(
+    df: &DataFrame,
+    config: &ChartConfig,
+    group_by: &str,
+) -> Result<Option<Pyramid>> {
+    let x_col = df
+        .column(config.x.as_ref().unwrap())
+        .context("X column not found")?;
+    let group_col = df.column(group_by).context("Group column not found")?;
+    let y_col = df
+        .column(config.y.as_ref().unwrap())
+        .context("Y column not found")?;
+
+    let mut rows = Vec::new();
+    for i in 0..df.height() {
+        if let (Ok(x_val), Ok(group_val), Ok(y_val)) =
+            (x_col.get(i), group_col.get(i), y_col.get(i))
+        {
+            let y = extract_numeric_value(y_val).unwrap_or(0.0);
+            rows.push((value_label(x_val), value_label(group_val), y));
+        }
+    }
+
+    Ok(build_pyramid(&rows))
+}
+
+/// Arrange (band, group, value) rows into a pyramid; `None` unless there are exactly two groups
+fn build_pyramid(rows: &[(String, String, f32)]) -> Option<Pyramid> {
+    let mut bands: Vec<String> = Vec::new();
+    let mut groups: Vec<String> = Vec::new();
+    for (band, group, _) in rows {
+        if !bands.contains(band) {
+            bands.push(band.clone());
+        }
+        if !groups.contains(group) {
+            groups.push(group.clone());
+        }
+    }
+
+    let groups: [String; 2] = groups.try_into().ok()?;
+
+    let bars = rows
+        .iter()
+        .map(|(band, group, value)| {
+            let group_idx = if *group == groups[0] { 0 } else { 1 };
+            let band_idx = bands.iter().position(|b| b == band).unwrap();
+            let signed = if group_idx == 0 {
+                -value.abs()
+            } else {
+                value.abs()
+            };
+            (group_idx, band_idx, signed)
+        })
+        .collect();
+
+    Some(Pyramid {
+        bands,
+        groups,
+        bars,
+    })
+}
+
+fn render_population_pyramid<DB: DrawingBackend>(
+    pyramid: &Pyramid,
+    config: &ChartConfig,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+    style: &crate::render::styling::ChartStyle,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let max_value = pyramid
+        .bars
+        .iter()
+        .map(|(_, _, v)| v.abs())
+        .fold(0.0f32, f32::max);
+    let x_limit = if max_value > 0.0 {
+        max_value * 1.1
+    } else {
+        1.0
+    };
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, style.title_font())
+        .margin(style.layout.margins.chart as i32)
+        .x_label_area_size(style.layout.areas.x_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
+        .build_cartesian_2d(-x_limit..x_limit, 0usize..pyramid.bands.len())
+        .context("Failed to build chart")?;
+
+    chart
+        .configure_mesh()
+        .ticks_for(config)
+        .x_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .y_desc(config.axis_label(config.x.as_ref().unwrap()))
+        .x_label_formatter(&|v| format!("{}", v.abs()))
+        .y_label_formatter(&|i| pyramid.bands.get(*i).cloned().unwrap_or_default())
+        .axis_desc_style(style.axis_desc_font())
+        .label_style(style.axis_label_font())
+        .draw()
+        .context("Failed to draw mesh")?;
+
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    let slots = group_color_slots(&pyramid.groups);
+    for (group_idx, group) in pyramid.groups.iter().enumerate() {
+        let color = style.get_group_color(slots[group_idx], group, &color_mode);
+        chart
+            .draw_series(pyramid.bars.iter().filter(|(g, _, _)| *g == group_idx).map(
+                |(_, band, value)| {
+                    Rectangle::new([(0.0, *band), (*value, band + 1)], color.filled())
+                },
+            ))
+            .context("Failed to draw pyramid series")?
+            .label(group)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], color));
+    }
+
+    // Legend is now handled externally
+
+    root.present().context("Failed to present chart")?;
+    Ok(())
+}
+
+/// Bars for the first series, lines on a secondary y-axis for the rest
+fn render_combo_chart<DB: DrawingBackend>(
+    df: &DataFrame,
+    config: &ChartConfig,
+    series: &[SeriesSpec],
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+    style: &crate::render::styling::ChartStyle,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    warn_row_cap("Bar", df.height(), 20)?;
+    let rows = df.height().min(20);
+    if rows == 0 {
+        return Ok(());
+    }
+
+    let mut values = Vec::new();
+    for s in series {
+        let name = s.output_column();
+        let column = df
+            .column(&name)
+            .with_context(|| format!("Series column '{}' not found", name))?;
+        let series_values: Vec<f32> = (0..rows)
+            .map(|i| {
+                column
+                    .get(i)
+                    .ok()
+                    .and_then(extract_numeric_value)
+                    .unwrap_or(0.0)
+            })
+            .collect();
+        values.push(series_values);
+    }
+
+    let (bar_values, line_values) = values.split_first().context("No series to draw")?;
+    let bar_name = series[0].output_column();
+    let line_names: Vec<String> = series[1..].iter().map(SeriesSpec::output_column).collect();
+
+    // `log_scale` and `y_min`/`y_max` shape the bars' axis; the lines keep their own linear one
+    let bar_max = bar_values.iter().copied().fold(0.0f32, f32::max);
+    let line_max = line_values.iter().flatten().copied().fold(0.0f32, f32::max);
+    let y_range = YCoord::for_chart(config, 0f32..(bar_max * 1.1), bar_values);
+    let (baseline, floor) = (y_range.baseline(), y_range.floor());
+    warn_flat_bars(config, bar_values)?;
+    let bar_labels = select_label_format(config.y_format.as_ref(), &bar_name, bar_values);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, style.title_font())
+        .margin(style.layout.margins.chart as i32)
+        .x_label_area_size(style.layout.areas.x_label_area)
+        .y_label_area_size(style.layout.areas.y_label_area)
+        .right_y_label_area_size(style.layout.areas.y_label_area)
+        .build_cartesian_2d(0usize..rows, y_range)
+        .context("Failed to build chart")?
+        .set_secondary_coord(0f32..rows as f32, 0f32..(line_max * 1.1));
+
+    chart
+        .configure_mesh()
+        .ticks_for(config)
+        .x_desc(config.axis_label(config.x.as_ref().unwrap()))
+        .y_desc(config.axis_label(&bar_name))
+        .y_label_formatter(&|v| bar_labels.format(*v))
+        .axis_desc_style(style.axis_desc_font())
+        .label_style(style.axis_label_font())
+        .draw()
+        .context("Failed to draw mesh")?;
+
+    if !line_values.is_empty() {
+        chart
+            .configure_secondary_axes()
+            .y_desc(line_names.join(", "))
+            .axis_desc_style(style.axis_desc_font())
+            .label_style(style.axis_label_font())
+            .draw()
+            .context("Failed to draw secondary axis")?;
+    }
+
+    let bar_color = style.get_primary_color(0);
+    chart
+        .draw_series(bar_values.iter().enumerate().map(|(i, y)| {
+            Rectangle::new([(i, baseline), (i + 1, y.max(floor))], bar_color.filled())
+        }))
+        .context("Failed to draw bar series")?;
+
+    // Lines pass through the middle of each bar
+    for (series_idx, series_values) in line_values.iter().enumerate() {
+        let line_color = style.get_primary_color(series_idx + 1);
+        chart
+            .draw_secondary_series(
+                LineSeries::new(
+                    series_values
+                        .iter()
+                        .enumerate()
+                        .map(|(i, y)| (i as f32 + 0.5, *y)),
+                    line_color.stroke_width(2),
+                )
+                .point_size(style.layout.elements.line_points),
+            )
+            .context("Failed to draw line series")?;
+    }
+
     // Legend is now handled externally
 
     root.present().context("Failed to present chart")?;
     Ok(())
 }
 
+fn value_label(value: AnyValue) -> String {
+    match value {
+        AnyValue::Utf8(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
+
 fn extract_numeric_value(value: AnyValue) -> Option<f32> {
     match value {
         AnyValue::Int32(i) => Some(i as f32),
@@ -171,3 +672,296 @@ fn extract_numeric_value(value: AnyValue) -> Option<f32> {
         _ => None,
     }
 }
+
+/// `label` as it fits in `width` pixels, shortened with an ellipsis when it would overlap its
+/// neighbours
+fn fit_label<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    label: &str,
+    width: u32,
+    font: &TextStyle,
+) -> Result<String> {
+    // Leave a little space between neighbouring labels
+    let width = width.saturating_sub(6);
+    let fits = |text: &str| -> Result<bool> {
+        let (text_width, _) = root
+            .estimate_text_size(text, font)
+            .context("Failed to measure category label")?;
+        Ok(text_width <= width)
+    };
+    if fits(label)? {
+        return Ok(label.to_string());
+    }
+    for max_len in (2..label.chars().count()).rev() {
+        let shortened = truncate_label(label, max_len);
+        if fits(&shortened)? {
+            return Ok(shortened);
+        }
+    }
+    Ok(truncate_label(label, 1))
+}
+
+/// Shorten `label` to at most `max_len` characters, ending in an ellipsis when cut
+///
+/// Counts chars rather than bytes, so multi-byte labels are never split mid-character.
+pub fn truncate_label(label: &str, max_len: usize) -> String {
+    if label.chars().count() <= max_len {
+        return label.to_string();
+    }
+    let mut truncated: String = label.chars().take(max_len.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::styling::get_chart_style;
+
+    fn pyramid_rows(groups: &[&str]) -> Vec<(String, String, f32)> {
+        let bands = ["0-19", "20-39", "40-59", "60-79", "80+"];
+        bands
+            .iter()
+            .enumerate()
+            .flat_map(|(i, band)| {
+                groups.iter().map(move |group| {
+                    (band.to_string(), group.to_string(), 100.0 - i as f32 * 15.0)
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_population_pyramid_bars_extend_both_sides() {
+        let pyramid = build_pyramid(&pyramid_rows(&["male", "female"])).unwrap();
+
+        assert_eq!(pyramid.bands.len(), 5);
+        assert_eq!(pyramid.groups, ["male".to_string(), "female".to_string()]);
+        assert_eq!(pyramid.bars.len(), 10);
+        for (group, _, value) in &pyramid.bars {
+            if *group == 0 {
+                assert!(*value < 0.0, "first group should extend left of center");
+            } else {
+                assert!(*value > 0.0, "second group should extend right of center");
+            }
+        }
+    }
+
+    #[test]
+    fn test_population_pyramid_requires_two_groups() {
+        assert!(build_pyramid(&pyramid_rows(&["a", "b", "c"])).is_none());
+        assert!(build_pyramid(&pyramid_rows(&["a"])).is_none());
+    }
+
+    #[test]
+    fn test_x2_draws_both_label_levels() {
+        let df = df! {
+            "month" => &["Jan", "Feb", "Mar", "Apr", "May"],
+            "quarter" => &["Q1", "Q1", "Q1", "Q2", "Q2"],
+            "revenue" => &[10.0, 12.0, 9.0, 15.0, 14.0],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Bar,
+            x: Some("month".to_string()),
+            x2: Some("quarter".to_string()),
+            y: Some("revenue".to_string()),
+            ..Default::default()
+        };
+
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (800, 600)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            render(&df, &config, root, "Revenue", &LegendPosition::Right).unwrap();
+        }
+
+        for label in ["Jan", "Mar", "May", "Q1", "Q2"] {
+            assert!(
+                svg.contains(&format!(">{}<", label)),
+                "missing label {}",
+                label
+            );
+        }
+    }
+
+    #[test]
+    fn test_bars_are_labelled_with_their_categories() {
+        let long = "Referral traffic from partner newsletters and other campaigns";
+        let df = df! {
+            "channel" => &["organic", "direct", long],
+            "users" => &[300.0, 120.0, 60.0],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Bar,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            ..Default::default()
+        };
+
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (600, 400)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            render(&df, &config, root, "Users", &LegendPosition::Right).unwrap();
+        }
+
+        assert!(svg.contains(">organic<"));
+        assert!(svg.contains(">direct<"));
+        // Row indices are not used as labels
+        assert!(!svg.contains(">1<") && !svg.contains(">2<"));
+        // Too wide for its bar, the long category is shortened rather than overlapping
+        assert!(!svg.contains(long));
+        assert!(svg.contains(">Referral"));
+        assert!(svg.contains("…<"));
+    }
+
+    #[test]
+    fn test_data_labels_write_one_value_per_bar() {
+        let df = df! {
+            "channel" => &["organic", "direct", "email"],
+            "users" => &[12500.0, 3400.0, 780.0],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Bar,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            ..Default::default()
+        };
+        let draw = |config: &ChartConfig| {
+            let mut svg = String::new();
+            {
+                let root = SVGBackend::with_string(&mut svg, (800, 600)).into_drawing_area();
+                root.fill(&WHITE).unwrap();
+                render(&df, config, root, "Users", &LegendPosition::Right).unwrap();
+            }
+            svg
+        };
+
+        let plain = draw(&config);
+        let labelled = draw(&ChartConfig {
+            data_labels: Some(true),
+            ..config.clone()
+        });
+
+        assert_eq!(
+            labelled.matches("<text").count() - plain.matches("<text").count(),
+            3
+        );
+        for value in ["12,500", "3,400", "780"] {
+            assert!(
+                labelled.contains(&format!(">{}<", value)),
+                "missing {}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_horizontal_bars_run_along_the_x_axis() {
+        let df = df! {
+            "channel" => &["Organic search from partner sites", "Direct", "Email"],
+            "users" => &[300.0, 120.0, 60.0],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Bar,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            horizontal: Some(true),
+            ..Default::default()
+        };
+
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (800, 600)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            render(&df, &config, root, "Users", &LegendPosition::Right).unwrap();
+        }
+
+        // The long category is labelled in full
+        assert!(svg.contains(">Organic search from partner sites<"));
+
+        // Bars are wider than they are tall, longest value first
+        let style = get_chart_style();
+        let bar_size = |index: usize| {
+            let color = style.get_primary_color(index);
+            let fill = format!("fill=\"#{:02X}{:02X}{:02X}\"", color.0, color.1, color.2);
+            let rect = svg
+                .split("<rect")
+                .find(|rect| rect.split("/>").next().unwrap().contains(&fill))
+                .unwrap();
+            let attribute = |name: &str| -> i32 {
+                let start = rect.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3;
+                rect[start..].split('"').next().unwrap().parse().unwrap()
+            };
+            (attribute("width"), attribute("height"))
+        };
+        let (first_width, first_height) = bar_size(0);
+        let (last_width, _) = bar_size(2);
+        assert!(first_width > first_height);
+        assert!(first_width > last_width * 4);
+    }
+
+    #[test]
+    fn test_truncate_label_respects_char_boundaries() {
+        let label = "Ünïcödé category with a very long name!!";
+        assert_eq!(label.chars().count(), 40);
+
+        let truncated = truncate_label(label, 10);
+        assert_eq!(truncated, "Ünïcödé c…");
+        assert_eq!(truncated.chars().count(), 10);
+        assert_eq!(truncate_label("Short", 10), "Short");
+    }
+
+    #[test]
+    fn test_combo_bars_follow_the_y_axis_settings() {
+        let df = df! {
+            "month" => &["Jan", "Feb", "Mar"],
+            "users_sum" => &[80.0, 120.0, 95.0],
+            "revenue_mean" => &[1.5, 2.0, 1.0],
+        }
+        .unwrap();
+        let series = vec![
+            SeriesSpec {
+                column: "users".to_string(),
+                agg: crate::spec::AggregationType::Sum,
+            },
+            SeriesSpec {
+                column: "revenue".to_string(),
+                agg: crate::spec::AggregationType::Mean,
+            },
+        ];
+        let render_svg = |config: &ChartConfig| {
+            let mut svg = String::new();
+            {
+                let root = SVGBackend::with_string(&mut svg, (600, 400)).into_drawing_area();
+                root.fill(&WHITE).unwrap();
+                render(&df, config, root, "Users", &LegendPosition::Right).unwrap();
+            }
+            svg
+        };
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Bar,
+            x: Some("month".to_string()),
+            series: Some(series),
+            ..Default::default()
+        };
+
+        // The bar axis reaches the fixed top rather than stopping just above the tallest bar
+        assert!(!render_svg(&config).contains(">200<"));
+        let fixed = ChartConfig {
+            y_max: Some(200.0),
+            ..config.clone()
+        };
+        assert!(render_svg(&fixed).contains(">200<"));
+
+        let log = ChartConfig {
+            log_scale: Some(true),
+            ..config
+        };
+        render_svg(&log);
+    }
+}
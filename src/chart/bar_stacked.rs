@@ -1,6 +1,12 @@
-use crate::render::styling::get_chart_style;
+use crate::chart::annotate::{BarLabelPlacement, draw_bar_labels};
+use crate::chart::axis::{TickCountExt, YAxisSideExt};
+use crate::chart::stack::{diverging_segments, order_groups, stack_extent, stack_segments};
+use crate::render::format::select_label_format;
+use crate::render::styling::{ChartStyle, get_chart_style_for, group_color_slots};
 use crate::spec::{ChartConfig, LegendPosition};
+use crate::warnings::warn_row_cap;
 use anyhow::{Context, Result};
+use plotters::coord::types::{RangedCoordf32, RangedCoordusize};
 use plotters::prelude::*;
 use polars::prelude::*;
 
@@ -14,7 +20,7 @@ pub fn render<DB: DrawingBackend>(
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    let style = get_chart_style();
+    let style = get_chart_style_for(config);
 
     // For stacked bars, we need both x and group_by columns
     let group_by_col = config
@@ -58,13 +64,15 @@ where
     let mut all_groups = std::collections::HashSet::new();
     let mut categories = Vec::new();
 
+    warn_row_cap("Stacked bar", df.height(), 50)?;
+
     for i in 0..df.height().min(50) {
         // Limit for performance
         if let (Ok(x_val), Ok(y_val), Ok(group_val)) =
             (x_col.get(i), y_col.get(i), group_col.get(i))
         {
-            let x_str = format!("{:?}", x_val);
-            let group_str = format!("{:?}", group_val);
+            let x_str = value_label(x_val);
+            let group_str = value_label(group_val);
             let y = extract_numeric_value(y_val).unwrap_or(0.0);
 
             category_data
@@ -83,61 +91,51 @@ where
         return Ok(());
     }
 
-    // Convert groups to sorted vector for consistent ordering
-    let mut groups: Vec<String> = all_groups.into_iter().collect();
-    groups.sort();
+    // Order groups bottom-to-top (stack_order first, then alphabetical)
+    let groups = order_groups(
+        all_groups.into_iter().collect(),
+        config.stack_order.as_deref(),
+    );
+    let (groups, slots) = shown_groups(config, groups);
 
     // Calculate stacked values for each category
-    let mut stacked_data = Vec::new();
-    for (cat_idx, category) in categories.iter().enumerate() {
-        let mut current_stack = 0.0;
-        let mut category_stacks = Vec::new();
-
-        for group in &groups {
-            let value = category_data
-                .get(category)
-                .and_then(|cat_map| cat_map.get(group))
-                .unwrap_or(&0.0);
-
-            category_stacks.push((current_stack, current_stack + value));
-            current_stack += value;
-        }
-
-        stacked_data.push((cat_idx, category_stacks));
-    }
+    let stacked_data: Vec<(usize, Vec<(f32, f32)>)> =
+        stack_segments(&categories, &groups, &category_data)
+            .into_iter()
+            .enumerate()
+            .collect();
 
-    // Find the maximum total height for scaling
-    let max_height = stacked_data
-        .iter()
-        .map(|(_, stacks)| stacks.last().map(|(_, end)| *end).unwrap_or(0.0))
-        .fold(0.0f32, f32::max);
+    // Scale to the tallest positive and deepest negative stacks
+    let (min_height, max_height) = stack_extent(stacked_data.iter().flat_map(|(_, stacks)| stacks));
 
-    if max_height == 0.0 {
+    if min_height == 0.0 && max_height == 0.0 {
         return Ok(());
     }
 
-    let y_range = 0f32..(max_height * 1.1);
+    let y_range = (min_height * 1.1)..(max_height * 1.1);
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
         .build_cartesian_2d(0usize..categories.len(), y_range)
         .context("Failed to build chart")?;
 
     chart
         .configure_mesh()
-        .y_desc(config.y.as_ref().unwrap())
-        .x_desc(config.x.as_ref().unwrap())
+        .ticks_for(config)
+        .y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .x_desc(config.axis_label(config.x.as_ref().unwrap()))
         .axis_desc_style(style.axis_desc_font())
         .label_style(style.axis_label_font())
         .draw()
         .context("Failed to draw mesh")?;
 
-    // Draw stacked bars for each group
+    // Draw stacked bars for each group, colored by its sorted position
+    let color_mode = config.color_mode.clone().unwrap_or_default();
     for (group_idx, group) in groups.iter().enumerate() {
-        let color = style.get_primary_color(group_idx);
+        let color = style.get_group_color(slots[group_idx], group, &color_mode);
 
         chart
             .draw_series(stacked_data.iter().map(|(cat_idx, stacks)| {
@@ -149,6 +147,14 @@ where
             .legend(|(x, y)| Rectangle::new([(x, y), (x + 10, y + 10)], color.filled()));
     }
 
+    if config.data_labels.unwrap_or(false) {
+        let stacks: Vec<&[(f32, f32)]> = stacked_data
+            .iter()
+            .map(|(_, stacks)| stacks.as_slice())
+            .collect();
+        draw_stack_labels(&mut chart, config, &stacks, style)?;
+    }
+
     // Legend is now handled externally
 
     root.present().context("Failed to present chart")?;
@@ -176,10 +182,12 @@ where
     let mut group_data: std::collections::HashMap<String, f32> = std::collections::HashMap::new();
     let mut groups = Vec::new();
 
+    warn_row_cap("Stacked bar", df.height(), 50)?;
+
     for i in 0..df.height().min(50) {
         // Limit for performance
         if let (Ok(group_val), Ok(value_val)) = (group_col.get(i), value_col.get(i)) {
-            let group_str = format!("{:?}", group_val);
+            let group_str = value_label(group_val);
             let value = extract_numeric_value(value_val).unwrap_or(0.0);
 
             group_data.insert(group_str.clone(), value);
@@ -193,46 +201,46 @@ where
         return Ok(());
     }
 
-    // Sort groups for consistent ordering
-    groups.sort();
+    // Order groups bottom-to-top (stack_order first, then alphabetical)
+    let groups = order_groups(groups, config.stack_order.as_deref());
+    let (groups, slots) = shown_groups(config, groups);
 
     // Calculate stacked values
-    let mut stacked_data = Vec::new();
-    let mut current_stack = 0.0;
-
-    for group in &groups {
-        let value = group_data.get(group).unwrap_or(&0.0);
-        stacked_data.push((current_stack, current_stack + value));
-        current_stack += value;
-    }
+    let values: Vec<f32> = groups
+        .iter()
+        .map(|group| group_data.get(group).copied().unwrap_or(0.0))
+        .collect();
+    let stacked_data = diverging_segments(&values);
 
-    let max_height = stacked_data.last().map(|(_, end)| *end).unwrap_or(0.0);
-    if max_height == 0.0 {
+    let (min_height, max_height) = stack_extent(&stacked_data);
+    if min_height == 0.0 && max_height == 0.0 {
         return Ok(());
     }
 
-    let y_range = 0f32..(max_height * 1.1);
+    let y_range = (min_height * 1.1)..(max_height * 1.1);
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
         .build_cartesian_2d(0usize..1, y_range)
         .context("Failed to build chart")?;
 
     chart
         .configure_mesh()
-        .y_desc(config.y.as_ref().unwrap())
-        .x_desc(group_by_col)
+        .ticks_for(config)
+        .y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .x_desc(config.axis_label(group_by_col))
         .axis_desc_style(style.axis_desc_font())
         .label_style(style.axis_label_font())
         .draw()
         .context("Failed to draw mesh")?;
 
-    // Draw stacked bars for each group
+    // Draw stacked bars for each group, colored by its sorted position
+    let color_mode = config.color_mode.clone().unwrap_or_default();
     for (group_idx, group) in groups.iter().enumerate() {
-        let color = style.get_primary_color(group_idx);
+        let color = style.get_group_color(slots[group_idx], group, &color_mode);
         let (start, end) = stacked_data[group_idx];
 
         chart
@@ -245,12 +253,72 @@ where
             .legend(|(x, y)| Rectangle::new([(x, y), (x + 10, y + 10)], color.filled()));
     }
 
+    if config.data_labels.unwrap_or(false) {
+        draw_stack_labels(&mut chart, config, &[stacked_data.as_slice()], style)?;
+    }
+
     // Legend is now handled externally
 
     root.present().context("Failed to present chart")?;
     Ok(())
 }
 
+/// The groups left once `hide_series` is applied, with the color slot each had among all of them
+fn shown_groups(config: &ChartConfig, groups: Vec<String>) -> (Vec<String>, Vec<usize>) {
+    let slots = group_color_slots(&groups);
+    groups
+        .into_iter()
+        .zip(slots)
+        .filter(|(group, _)| config.shows_series(group))
+        .unzip()
+}
+
+/// Write each non-empty segment's value in its middle, and each stack's total past its end
+fn draw_stack_labels<DB>(
+    chart: &mut ChartContext<'_, DB, Cartesian2d<RangedCoordusize, RangedCoordf32>>,
+    config: &ChartConfig,
+    stacks: &[&[(f32, f32)]],
+    style: &ChartStyle,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let values: Vec<f32> = stacks
+        .iter()
+        .flat_map(|segments| segments.iter().map(|(start, end)| end - start))
+        .collect();
+    let format = select_label_format(
+        config.y_format.as_ref(),
+        config.y.as_ref().unwrap(),
+        &values,
+    )
+    .with_separators();
+
+    let mut segments = Vec::new();
+    let mut totals = Vec::new();
+    for (slot, stack) in stacks.iter().enumerate() {
+        for (start, end) in stack.iter().filter(|(start, end)| start != end) {
+            segments.push((slot, (start + end) / 2.0, format.format(end - start)));
+        }
+
+        let total: f32 = stack.iter().map(|(start, end)| end - start).sum();
+        let (low, high) = stack_extent(stack.iter());
+        let edge = if total < 0.0 { low } else { high };
+        totals.push((slot, edge, format.format(total)));
+    }
+
+    draw_bar_labels(chart, &segments, BarLabelPlacement::Inside, style)?;
+    draw_bar_labels(chart, &totals, BarLabelPlacement::Outside, style)
+}
+
+fn value_label(value: AnyValue) -> String {
+    match value {
+        AnyValue::Utf8(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
+
 fn extract_numeric_value(value: AnyValue) -> Option<f32> {
     match value {
         AnyValue::Int32(i) => Some(i as f32),
@@ -262,3 +330,93 @@ fn extract_numeric_value(value: AnyValue) -> Option<f32> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::styling::get_chart_style;
+
+    #[test]
+    fn test_data_labels_cover_segments_and_totals() {
+        let df = df! {
+            "month" => &["Jan", "Jan", "Feb", "Feb"],
+            "channel" => &["direct", "organic", "direct", "organic"],
+            "users" => &[1200.0, 3400.0, 0.0, 2500.0],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::BarStacked,
+            x: Some("month".to_string()),
+            y: Some("users".to_string()),
+            group_by: Some("channel".to_string()),
+            ..Default::default()
+        };
+        let draw = |config: &ChartConfig| {
+            let mut svg = String::new();
+            {
+                let root = SVGBackend::with_string(&mut svg, (800, 600)).into_drawing_area();
+                root.fill(&WHITE).unwrap();
+                render(&df, config, root, "Users", &LegendPosition::Right).unwrap();
+            }
+            svg
+        };
+
+        let plain = draw(&config);
+        let labelled = draw(&ChartConfig {
+            data_labels: Some(true),
+            ..config.clone()
+        });
+
+        // Three non-empty segments and two totals
+        assert_eq!(
+            labelled.matches("<text").count() - plain.matches("<text").count(),
+            5
+        );
+        for value in ["1,200", "3,400", "4,600", "2,500"] {
+            assert!(
+                labelled.contains(&format!(">{}<", value)),
+                "missing {}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn test_spec_colors_replace_the_palette() {
+        let df = df! {
+            "month" => &["Jan", "Jan", "Jan", "Feb", "Feb", "Feb"],
+            "channel" => &["direct", "organic", "paid", "direct", "organic", "paid"],
+            "users" => &[1200.0, 3400.0, 800.0, 900.0, 2500.0, 600.0],
+        }
+        .unwrap();
+        let spec = crate::spec::ChartSpec::from_yaml(
+            r##"
+charts:
+  - type: bar-stacked
+    x: month
+    y: users
+    group_by: channel
+    colors: ["#123456", "#abcdef", "#f0a"]
+"##,
+        )
+        .unwrap();
+
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (800, 600)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            render(&df, &spec.charts[0], root, "Users", &LegendPosition::Right).unwrap();
+        }
+
+        // Groups take the custom colors in sorted order
+        for color in ["#123456", "#ABCDEF", "#FF00AA"] {
+            assert!(
+                svg.contains(&format!("fill=\"{}\"", color)),
+                "missing {}",
+                color
+            );
+        }
+        let RGBColor(r, g, b) = *get_chart_style().get_primary_color(0);
+        assert!(!svg.contains(&format!("fill=\"#{:02X}{:02X}{:02X}\"", r, g, b)));
+    }
+}
@@ -0,0 +1,299 @@
+//! Box-and-whisker plots: the spread of `y` within each `group_by` category
+use crate::chart::axis::{TickCountExt, YAxisSideExt, YCoord};
+use crate::render::format::select_label_format;
+use crate::render::styling::{get_chart_style_for, group_color_slots};
+use crate::spec::{ChartConfig, LegendPosition};
+use anyhow::{Context, Result};
+use plotters::prelude::*;
+use polars::prelude::*;
+
+/// Share of each category's slot taken by its box
+const BOX_WIDTH: f32 = 0.6;
+
+/// Share of each category's slot taken by the whisker caps
+const CAP_WIDTH: f32 = 0.3;
+
+/// Points further than this many interquartile ranges outside the box are outliers
+const WHISKER_IQR: f64 = 1.5;
+
+pub fn render<DB: DrawingBackend>(
+    df: &DataFrame,
+    config: &ChartConfig,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+    _legend_position: &LegendPosition,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    // Colors follow the sorted groups, so hiding a box doesn't recolor the others
+    let all_boxes = box_stats(df, config)?;
+    let names: Vec<String> = all_boxes.iter().map(|stats| stats.group.clone()).collect();
+    let (boxes, slots): (Vec<BoxStats>, Vec<usize>) = all_boxes
+        .into_iter()
+        .zip(group_color_slots(&names))
+        .filter(|(stats, _)| config.shows_series(&stats.group))
+        .unzip();
+    if boxes.is_empty() {
+        return Ok(());
+    }
+
+    let style = get_chart_style_for(config);
+    let y_column = config.y.as_ref().unwrap();
+
+    let y_values: Vec<f32> = boxes
+        .iter()
+        .flat_map(|stats| [stats.min as f32, stats.max as f32])
+        .collect();
+    let y_min = y_values.iter().copied().fold(f32::INFINITY, f32::min);
+    let y_max = y_values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let padding = if y_max > y_min {
+        (y_max - y_min) * 0.1
+    } else {
+        1.0
+    };
+    let y_range = YCoord::for_chart(config, (y_min - padding)..(y_max + padding), &y_values);
+    let y_labels = select_label_format(config.y_format.as_ref(), y_column, &y_values);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, style.title_font())
+        .margin(style.layout.margins.chart as i32)
+        .x_label_area_size(style.layout.areas.x_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
+        .build_cartesian_2d(0f32..boxes.len() as f32, y_range)
+        .context("Failed to build chart")?;
+
+    // Ticks fall on the slot edges, each labelled with the category to its right
+    let slot_width = chart.plotting_area().dim_in_pixel().0 / boxes.len() as u32;
+    let x_label = |v: &f32| {
+        if v.fract() != 0.0 {
+            return String::new();
+        }
+        boxes
+            .get(*v as usize)
+            .map(|stats| stats.group.clone())
+            .unwrap_or_default()
+    };
+    let mut mesh = chart.configure_mesh();
+    mesh.ticks_for(config);
+    mesh.y_desc(config.axis_label(y_column))
+        .y_label_formatter(&|v| y_labels.format(*v))
+        .x_desc(config.axis_label(config.group_by.as_ref().unwrap()))
+        .x_label_formatter(&x_label)
+        .x_label_offset(slot_width as i32 / 2)
+        .x_labels(boxes.len() + 1)
+        .disable_x_mesh()
+        .axis_desc_style(style.axis_desc_font())
+        .label_style(style.axis_label_font());
+    mesh.draw().context("Failed to draw mesh")?;
+
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    let point_size = style.layout.elements.line_points;
+
+    for (i, stats) in boxes.iter().enumerate() {
+        let color = *style.get_group_color(slots[i], &stats.group, &color_mode);
+        let center = i as f32 + 0.5;
+        let (left, right) = (center - BOX_WIDTH / 2.0, center + BOX_WIDTH / 2.0);
+        let (cap_left, cap_right) = (center - CAP_WIDTH / 2.0, center + CAP_WIDTH / 2.0);
+        let (q1, median, q3) = (stats.q1 as f32, stats.median as f32, stats.q3 as f32);
+        let (lower, upper) = (stats.lower_whisker as f32, stats.upper_whisker as f32);
+
+        chart
+            .draw_series([
+                Rectangle::new([(left, q3), (right, q1)], color.mix(0.3).filled()),
+                Rectangle::new([(left, q3), (right, q1)], color.stroke_width(2)),
+            ])
+            .context("Failed to draw box")?;
+
+        chart
+            .draw_series(
+                [
+                    vec![(center, q3), (center, upper)],
+                    vec![(center, q1), (center, lower)],
+                    vec![(cap_left, upper), (cap_right, upper)],
+                    vec![(cap_left, lower), (cap_right, lower)],
+                ]
+                .into_iter()
+                .map(|path| PathElement::new(path, color.stroke_width(2))),
+            )
+            .context("Failed to draw whiskers")?;
+
+        chart
+            .draw_series(std::iter::once(PathElement::new(
+                vec![(left, median), (right, median)],
+                color.stroke_width(3),
+            )))
+            .context("Failed to draw median")?;
+
+        chart
+            .draw_series(
+                stats
+                    .outliers
+                    .iter()
+                    .map(|v| Circle::new((center, *v as f32), point_size, color.stroke_width(1))),
+            )
+            .context("Failed to draw outliers")?;
+    }
+
+    root.present().context("Failed to present chart")?;
+    Ok(())
+}
+
+/// The five-number summary of one category, with whiskers and outliers under the 1.5×IQR rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoxStats {
+    pub group: String,
+    pub min: f64,
+    pub q1: f64,
+    pub median: f64,
+    pub q3: f64,
+    pub max: f64,
+    /// Smallest value no more than 1.5×IQR below the first quartile
+    pub lower_whisker: f64,
+    /// Largest value no more than 1.5×IQR above the third quartile
+    pub upper_whisker: f64,
+    /// Values beyond the whiskers, in row order
+    pub outliers: Vec<f64>,
+}
+
+/// Quartiles of `y` per `group_by` value, in first-seen order; missing values are left out
+pub fn box_stats(df: &DataFrame, config: &ChartConfig) -> Result<Vec<BoxStats>> {
+    let group_by = config
+        .group_by
+        .as_ref()
+        .context("Box plots require 'group_by'")?;
+    let y = config.y.as_ref().context("Box plots require 'y'")?;
+    let values = || col(y).cast(DataType::Float64);
+    let quartile = |q: f64| values().quantile(lit(q), QuantileInterpolOptions::Linear);
+
+    let summary = df
+        .clone()
+        .lazy()
+        .filter(col(y).is_not_null())
+        .group_by_stable([col(group_by)])
+        .agg([
+            values().min().alias("min"),
+            quartile(0.25).alias("q1"),
+            values().median().alias("median"),
+            quartile(0.75).alias("q3"),
+            values().max().alias("max"),
+        ])
+        .collect()
+        .context("Failed to compute box plot quartiles")?;
+
+    let stat = |name: &str, i: usize| -> Result<f64> {
+        summary
+            .column(name)?
+            .f64()?
+            .get(i)
+            .with_context(|| format!("Missing {} for box {}", name, i))
+    };
+    let groups = summary.column(group_by)?;
+    let mut boxes = Vec::with_capacity(summary.height());
+    for i in 0..summary.height() {
+        let (q1, q3) = (stat("q1", i)?, stat("q3", i)?);
+        boxes.push(BoxStats {
+            group: value_label(groups.get(i)?),
+            min: stat("min", i)?,
+            q1,
+            median: stat("median", i)?,
+            q3,
+            max: stat("max", i)?,
+            lower_whisker: q1,
+            upper_whisker: q3,
+            outliers: Vec::new(),
+        });
+    }
+
+    // Whiskers reach the furthest values inside the fences; anything beyond is an outlier
+    let group_col = df.column(group_by).context("Group column not found")?;
+    let y_col = df
+        .column(y)
+        .context("Y column not found")?
+        .cast(&DataType::Float64)?;
+    let y_values = y_col.f64()?;
+    for (i, value) in y_values.into_iter().enumerate() {
+        let Some(value) = value else {
+            continue;
+        };
+        let group = value_label(group_col.get(i)?);
+        let Some(stats) = boxes.iter_mut().find(|stats| stats.group == group) else {
+            continue;
+        };
+        let iqr = stats.q3 - stats.q1;
+        if value < stats.q1 - WHISKER_IQR * iqr || value > stats.q3 + WHISKER_IQR * iqr {
+            stats.outliers.push(value);
+        } else {
+            stats.lower_whisker = stats.lower_whisker.min(value);
+            stats.upper_whisker = stats.upper_whisker.max(value);
+        }
+    }
+
+    Ok(boxes)
+}
+
+fn value_label(value: AnyValue) -> String {
+    match value {
+        AnyValue::Utf8(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ChartConfig {
+        ChartConfig {
+            chart_type: crate::spec::ChartType::Box,
+            y: Some("load_ms".to_string()),
+            group_by: Some("page".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_box_stats_match_known_quartiles() {
+        let df = df! {
+            "page" => &["home", "cart", "home", "cart", "home", "cart", "home", "cart", "home", "cart"],
+            "load_ms" => &[1i64, 10, 2, 20, 3, 30, 4, 40, 5, 100],
+        }
+        .unwrap();
+
+        let boxes = box_stats(&df, &config()).unwrap();
+
+        assert_eq!(boxes.len(), 2);
+        let home = &boxes[0];
+        assert_eq!(home.group, "home");
+        assert_eq!(
+            (home.min, home.q1, home.median, home.q3, home.max),
+            (1.0, 2.0, 3.0, 4.0, 5.0)
+        );
+        assert_eq!((home.lower_whisker, home.upper_whisker), (1.0, 5.0));
+        assert!(home.outliers.is_empty());
+
+        // IQR 20 puts the upper fence at 70, so 100 is drawn as a point past a whisker at 40
+        let cart = &boxes[1];
+        assert_eq!(cart.group, "cart");
+        assert_eq!((cart.q1, cart.median, cart.q3), (20.0, 30.0, 40.0));
+        assert_eq!((cart.lower_whisker, cart.upper_whisker), (10.0, 40.0));
+        assert_eq!(cart.outliers, vec![100.0]);
+    }
+
+    #[test]
+    fn test_box_plot_renders() {
+        let df = df! {
+            "page" => &["home", "home", "home", "cart", "cart", "cart"],
+            "load_ms" => &[120.0, 180.0, 150.0, 300.0, 260.0, 900.0],
+        }
+        .unwrap();
+
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (600, 400)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            render(&df, &config(), root, "Load time", &LegendPosition::Right).unwrap();
+        }
+        assert!(svg.contains(">home<") && svg.contains(">cart<"));
+    }
+}
@@ -1,8 +1,10 @@
-use crate::render::styling::get_chart_style;
-use crate::spec::{ChartConfig, LegendPosition};
+use crate::render::format::LabelFormat;
+use crate::render::styling::{colormap_color, get_chart_style_for};
+use crate::spec::{ChartConfig, ColorMap, LegendPosition, NumberFormat};
 use anyhow::{Context, Result};
 use plotters::prelude::*;
 use polars::prelude::*;
+use std::collections::HashMap;
 
 pub fn render<DB: DrawingBackend>(
     df: &DataFrame,
@@ -14,67 +16,11 @@ pub fn render<DB: DrawingBackend>(
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    // For funnel charts, we need steps and values
-    let steps = config
-        .steps
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Funnel charts require a 'steps' field"))?;
-    let values_col = config
-        .values
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Funnel charts require a 'values' field"))?;
-
-    let values_col_data = df.column(values_col).context("Values column not found")?;
-
-    // Extract values for each step
-    let mut step_values = Vec::new();
-    for (step_idx, step) in steps.iter().enumerate() {
-        if step_idx < df.height()
-            && let Ok(value) = values_col_data.get(step_idx)
-        {
-            let numeric_value = extract_numeric_value(value).unwrap_or(0.0);
-            step_values.push((step.clone(), numeric_value));
-        }
-    }
-
-    if step_values.is_empty() {
+    let ordered_step_values = ordered_step_values(df, config)?;
+    if ordered_step_values.is_empty() {
         return Ok(());
     }
 
-    // Apply step ordering if specified
-    let ordered_step_values = if let Some(step_order) = &config.step_order {
-        // Validate step order
-        if step_order.len() != step_values.len() {
-            anyhow::bail!(
-                "Step order length ({}) must match number of steps ({})",
-                step_order.len(),
-                step_values.len()
-            );
-        }
-
-        // Check for valid indices
-        for &idx in step_order {
-            if idx >= step_values.len() {
-                anyhow::bail!(
-                    "Invalid step order index: {} (max: {})",
-                    idx,
-                    step_values.len() - 1
-                );
-            }
-        }
-
-        // Reorder steps according to step_order
-        step_order
-            .iter()
-            .map(|&idx| step_values[idx].clone())
-            .collect()
-    } else {
-        // Default order: largest value first (top of funnel)
-        let mut sorted = step_values.clone();
-        sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        sorted
-    };
-
     // Find the maximum value for scaling
     let max_value = ordered_step_values
         .iter()
@@ -84,7 +30,8 @@ where
         return Ok(());
     }
 
-    let style = get_chart_style();
+    let style = get_chart_style_for(config);
+    let value_format = funnel_value_format(config);
 
     // Fill background with white (no grid/axes needed for funnel)
     root.fill(&WHITE).context("Failed to fill background")?;
@@ -108,8 +55,20 @@ where
     let num_steps = ordered_step_values.len();
     let segment_height = funnel_height / num_steps as u32;
 
+    let colors: Vec<RGBColor> = if config.highlight_dropoff.unwrap_or(false) {
+        let values: Vec<f32> = ordered_step_values
+            .iter()
+            .map(|(_, value)| *value)
+            .collect();
+        dropoff_colors(&values)
+    } else {
+        (0..num_steps)
+            .map(|step_idx| *style.get_primary_color(step_idx))
+            .collect()
+    };
+
     for (step_idx, (step_name, value)) in ordered_step_values.iter().enumerate() {
-        let color = style.get_primary_color(step_idx);
+        let color = colors[step_idx];
 
         // Calculate segment dimensions (top to bottom)
         let segment_y_start = funnel_start_y + (step_idx as u32 * segment_height);
@@ -132,7 +91,7 @@ where
         .context("Failed to draw funnel segment")?;
 
         // Draw step label based on value_labels position
-        let label_text = format!("{}: {:.0}", step_name, value);
+        let label_text = step_label(step_name, *value, &value_format);
         let (text_x, text_y) = match config
             .value_labels
             .as_ref()
@@ -165,6 +124,163 @@ where
     Ok(())
 }
 
+/// A red per step, deeper the more of the previous step's users it lost; the largest drop
+/// gets the deepest red and the first step (nothing to lose) the palest
+fn dropoff_colors(values: &[f32]) -> Vec<RGBColor> {
+    let drops: Vec<f32> = values
+        .iter()
+        .enumerate()
+        .map(
+            |(i, value)| match i.checked_sub(1).map(|previous| values[previous]) {
+                Some(previous) if previous > 0.0 => ((previous - value) / previous).clamp(0.0, 1.0),
+                _ => 0.0,
+            },
+        )
+        .collect();
+    let largest = drops.iter().copied().fold(0.0f32, f32::max);
+
+    drops
+        .iter()
+        .map(|&drop| {
+            let severity = if largest > 0.0 { drop / largest } else { 0.0 };
+            // Keep the palest steps visible against the white background
+            colormap_color(&ColorMap::Reds, 0.2 + 0.8 * severity)
+        })
+        .collect()
+}
+
+/// Each step's value in the order the funnel draws them, top to bottom
+pub fn ordered_step_values(df: &DataFrame, config: &ChartConfig) -> Result<Vec<(String, f32)>> {
+    // For funnel charts, we need steps and values
+    let steps = config
+        .steps
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Funnel charts require a 'steps' field"))?;
+
+    // Extract values for each step
+    let step_values = match (&config.step_column, &config.user_column) {
+        (Some(step_col), Some(user_col)) => step_values_by_name(df, steps, step_col, user_col)?,
+        (step_col, _) => {
+            let values_col = config
+                .values
+                .as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Funnel charts require a 'values' field"))?;
+            match step_col {
+                // Rows carry their step name, so they can appear in any order
+                Some(step_col) => step_values_by_name(df, steps, step_col, values_col)?,
+                None => step_values_by_position(df, steps, values_col)?,
+            }
+        }
+    };
+
+    if step_values.is_empty() {
+        return Ok(step_values);
+    }
+
+    // Apply step ordering if specified
+    if let Some(step_order) = &config.step_order {
+        // Validate step order
+        if step_order.len() != step_values.len() {
+            anyhow::bail!(
+                "Step order length ({}) must match number of steps ({})",
+                step_order.len(),
+                step_values.len()
+            );
+        }
+
+        // Check for valid indices
+        for &idx in step_order {
+            if idx >= step_values.len() {
+                anyhow::bail!(
+                    "Invalid step order index: {} (max: {})",
+                    idx,
+                    step_values.len() - 1
+                );
+            }
+        }
+
+        // Reorder steps according to step_order
+        Ok(step_order
+            .iter()
+            .map(|&idx| step_values[idx].clone())
+            .collect())
+    } else {
+        // Default order: largest value first (top of funnel)
+        let mut sorted = step_values;
+        sorted.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(sorted)
+    }
+}
+
+/// Funnel values are counts, so they get thousands separators unless `y_format` says otherwise
+fn funnel_value_format(config: &ChartConfig) -> LabelFormat {
+    LabelFormat {
+        format: config.y_format.clone().unwrap_or(NumberFormat::Thousands),
+        fraction: false,
+    }
+}
+
+/// Label drawn beside a funnel segment, e.g. "Signup: 1,200,000"
+fn step_label(step_name: &str, value: f32, format: &LabelFormat) -> String {
+    format!("{}: {}", step_name, format.format(value))
+}
+
+/// Count distinct users per step from raw event rows
+///
+/// The result has one row per step, with the count stored under `user_col`.
+pub fn count_step_users(lf: LazyFrame, step_col: &str, user_col: &str) -> LazyFrame {
+    lf.group_by([col(step_col)])
+        .agg([col(user_col).n_unique().alias(user_col)])
+}
+
+/// Read one pre-aggregated value per step, aligned by row
+fn step_values_by_position(
+    df: &DataFrame,
+    steps: &[String],
+    values_col: &str,
+) -> Result<Vec<(String, f32)>> {
+    let values_col_data = df.column(values_col).context("Values column not found")?;
+
+    let mut step_values = Vec::new();
+    for (step_idx, step) in steps.iter().enumerate() {
+        if step_idx < df.height()
+            && let Ok(value) = values_col_data.get(step_idx)
+        {
+            let numeric_value = extract_numeric_value(value).unwrap_or(0.0);
+            step_values.push((step.clone(), numeric_value));
+        }
+    }
+
+    Ok(step_values)
+}
+
+/// Look up each step's value by name, either in per-step rows from `count_step_users`
+/// or in a values column labelled by `step_col`; rows sharing a step name are summed
+fn step_values_by_name(
+    df: &DataFrame,
+    steps: &[String],
+    step_col: &str,
+    count_col: &str,
+) -> Result<Vec<(String, f32)>> {
+    let step_data = df.column(step_col).context("Step column not found")?;
+    let count_data = df
+        .column(count_col)
+        .with_context(|| format!("Column '{}' not found", count_col))?;
+
+    let mut counts = HashMap::new();
+    for i in 0..df.height() {
+        if let (Ok(AnyValue::Utf8(step)), Ok(count)) = (step_data.get(i), count_data.get(i)) {
+            *counts.entry(step.to_string()).or_insert(0.0) +=
+                extract_numeric_value(count).unwrap_or(0.0);
+        }
+    }
+
+    Ok(steps
+        .iter()
+        .map(|step| (step.clone(), counts.get(step).copied().unwrap_or(0.0)))
+        .collect())
+}
+
 fn extract_numeric_value(value: AnyValue) -> Option<f32> {
     match value {
         AnyValue::Int32(i) => Some(i as f32),
@@ -176,3 +292,90 @@ fn extract_numeric_value(value: AnyValue) -> Option<f32> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_step_users_from_raw_events() {
+        let events = df! {
+            "user_id" => &["u1", "u1", "u1", "u2", "u2", "u3", "u3", "u1"],
+            "event" => &["visit", "signup", "purchase", "visit", "signup", "visit", "visit", "visit"],
+        }
+        .unwrap();
+
+        let counted = count_step_users(events.lazy(), "event", "user_id")
+            .collect()
+            .unwrap();
+        let steps: Vec<String> = ["visit", "signup", "purchase"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let step_values = step_values_by_name(&counted, &steps, "event", "user_id").unwrap();
+
+        assert_eq!(
+            step_values,
+            vec![
+                ("visit".to_string(), 3.0),
+                ("signup".to_string(), 2.0),
+                ("purchase".to_string(), 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_largest_dropoff_gets_most_intense_color() {
+        // Losses: 20%, 75%, 10%
+        let colors = dropoff_colors(&[1000.0, 800.0, 200.0, 180.0]);
+        let brightness = |color: &RGBColor| color.0 as u32 + color.1 as u32 + color.2 as u32;
+
+        let darkest = (0..colors.len())
+            .min_by_key(|&i| brightness(&colors[i]))
+            .unwrap();
+        assert_eq!(darkest, 2);
+        // A small drop is paler than a large one
+        assert!(brightness(&colors[3]) > brightness(&colors[1]));
+    }
+
+    #[test]
+    fn test_step_values_matched_by_label() {
+        let df = df! {
+            "step" => &["purchase", "visit", "signup"],
+            "value" => &[120i64, 5000, 900],
+        }
+        .unwrap();
+        let steps: Vec<String> = ["visit", "signup", "purchase"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let step_values = step_values_by_name(&df, &steps, "step", "value").unwrap();
+
+        assert_eq!(
+            step_values,
+            vec![
+                ("visit".to_string(), 5000.0),
+                ("signup".to_string(), 900.0),
+                ("purchase".to_string(), 120.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_step_label_groups_thousands() {
+        let config = ChartConfig::default();
+        let label = step_label("Visit", 1_200_000.0, &funnel_value_format(&config));
+        assert_eq!(label, "Visit: 1,200,000");
+    }
+
+    #[test]
+    fn test_step_label_respects_y_format() {
+        let config = ChartConfig {
+            y_format: Some(NumberFormat::SiCompact),
+            ..Default::default()
+        };
+        let label = step_label("Visit", 1_200_000.0, &funnel_value_format(&config));
+        assert_eq!(label, "Visit: 1.2M");
+    }
+}
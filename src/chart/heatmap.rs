@@ -1,8 +1,14 @@
-use crate::render::styling::{get_chart_style, get_heatmap_style};
-use crate::spec::{ChartConfig, LegendPosition};
+use crate::chart::axis::YAxisSideExt;
+use crate::render::styling::{ChartStyle, color_positions, colormap_color, get_chart_style_for};
+use crate::spec::{ChartConfig, ColorMap, LegendPosition};
+use crate::warnings::warn_row_cap;
 use anyhow::{Context, Result};
 use plotters::prelude::*;
 use polars::prelude::*;
+use std::collections::HashMap;
+
+/// Most distinct values an integer z can have and still be colored as categories
+const MAX_INTEGER_CATEGORIES: usize = 6;
 
 pub fn render<DB: DrawingBackend>(
     df: &DataFrame,
@@ -20,88 +26,289 @@ where
         return crate::chart::bar::render(df, config, root, title, legend_position);
     }
 
-    let x_col = df
-        .column(config.x.as_ref().unwrap())
-        .context("X column not found")?;
-    let y_col = df
-        .column(config.y.as_ref().unwrap())
-        .context("Y column not found")?;
-    let z_col = df
-        .column(config.z.as_ref().unwrap())
-        .context("Z column not found")?;
-
-    // For now, create a simplified heatmap using rectangles
-    // In a full implementation, we'd create a proper grid
-    let mut data_points = Vec::new();
+    warn_row_cap("Heatmap", df.height(), 100)?;
 
-    for i in 0..df.height().min(100) {
-        // Limit for performance
-        if let (Ok(_x_val), Ok(_y_val), Ok(z_val)) = (x_col.get(i), y_col.get(i), z_col.get(i))
-            && let Some(z_value) = extract_numeric_value(z_val)
-        {
-            data_points.push((i, i, z_value)); // Simple mapping for now
-        }
-    }
-
-    if data_points.is_empty() {
+    let Some(HeatmapGrid { grid, categories }) = heatmap_grid(df, config)? else {
         return Ok(());
-    }
+    };
 
-    let z_max = data_points
-        .iter()
-        .map(|(_, _, z)| *z)
-        .fold(0.0f32, f32::max);
-    let z_min = data_points.iter().map(|(_, _, z)| *z).fold(z_max, f32::min);
+    let style = get_chart_style_for(config);
+    let cell_colors = match &categories {
+        Some(categories) => category_colors(&grid, categories, config, &style),
+        None => gradient_colors(&grid, config),
+    };
 
-    let style = get_chart_style();
-    let heatmap_style = get_heatmap_style();
+    // Margins add a column on the right for row totals and a row below for column totals;
+    // totals only make sense for numeric z
+    let margins = config.margins.unwrap_or(false) && categories.is_none();
+    let columns = grid.columns.len() as f32;
+    let rows = grid.rows.len() as f32;
+    let x_range = 0f32..if margins { columns + 1.0 } else { columns };
+    let y_range = if margins { -1f32 } else { 0f32 }..rows;
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
-        .build_cartesian_2d(0usize..data_points.len(), 0usize..data_points.len())
+        .y_label_area_for(config, style.layout.areas.y_label_area)
+        .build_cartesian_2d(x_range, y_range)
         .context("Failed to build chart")?;
 
     chart
         .configure_mesh()
-        .y_desc(config.y.as_ref().unwrap())
-        .x_desc(config.x.as_ref().unwrap())
+        .y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .x_desc(config.axis_label(config.x.as_ref().unwrap()))
+        .x_labels(grid.columns.len() + 1)
+        .y_labels(grid.rows.len() + 1)
+        .x_label_formatter(&|v| grid_label(&grid.columns, *v))
+        .y_label_formatter(&|v| grid_label(&grid.rows, *v))
         .axis_desc_style(style.axis_desc_font())
         .label_style(style.axis_label_font())
         .draw()
         .context("Failed to draw mesh")?;
 
-    // Draw heatmap rectangles with neutral color intensity based on z value
     chart
-        .draw_series(data_points.iter().enumerate().map(|(i, (_, _, z))| {
-            let intensity = if z_max > z_min {
-                (z - z_min) / (z_max - z_min)
-            } else {
-                0.5
-            };
-            // Use the styled gradient colors
-            let (_min_color, _max_color) = heatmap_style.gradient_colors;
-            let base_color = heatmap_style.intensity_range.0
-                + (intensity * (heatmap_style.intensity_range.1 - heatmap_style.intensity_range.0));
-            let color = RGBColor(
-                base_color as u8,
-                (base_color * 1.1) as u8,
-                (base_color * 1.2) as u8,
-            );
-            Rectangle::new([(i, i), (i + 1, i + 1)], color.filled())
-        }))
+        .draw_series(
+            grid.cells
+                .iter()
+                .zip(&cell_colors)
+                .map(|(&(column, row, _), color)| {
+                    let (x, y) = (column as f32, row as f32);
+                    Rectangle::new([(x, y), (x + 1.0, y + 1.0)], color.filled())
+                }),
+        )
         .context("Failed to draw heatmap series")?
         .label(config.z.as_ref().unwrap())
         .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], style.get_primary_color(0)));
 
+    if margins {
+        let (row_totals, column_totals) = marginal_totals(&grid);
+        let row_max = row_totals
+            .iter()
+            .copied()
+            .fold(0.0f32, f32::max)
+            .max(f32::EPSILON);
+        let column_max = column_totals
+            .iter()
+            .copied()
+            .fold(0.0f32, f32::max)
+            .max(f32::EPSILON);
+        let bar_color = style.get_primary_color(0);
+
+        // Row totals grow rightwards from the grid edge
+        chart
+            .draw_series(row_totals.iter().enumerate().map(|(row, total)| {
+                let (x, y) = (columns + 0.05, row as f32);
+                let length = 0.9 * total / row_max;
+                Rectangle::new([(x, y + 0.15), (x + length, y + 0.85)], bar_color.filled())
+            }))
+            .context("Failed to draw row totals")?;
+
+        // Column totals grow downwards from the grid edge
+        chart
+            .draw_series(column_totals.iter().enumerate().map(|(column, total)| {
+                let (x, y) = (column as f32, -0.05);
+                let length = 0.9 * total / column_max;
+                Rectangle::new([(x + 0.15, y - length), (x + 0.85, y)], bar_color.filled())
+            }))
+            .context("Failed to draw column totals")?;
+    }
+
     // Legend is now handled externally
 
     root.present().context("Failed to present chart")?;
     Ok(())
 }
 
+/// The cells a heatmap draws, and the z categories their values index when z is discrete
+pub struct HeatmapGrid {
+    pub grid: Grid,
+    /// Discrete z values, in first-seen order; `None` for continuous z
+    pub categories: Option<Vec<String>>,
+}
+
+/// Sum z into a grid of distinct x (columns) by distinct y (rows), from the first 100 rows;
+/// categorical cells hold the category index instead. `None` when no row has a usable z
+pub fn heatmap_grid(df: &DataFrame, config: &ChartConfig) -> Result<Option<HeatmapGrid>> {
+    let x_col = df
+        .column(config.x.as_ref().unwrap())
+        .context("X column not found")?;
+    let y_col = df
+        .column(config.y.as_ref().unwrap())
+        .context("Y column not found")?;
+    let z_col = df
+        .column(config.z.as_ref().unwrap())
+        .context("Z column not found")?;
+
+    // Discrete z (status codes, labels) gets one color per category instead of a gradient
+    let categories = z_categories(z_col);
+
+    let mut entries = Vec::new();
+    for i in 0..df.height().min(100) {
+        // Limit for performance
+        if let (Ok(x_val), Ok(y_val), Ok(z_val)) = (x_col.get(i), y_col.get(i), z_col.get(i))
+            && let Some(z_value) = match &categories {
+                Some(categories) => category_index(categories, z_val),
+                None => extract_numeric_value(z_val),
+            }
+        {
+            entries.push((value_label(x_val), value_label(y_val), z_value));
+        }
+    }
+
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    // A repeated (x, y) pair keeps its last category, or sums its numeric z
+    let grid = match &categories {
+        Some(_) => build_grid(entries, |_, z| z),
+        None => build_grid(entries, |total, z| total + z),
+    };
+    Ok(Some(HeatmapGrid { grid, categories }))
+}
+
+/// Each cell's z value placed along the chart's colormap [default: viridis]
+fn gradient_colors(grid: &Grid, config: &ChartConfig) -> Vec<RGBColor> {
+    let colormap = config.colormap.as_ref().unwrap_or(&ColorMap::Viridis);
+    let z_values: Vec<f32> = grid.cells.iter().map(|(_, _, z)| *z).collect();
+    let positions = color_positions(&z_values, &config.color_scale.clone().unwrap_or_default());
+
+    positions
+        .iter()
+        .map(|&intensity| colormap_color(colormap, intensity))
+        .collect()
+}
+
+/// One palette color per category, matching the swatches in the external legend
+fn category_colors(
+    grid: &Grid,
+    categories: &[String],
+    config: &ChartConfig,
+    style: &ChartStyle,
+) -> Vec<RGBColor> {
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    grid.cells
+        .iter()
+        .map(|&(_, _, z)| {
+            let index = z as usize;
+            *style.get_group_color(index, &categories[index], &color_mode)
+        })
+        .collect()
+}
+
+/// Distinct z values, in first-seen order, when z is discrete: non-numeric, or integers
+/// with only a handful of distinct values (e.g. status codes). `None` for continuous z
+pub fn z_categories(z_col: &Series) -> Option<Vec<String>> {
+    let integer = match z_col.dtype() {
+        DataType::Float32 | DataType::Float64 => return None,
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => true,
+        _ => false,
+    };
+
+    let mut categories: Vec<String> = Vec::new();
+    for i in 0..z_col.len().min(100) {
+        if let Ok(value) = z_col.get(i)
+            && !matches!(value, AnyValue::Null)
+        {
+            position_of(&mut categories, value_label(value));
+        }
+    }
+
+    if integer && categories.len() > MAX_INTEGER_CATEGORIES {
+        None
+    } else {
+        Some(categories)
+    }
+}
+
+fn category_index(categories: &[String], value: AnyValue) -> Option<f32> {
+    let label = value_label(value);
+    categories
+        .iter()
+        .position(|category| *category == label)
+        .map(|index| index as f32)
+}
+
+/// Cross-tab of z by distinct x (columns) and y (rows), in first-seen order
+pub struct Grid {
+    pub columns: Vec<String>,
+    pub rows: Vec<String>,
+    /// (column index, row index, summed z) for each non-empty cell
+    pub cells: Vec<(usize, usize, f32)>,
+}
+
+/// `combine` folds a repeated (x, y) pair's z into the cell's current value
+fn build_grid(entries: Vec<(String, String, f32)>, combine: impl Fn(f32, f32) -> f32) -> Grid {
+    let mut columns: Vec<String> = Vec::new();
+    let mut rows: Vec<String> = Vec::new();
+    let mut sums: HashMap<(usize, usize), f32> = HashMap::new();
+
+    for (x, y, z) in entries {
+        let column = position_of(&mut columns, x);
+        let row = position_of(&mut rows, y);
+        sums.entry((column, row))
+            .and_modify(|cell| *cell = combine(*cell, z))
+            .or_insert(z);
+    }
+
+    let mut cells: Vec<(usize, usize, f32)> = sums
+        .into_iter()
+        .map(|((column, row), z)| (column, row, z))
+        .collect();
+    cells.sort_by_key(|&(column, row, _)| (row, column));
+
+    Grid {
+        columns,
+        rows,
+        cells,
+    }
+}
+
+/// Index of `value` in `values`, appending it if it is new
+fn position_of(values: &mut Vec<String>, value: String) -> usize {
+    match values.iter().position(|v| *v == value) {
+        Some(index) => index,
+        None => {
+            values.push(value);
+            values.len() - 1
+        }
+    }
+}
+
+/// Row totals (z summed across each row) and column totals (z summed down each column)
+fn marginal_totals(grid: &Grid) -> (Vec<f32>, Vec<f32>) {
+    let mut row_totals = vec![0.0; grid.rows.len()];
+    let mut column_totals = vec![0.0; grid.columns.len()];
+    for &(column, row, z) in &grid.cells {
+        row_totals[row] += z;
+        column_totals[column] += z;
+    }
+    (row_totals, column_totals)
+}
+
+/// Axis label for a grid line: the name of the cell that starts there
+fn grid_label(names: &[String], position: f32) -> String {
+    if position < 0.0 || position.fract() != 0.0 {
+        return String::new();
+    }
+    names.get(position as usize).cloned().unwrap_or_default()
+}
+
+fn value_label(value: AnyValue) -> String {
+    match value {
+        AnyValue::Utf8(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
+
 fn extract_numeric_value(value: AnyValue) -> Option<f32> {
     match value {
         AnyValue::Int32(i) => Some(i as f32),
@@ -113,3 +320,97 @@ fn extract_numeric_value(value: AnyValue) -> Option<f32> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::styling::get_chart_style;
+
+    #[test]
+    fn test_marginal_totals_sum_rows_and_columns() {
+        let entries = vec![
+            ("mon".to_string(), "am".to_string(), 1.0),
+            ("tue".to_string(), "am".to_string(), 2.0),
+            ("mon".to_string(), "pm".to_string(), 3.0),
+            ("tue".to_string(), "pm".to_string(), 4.0),
+            ("mon".to_string(), "am".to_string(), 5.0),
+        ];
+        let grid = build_grid(entries, |total, z| total + z);
+        assert_eq!(grid.columns, vec!["mon", "tue"]);
+        assert_eq!(grid.rows, vec!["am", "pm"]);
+
+        let (row_totals, column_totals) = marginal_totals(&grid);
+        assert_eq!(row_totals, vec![8.0, 7.0]); // am: 1 + 2 + 5, pm: 3 + 4
+        assert_eq!(column_totals, vec![9.0, 6.0]); // mon: 1 + 3 + 5, tue: 2 + 4
+    }
+
+    #[test]
+    fn test_three_by_three_pairs_fill_nine_cells() {
+        let mut entries = Vec::new();
+        for (column, x) in ["mon", "tue", "wed"].iter().enumerate() {
+            for (row, y) in ["am", "noon", "pm"].iter().enumerate() {
+                entries.push((x.to_string(), y.to_string(), (column * 3 + row) as f32));
+            }
+        }
+        let grid = build_grid(entries, |total, z| total + z);
+
+        assert_eq!(grid.columns, vec!["mon", "tue", "wed"]);
+        assert_eq!(grid.rows, vec!["am", "noon", "pm"]);
+        let mut positions: Vec<(usize, usize)> = grid
+            .cells
+            .iter()
+            .map(|&(column, row, _)| (column, row))
+            .collect();
+        positions.dedup();
+        assert_eq!(positions.len(), 9);
+        // Each cell holds the z of its own (x, y) pair, not the row index
+        assert!(grid.cells.contains(&(2, 0, 6.0)));
+        assert!(grid.cells.contains(&(0, 2, 2.0)));
+    }
+
+    #[test]
+    fn test_categorical_z_gets_distinct_colors() {
+        let df = df! {
+            "x" => &["a", "b", "c", "a"],
+            "y" => &["r1", "r1", "r2", "r2"],
+            "status" => &["ok", "warn", "error", "ok"],
+        }
+        .unwrap();
+        let categories = z_categories(df.column("status").unwrap()).unwrap();
+        assert_eq!(categories, vec!["ok", "warn", "error"]);
+
+        let entries = (0..df.height())
+            .map(|i| {
+                let x = value_label(df.column("x").unwrap().get(i).unwrap());
+                let y = value_label(df.column("y").unwrap().get(i).unwrap());
+                let z = category_index(&categories, df.column("status").unwrap().get(i).unwrap());
+                (x, y, z.unwrap())
+            })
+            .collect();
+        let grid = build_grid(entries, |_, z| z);
+        let colors = category_colors(
+            &grid,
+            &categories,
+            &ChartConfig::default(),
+            &get_chart_style(),
+        );
+
+        let mut distinct: Vec<RGBColor> = Vec::new();
+        for color in colors {
+            if !distinct.contains(&color) {
+                distinct.push(color);
+            }
+        }
+        assert_eq!(distinct.len(), 3);
+    }
+
+    #[test]
+    fn test_continuous_z_is_not_categorical() {
+        let floats = Series::new("z", &[1.5f64, 2.0, 3.25]);
+        assert_eq!(z_categories(&floats), None);
+        let counts = Series::new("z", &(1..=20).collect::<Vec<i64>>());
+        assert_eq!(z_categories(&counts), None);
+        let codes = Series::new("z", &[200i64, 404, 500, 200]);
+        assert_eq!(z_categories(&codes).unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::render::styling::ChartStyle;
+use anyhow::{Context, Result};
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use polars::prelude::*;
+
+/// Extra x label area height that makes room for the `x2` row
+pub const X2_LABEL_AREA: u32 = 35;
+
+/// Distance from the bottom of the plot to the `x2` brackets, clearing the primary tick labels
+const X2_BRACKET_OFFSET: i32 = 28;
+
+/// A run of consecutive points sharing the same `x2` value
+#[derive(Debug, Clone, PartialEq)]
+pub struct X2Group {
+    pub label: String,
+    pub first: usize,
+    pub last: usize,
+}
+
+/// Display labels for the first `limit` values of a column
+pub fn column_labels(df: &DataFrame, column: &str, limit: usize) -> Result<Vec<String>> {
+    let series = df
+        .column(column)
+        .with_context(|| format!("Column '{}' not found", column))?;
+    Ok((0..series.len().min(limit))
+        .map(|i| match series.get(i) {
+            Ok(AnyValue::Utf8(s)) => s.to_string(),
+            Ok(AnyValue::Null) | Err(_) => String::new(),
+            Ok(other) => other.to_string(),
+        })
+        .collect())
+}
+
+/// Group consecutive equal labels, e.g. Q1 Q1 Q2 -> [Q1: 0..=1, Q2: 2..=2]
+pub fn x2_groups(labels: &[String]) -> Vec<X2Group> {
+    let mut groups: Vec<X2Group> = Vec::new();
+    for (index, label) in labels.iter().enumerate() {
+        match groups.last_mut() {
+            Some(group) if group.label == *label => group.last = index,
+            _ => groups.push(X2Group {
+                label: label.clone(),
+                first: index,
+                last: index,
+            }),
+        }
+    }
+    groups
+}
+
+/// Draw each group as a bracket beneath the x tick labels, with its label centered below
+///
+/// `spans` holds each group's label and left/right backend pixel; `plot_bottom` is the
+/// backend pixel row of the plot's bottom edge.
+pub fn draw_x2_labels<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    spans: &[(String, i32, i32)],
+    plot_bottom: i32,
+    style: &ChartStyle,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let (base_x, base_y) = root.get_base_pixel();
+    let y = plot_bottom - base_y + X2_BRACKET_OFFSET;
+    let line_color = &style.colors.text.axis_labels;
+    let label_style = style
+        .axis_label_font()
+        .pos(Pos::new(HPos::Center, VPos::Top));
+
+    for (label, left, right) in spans {
+        let (left, right) = (left - base_x + 2, right - base_x - 2);
+        root.draw(&PathElement::new(
+            vec![(left, y - 5), (left, y), (right, y), (right, y - 5)],
+            line_color,
+        ))
+        .context("Failed to draw x2 bracket")?;
+        root.draw_text(label, &label_style, ((left + right) / 2, y + 4))
+            .context("Failed to draw x2 label")?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_x2_groups_merge_consecutive_labels() {
+        let labels: Vec<String> = ["Q1", "Q1", "Q1", "Q2", "Q2"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            x2_groups(&labels),
+            vec![
+                X2Group {
+                    label: "Q1".to_string(),
+                    first: 0,
+                    last: 2,
+                },
+                X2Group {
+                    label: "Q2".to_string(),
+                    first: 3,
+                    last: 4,
+                },
+            ]
+        );
+    }
+}
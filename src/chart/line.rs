@@ -1,4 +1,9 @@
-use crate::render::styling::get_chart_style;
+use crate::chart::annotate::{draw_endpoint_labels, draw_extremes};
+use crate::chart::axis::{TickCountExt, YAxisSideExt, YCoord, drop_non_positive};
+use crate::chart::hierarchy::{X2_LABEL_AREA, column_labels, draw_x2_labels, x2_groups};
+use crate::chart::rolling::rolling_band;
+use crate::render::format::select_label_format;
+use crate::render::styling::get_chart_style_for;
 use crate::spec::{ChartConfig, LegendPosition};
 use anyhow::{Context, Result};
 use plotters::prelude::*;
@@ -14,11 +19,16 @@ pub fn render<DB: DrawingBackend>(
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    let style = get_chart_style();
+    let style = get_chart_style_for(config);
 
-    // Check if we have grouped data
-    if let Some(group_by) = &config.group_by {
-        render_grouped_line_chart(df, config, root, title, group_by, &style)
+    // Grouped rows and wide `ys` columns both draw one line per series
+    if config.group_by.is_some() || config.ys.is_some() {
+        if config.rolling_band.is_some() {
+            crate::warnings::warn(
+                "'rolling_band' is only drawn on line charts without group_by or ys",
+            )?;
+        }
+        render_grouped_line_chart(df, config, root, title, &style)
     } else {
         render_simple_line_chart(df, config, root, title, &style)
     }
@@ -42,41 +52,143 @@ where
         .column(config.y.as_ref().unwrap())
         .context("Y column not found")?;
 
+    // Numeric x columns are plotted at their values, in x order; categories (and x2 brackets,
+    // which are laid out per row) fall back to the row index
+    let x_values = match &config.x2 {
+        Some(_) => None,
+        None => numeric_x_values(x_col),
+    };
+    let mut rows: Vec<usize> = (0..df.height()).collect();
+    if let Some(xs) = &x_values {
+        rows.sort_by(|&a, &b| xs[a].total_cmp(&xs[b]));
+    }
+    let xs: Vec<f32> = rows
+        .iter()
+        .map(|&i| x_values.as_ref().map_or(i as f32, |values| values[i]))
+        .collect();
+
     // Convert to vectors for plotting
     let mut data_points = Vec::new();
-    for i in 0..df.height() {
-        if let (Ok(_x_val), Ok(y_val)) = (x_col.get(i), y_col.get(i)) {
-            // Simple approach: use index as x if not numeric, otherwise try to extract numeric
-            let x = i as f32;
+    for (&i, &x) in rows.iter().zip(&xs) {
+        if let Ok(y_val) = y_col.get(i) {
             let y = extract_numeric_value(y_val).unwrap_or(0.0);
             data_points.push((x, y));
         }
     }
+    drop_non_positive(config, &mut data_points)?;
 
     if data_points.is_empty() {
         return Ok(()); // Nothing to plot
     }
 
-    let x_range = 0f32..data_points.len() as f32;
-    let y_max = data_points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max);
-    let y_range = 0f32..(y_max * 1.1); // Add 10% padding
+    let band = match (&config.y_lower, &config.y_upper) {
+        (Some(lower), Some(upper)) => {
+            let (lower, upper) = (column_values(df, lower)?, column_values(df, upper)?);
+            let lower: Vec<_> = rows.iter().map(|&i| lower[i]).collect();
+            let upper: Vec<_> = rows.iter().map(|&i| upper[i]).collect();
+            band_polygons(&xs, &lower, &upper)
+        }
+        _ => Vec::new(),
+    };
+    let (rolling_mean, rolling_area) = match &config.rolling_band {
+        Some(band) => rolling_band_shapes(&data_points, band)?,
+        None => (Vec::new(), Vec::new()),
+    };
+
+    let x_range = match &x_values {
+        Some(_) => x_domain(&xs),
+        None => 0f32..data_points.len() as f32,
+    };
+    let y_max = data_points
+        .iter()
+        .chain(band.iter().flatten())
+        .chain(rolling_area.iter().flatten())
+        .map(|(_, y)| *y)
+        .fold(0.0f32, f32::max);
+    let y_values: Vec<f32> = data_points.iter().map(|(_, y)| *y).collect();
+    let y_range = YCoord::for_chart(config, 0f32..(y_max * 1.1), &y_values); // Add 10% padding
+    let baseline = y_range.baseline();
+    let y_labels = select_label_format(
+        config.y_format.as_ref(),
+        config.y.as_ref().unwrap(),
+        &y_values,
+    );
+
+    // With x2, points are labelled by their x value and grouped under x2 brackets
+    let x2_labels = match &config.x2 {
+        Some(x2) => Some(column_labels(df, x2, data_points.len())?),
+        None => None,
+    };
+    let x_labels = column_labels(df, config.x.as_ref().unwrap(), data_points.len())?;
+    let x_label_area = style.layout.areas.x_label_area
+        + if x2_labels.is_some() {
+            X2_LABEL_AREA
+        } else {
+            0
+        };
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
-        .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
+        .x_label_area_size(x_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
         .build_cartesian_2d(x_range, y_range)
         .context("Failed to build chart")?;
 
-    chart
-        .configure_mesh()
-        .x_desc(config.x.as_ref().unwrap())
-        .y_desc(config.y.as_ref().unwrap())
+    let y_label = |v: &f32| y_labels.format(*v);
+    let x_label = |v: &f32| {
+        if v.fract() == 0.0 && *v >= 0.0 {
+            x_labels.get(*v as usize).cloned().unwrap_or_default()
+        } else {
+            String::new()
+        }
+    };
+    let mut mesh = chart.configure_mesh();
+    mesh.ticks_for(config);
+    mesh.x_desc(config.axis_label(config.x.as_ref().unwrap()))
+        .y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .y_label_formatter(&y_label)
         .axis_desc_style(style.axis_desc_font())
-        .label_style(style.axis_label_font())
-        .draw()
-        .context("Failed to draw mesh")?;
+        .label_style(style.axis_label_font());
+    if x2_labels.is_some() {
+        mesh.x_labels(data_points.len() + 1)
+            .x_label_formatter(&x_label);
+    }
+    mesh.draw().context("Failed to draw mesh")?;
+
+    if let Some(x2_labels) = &x2_labels {
+        // Each bracket reaches halfway to the neighbouring group's points
+        let x_end = data_points.len() as f32;
+        let spans: Vec<(String, i32, i32)> = x2_groups(x2_labels)
+            .into_iter()
+            .map(|group| {
+                let left = (group.first as f32 - 0.5).max(0.0);
+                let right = (group.last as f32 + 0.5).min(x_end);
+                let left = chart.backend_coord(&(left, baseline)).0;
+                let right = chart.backend_coord(&(right, baseline)).0;
+                (group.label, left, right)
+            })
+            .collect();
+        let plot_bottom = chart.plotting_area().get_pixel_range().1.end;
+        draw_x2_labels(&root, &spans, plot_bottom, style)?;
+    }
+
+    // Shade the band first so the line is drawn on top of it
+    let band_color = style.get_primary_color(0).mix(0.2);
+    chart
+        .draw_series(
+            band.into_iter()
+                .map(|polygon| Polygon::new(polygon, band_color.filled())),
+        )
+        .context("Failed to draw band")?;
+    let rolling_color = style.get_primary_color(1);
+    chart
+        .draw_series(
+            rolling_area
+                .into_iter()
+                .map(|polygon| Polygon::new(polygon, rolling_color.mix(0.2).filled())),
+        )
+        .context("Failed to draw rolling band")?;
 
     // Use the primary color for line charts
     chart
@@ -88,6 +200,22 @@ where
         .label(config.y.as_ref().unwrap())
         .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], style.get_primary_color(0)));
 
+    // The rolling mean goes over the line it summarises
+    chart
+        .draw_series(LineSeries::new(rolling_mean, rolling_color.stroke_width(2)))
+        .context("Failed to draw rolling mean")?;
+
+    if config.endpoint_labels.unwrap_or(false) {
+        let color = *style.get_primary_color(0);
+        draw_endpoint_labels(&mut chart, &data_points, color, style, |y| {
+            y_labels.format(y)
+        })?;
+    }
+
+    if config.mark_extremes.unwrap_or(false) {
+        draw_extremes(&mut chart, &data_points, style, |x| x.to_string())?;
+    }
+
     // Legend is now handled externally
 
     root.present().context("Failed to present chart")?;
@@ -99,65 +227,91 @@ fn render_grouped_line_chart<DB: DrawingBackend>(
     config: &ChartConfig,
     root: DrawingArea<DB, plotters::coord::Shift>,
     title: &str,
-    group_by: &str,
     style: &crate::render::styling::ChartStyle,
 ) -> Result<()>
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    // For grouped data, we need to handle the structure differently
-    // The data should have been transformed to have the group column and aggregated values
-
-    // Try to get the group column and value column
-    let group_col = df.column(group_by).context("Group column not found")?;
-    let value_col = df
-        .column(config.y.as_ref().unwrap())
-        .context("Value column not found")?;
-
-    // Convert to vectors for plotting
-    let mut data_points = Vec::new();
-    for i in 0..df.height() {
-        if let (Ok(_group_val), Ok(value_val)) = (group_col.get(i), value_col.get(i)) {
-            let x = i as f32;
-            let y = extract_numeric_value(value_val).unwrap_or(0.0);
-            data_points.push((x, y));
-        }
+    let mut lines = group_lines(df, config)?;
+    for (_, points) in &mut lines.series {
+        drop_non_positive(config, points)?;
     }
-
-    if data_points.is_empty() {
+    let all_points: Vec<(f32, f32)> = lines
+        .series
+        .iter()
+        .filter(|(group, _)| config.shows_series(group))
+        .flat_map(|(_, points)| points.iter().copied())
+        .collect();
+    if all_points.is_empty() {
         return Ok(()); // Nothing to plot
     }
 
-    let x_range = 0f32..data_points.len() as f32;
-    let y_max = data_points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max);
-    let y_range = 0f32..(y_max * 1.1); // Add 10% padding
+    let xs: Vec<f32> = all_points.iter().map(|(x, _)| *x).collect();
+    let x_range = match &lines.x_labels {
+        Some(labels) => 0f32..labels.len().max(2) as f32 - 1.0,
+        None => x_domain(&xs),
+    };
+    let y_max = all_points.iter().map(|(_, y)| *y).fold(0.0f32, f32::max);
+    let y_values: Vec<f32> = all_points.iter().map(|(_, y)| *y).collect();
+    let y_range = YCoord::for_chart(config, 0f32..(y_max * 1.1), &y_values); // Add 10% padding
+    let y_name = match &config.ys {
+        Some(ys) => ys.join(", "),
+        None => config.y.clone().unwrap_or_default(),
+    };
+    let y_labels = select_label_format(config.y_format.as_ref(), &y_name, &y_values);
 
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
         .build_cartesian_2d(x_range, y_range)
         .context("Failed to build chart")?;
 
-    chart
-        .configure_mesh()
-        .x_desc(group_by)
-        .y_desc(config.y.as_ref().unwrap())
+    // Category x values sit at positions; label them with the values they stand for
+    let x_label = |v: &f32| match &lines.x_labels {
+        Some(labels) if v.fract() == 0.0 && *v >= 0.0 => {
+            labels.get(*v as usize).cloned().unwrap_or_default()
+        }
+        _ => String::new(),
+    };
+    let y_label = |v: &f32| y_labels.format(*v);
+    let mut mesh = chart.configure_mesh();
+    mesh.ticks_for(config);
+    mesh.x_desc(config.axis_label(config.x.as_ref().unwrap()))
+        .y_desc(config.axis_label(&y_name))
+        .y_label_formatter(&y_label)
         .axis_desc_style(style.axis_desc_font())
-        .label_style(style.axis_label_font())
-        .draw()
-        .context("Failed to draw mesh")?;
+        .label_style(style.axis_label_font());
+    if lines.x_labels.is_some() {
+        mesh.x_label_formatter(&x_label);
+    }
+    mesh.draw().context("Failed to draw mesh")?;
 
-    // Use the primary color for line charts
-    chart
-        .draw_series(
-            LineSeries::new(data_points.iter().cloned(), style.get_primary_color(0))
-                .point_size(style.layout.elements.line_points),
-        )
-        .context("Failed to draw line series")?
-        .label(config.y.as_ref().unwrap())
-        .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], style.get_primary_color(0)));
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    for (group_idx, (group, points)) in lines.series.iter().enumerate() {
+        // Hidden groups keep their color slot, so the others look the same either way
+        if !config.shows_series(group) {
+            continue;
+        }
+        let color = *style.get_group_color(group_idx, group, &color_mode);
+        chart
+            .draw_series(
+                LineSeries::new(points.iter().cloned(), color)
+                    .point_size(style.layout.elements.line_points),
+            )
+            .context("Failed to draw line series")?
+            .label(group)
+            .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 10, y)], color));
+
+        if config.endpoint_labels.unwrap_or(false) {
+            draw_endpoint_labels(&mut chart, points, color, style, |y| y_labels.format(y))?;
+        }
+    }
+
+    if config.mark_extremes.unwrap_or(false) {
+        draw_extremes(&mut chart, &all_points, style, |x| x.to_string())?;
+    }
 
     // Legend is now handled externally
 
@@ -165,6 +319,201 @@ where
     Ok(())
 }
 
+/// The rolling mean line through `data_points` and the polygons of its ±k·std band
+fn rolling_band_shapes(
+    data_points: &[(f32, f32)],
+    band: &crate::spec::RollingBandConfig,
+) -> Result<(Vec<(f32, f32)>, Vec<Vec<(f32, f32)>>)> {
+    if band.window > data_points.len() {
+        crate::warnings::warn(format!(
+            "rolling_band window of {} is longer than the {} plotted points; no band is drawn",
+            band.window,
+            data_points.len()
+        ))?;
+    }
+
+    let (xs, ys): (Vec<f32>, Vec<f32>) = data_points.iter().copied().unzip();
+    let points = rolling_band(&ys, band.window, band.k.unwrap_or(2.0) as f32);
+    let mean = xs
+        .iter()
+        .zip(&points)
+        .filter_map(|(x, point)| point.map(|point| (*x, point.mean)))
+        .collect();
+    let lower: Vec<Option<f32>> = points.iter().map(|p| p.map(|p| p.lower)).collect();
+    let upper: Vec<Option<f32>> = points.iter().map(|p| p.map(|p| p.upper)).collect();
+    Ok((mean, band_polygons(&xs, &lower, &upper)))
+}
+
+/// Points of a grouped line chart, one series per group over a shared x axis
+struct GroupLines {
+    /// Labels by position when x is not numeric; numeric x values are plotted as they are
+    x_labels: Option<Vec<String>>,
+    series: Vec<(String, Vec<(f32, f32)>)>,
+}
+
+/// Split the rows into one line per `group_by` value, in sorted group order, or one line per
+/// `ys` column, in listed order; either way a series' index is its color slot
+///
+/// Points are in x order. A non-numeric x is laid out by position over the union of every
+/// group's x values, so the same x lines up across groups.
+fn group_lines(df: &DataFrame, config: &ChartConfig) -> Result<GroupLines> {
+    let x_col = df
+        .column(config.x.as_ref().unwrap())
+        .context("X column not found")?;
+
+    // Each series' y by row, `None` on rows that aren't part of it
+    let mut series_values: Vec<(String, Vec<Option<f32>>)> = Vec::new();
+    if let Some(ys) = &config.ys {
+        for y in ys {
+            series_values.push((y.clone(), column_values(df, y)?));
+        }
+    } else if let Some(group_by) = &config.group_by {
+        let y_values = column_values(df, config.y.as_ref().unwrap())?;
+        let group_col = df.column(group_by).context("Group column not found")?;
+        let row_groups: Vec<Option<String>> = (0..df.height())
+            .map(|i| group_col.get(i).ok().map(value_label))
+            .collect();
+        for group in group_names(df, group_by)? {
+            let values = y_values
+                .iter()
+                .zip(&row_groups)
+                .map(|(y, row_group)| y.filter(|_| row_group.as_ref() == Some(&group)))
+                .collect();
+            series_values.push((group, values));
+        }
+    }
+
+    let numeric_xs = numeric_x_values(x_col);
+    let mut rows: Vec<usize> = (0..df.height()).collect();
+    let sort_keys: Option<Vec<f64>> = (0..df.height())
+        .map(|i| x_col.get(i).ok().and_then(x_sort_key))
+        .collect();
+    if let Some(keys) = &sort_keys {
+        rows.sort_by(|&a, &b| keys[a].total_cmp(&keys[b]));
+    }
+
+    let mut x_labels: Vec<String> = Vec::new();
+    let mut series: Vec<(String, Vec<(f32, f32)>)> = series_values
+        .iter()
+        .map(|(name, _)| (name.clone(), Vec::new()))
+        .collect();
+    for i in rows {
+        let Ok(x_val) = x_col.get(i) else {
+            continue;
+        };
+        let x = match &numeric_xs {
+            Some(xs) => xs[i],
+            None => {
+                let label = value_label(x_val);
+                let position = x_labels.iter().position(|existing| *existing == label);
+                position.unwrap_or_else(|| {
+                    x_labels.push(label);
+                    x_labels.len() - 1
+                }) as f32
+            }
+        };
+        for ((_, points), (_, values)) in series.iter_mut().zip(&series_values) {
+            if let Some(y) = values[i] {
+                points.push((x, y));
+            }
+        }
+    }
+
+    Ok(GroupLines {
+        x_labels: numeric_xs.is_none().then_some(x_labels),
+        series,
+    })
+}
+
+/// Distinct values of the `group_by` column, sorted; each is one line, and the legend lists
+/// them in this order so colors match
+pub fn group_names(df: &DataFrame, group_by: &str) -> Result<Vec<String>> {
+    let group_col = df.column(group_by).context("Group column not found")?;
+    let mut names: Vec<String> = Vec::new();
+    for i in 0..df.height() {
+        if let Ok(value) = group_col.get(i) {
+            let name = value_label(value);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Numeric values of a column by row, `None` where the value is missing
+fn column_values(df: &DataFrame, column: &str) -> Result<Vec<Option<f32>>> {
+    let series = df
+        .column(column)
+        .with_context(|| format!("Column '{}' not found", column))?;
+    Ok((0..df.height())
+        .map(|i| series.get(i).ok().and_then(extract_numeric_value))
+        .collect())
+}
+
+/// Every value of an x column as a number, or None when any is not numeric
+fn numeric_x_values(column: &Series) -> Option<Vec<f32>> {
+    (0..column.len())
+        .map(|i| column.get(i).ok().and_then(extract_numeric_value))
+        .collect()
+}
+
+/// `min..max` of the x values with 10% padding on each side, like scatter plots
+fn x_domain(xs: &[f32]) -> std::ops::Range<f32> {
+    let x_min = xs.iter().copied().fold(f32::INFINITY, f32::min);
+    let x_max = xs.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    // A single distinct x still needs a non-empty range
+    let padding = if x_max > x_min {
+        (x_max - x_min) * 0.1
+    } else {
+        1.0
+    };
+    (x_min - padding)..(x_max + padding)
+}
+
+/// Closed polygons between the upper and lower bounds at each x, one per run of rows where
+/// both bounds are present; a missing bound breaks the band into segments
+fn band_polygons(xs: &[f32], lower: &[Option<f32>], upper: &[Option<f32>]) -> Vec<Vec<(f32, f32)>> {
+    let mut polygons = Vec::new();
+    let mut run: Vec<(f32, f32, f32)> = Vec::new();
+
+    let mut close_run = |run: &mut Vec<(f32, f32, f32)>| {
+        if !run.is_empty() {
+            let upper_edge = run.iter().map(|&(x, _, high)| (x, high));
+            let lower_edge = run.iter().rev().map(|&(x, low, _)| (x, low));
+            polygons.push(upper_edge.chain(lower_edge).collect());
+            run.clear();
+        }
+    };
+
+    for (&x, bounds) in xs.iter().zip(lower.iter().zip(upper)) {
+        match bounds {
+            (Some(low), Some(high)) => run.push((x, low.min(*high), low.max(*high))),
+            _ => close_run(&mut run),
+        }
+    }
+    close_run(&mut run);
+
+    polygons
+}
+
+/// Sort position of an x value: numbers as they are, dates by their day or timestamp
+fn x_sort_key(value: AnyValue) -> Option<f64> {
+    match value {
+        AnyValue::Date(days) => Some(days as f64),
+        AnyValue::Datetime(timestamp, _, _) => Some(timestamp as f64),
+        other => extract_numeric_value(other).map(f64::from),
+    }
+}
+
+fn value_label(value: AnyValue) -> String {
+    match value {
+        AnyValue::Utf8(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
+
 fn extract_numeric_value(value: AnyValue) -> Option<f32> {
     match value {
         AnyValue::Int32(i) => Some(i as f32),
@@ -176,3 +525,189 @@ fn extract_numeric_value(value: AnyValue) -> Option<f32> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::styling::get_chart_style;
+
+    #[test]
+    fn test_band_polygon_spans_between_bounds() {
+        let lower = vec![Some(8.0), Some(9.0), Some(10.0)];
+        let upper = vec![Some(12.0), Some(14.0), Some(16.0)];
+        let polygons = band_polygons(&[0.0, 1.0, 2.0], &lower, &upper);
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(
+            polygons[0],
+            vec![
+                (0.0, 12.0),
+                (1.0, 14.0),
+                (2.0, 16.0),
+                (2.0, 10.0),
+                (1.0, 9.0),
+                (0.0, 8.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_band_breaks_at_missing_bounds() {
+        let lower = vec![Some(1.0), Some(1.0), None, Some(1.0), Some(1.0)];
+        let upper = vec![Some(2.0), Some(2.0), Some(2.0), Some(2.0), Some(2.0)];
+        let polygons = band_polygons(&[0.0, 1.0, 2.0, 3.0, 4.0], &lower, &upper);
+        assert_eq!(polygons.len(), 2);
+        assert!(polygons[1].iter().all(|(x, _)| *x >= 3.0));
+    }
+
+    #[test]
+    fn test_grouped_line_draws_a_series_per_group() {
+        // Two channels over three days, rows out of date order
+        let date = Series::new("date", &[19359i32, 19358, 19358, 19360, 19359, 19360])
+            .cast(&DataType::Date)
+            .unwrap();
+        let channel = Series::new(
+            "channel",
+            &[
+                "organic", "organic", "direct", "organic", "direct", "direct",
+            ],
+        );
+        let users = Series::new("users", &[150, 100, 40, 120, 60, 80]);
+        let df = DataFrame::new(vec![date, channel, users]).unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            group_by: Some("channel".to_string()),
+            ..Default::default()
+        };
+
+        let lines = group_lines(&df, &config).unwrap();
+        assert_eq!(
+            lines.x_labels,
+            Some(vec![
+                "2023-01-01".to_string(),
+                "2023-01-02".to_string(),
+                "2023-01-03".to_string()
+            ])
+        );
+        assert_eq!(lines.series.len(), 2);
+        // Groups come in sorted order, not the order they first appear
+        assert_eq!(lines.series[0].0, "direct");
+        assert_eq!(
+            lines.series[0].1,
+            vec![(0.0, 40.0), (1.0, 60.0), (2.0, 80.0)]
+        );
+        assert_eq!(lines.series[1].0, "organic");
+        assert_eq!(
+            lines.series[1].1,
+            vec![(0.0, 100.0), (1.0, 150.0), (2.0, 120.0)]
+        );
+
+        let mut buffer = vec![0u8; 400 * 300 * 3];
+        let root = BitMapBackend::with_buffer(&mut buffer, (400, 300)).into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        render(&df, &config, root, "Users", &LegendPosition::Right).unwrap();
+
+        let style = get_chart_style();
+        let drawn = |color: &RGBColor| {
+            buffer
+                .chunks_exact(3)
+                .any(|pixel| pixel == [color.0, color.1, color.2])
+        };
+        assert!(drawn(style.get_primary_color(0)));
+        assert!(drawn(style.get_primary_color(1)));
+    }
+
+    #[test]
+    fn test_endpoint_labels_mark_first_and_last_point_of_each_series() {
+        let df = df! {
+            "day" => &[1i64, 2, 3, 4, 1, 2, 3, 4],
+            "channel" => &["organic", "organic", "organic", "organic", "direct", "direct", "direct", "direct"],
+            "users" => &[100, 130, 90, 120, 40, 55, 70, 80],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("day".to_string()),
+            y: Some("users".to_string()),
+            group_by: Some("channel".to_string()),
+            endpoint_labels: Some(true),
+            ..Default::default()
+        };
+
+        let mut svg = String::new();
+        {
+            let root = SVGBackend::with_string(&mut svg, (600, 400)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            render(&df, &config, root, "Users", &LegendPosition::Right).unwrap();
+        }
+
+        // Labels take their series' color, which nothing else written as text uses
+        let style = get_chart_style();
+        for (index, expected) in [["40", "80"], ["100", "120"]].iter().enumerate() {
+            let color = style.get_primary_color(index);
+            let fill = format!("fill=\"#{:02X}{:02X}{:02X}\"", color.0, color.1, color.2);
+            let labels: Vec<&str> = svg
+                .split("<text")
+                .skip(1)
+                .filter(|text| text.split('>').next().unwrap().contains(&fill))
+                .map(|text| text.split('>').nth(1).unwrap().trim_end_matches("</text"))
+                .collect();
+            assert_eq!(labels, expected);
+        }
+    }
+
+    #[test]
+    fn test_log_scale_lifts_exponential_growth_off_the_floor() {
+        let df = df! {
+            "day" => &[0i64, 1, 2, 3, 4, 5, 6],
+            "events" => &[1.0, 10.0, 100.0, 1_000.0, 10_000.0, 100_000.0, 1_000_000.0],
+        }
+        .unwrap();
+        let linear = ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("day".to_string()),
+            y: Some("events".to_string()),
+            ..Default::default()
+        };
+        let log = ChartConfig {
+            log_scale: Some(true),
+            ..linear.clone()
+        };
+
+        // Leftmost column where the line is drawn in the top half of the canvas
+        let first_high_column = |config: &ChartConfig| {
+            let mut buffer = vec![0u8; 400 * 300 * 3];
+            let root = BitMapBackend::with_buffer(&mut buffer, (400, 300)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            render(&df, config, root, "Events", &LegendPosition::Right).unwrap();
+
+            let line = *get_chart_style().get_primary_color(0);
+            buffer
+                .chunks_exact(3)
+                .enumerate()
+                .filter(|(i, pixel)| i / 400 < 150 && *pixel == [line.0, line.1, line.2])
+                .map(|(i, _)| i % 400)
+                .min()
+                .unwrap()
+        };
+
+        // On a linear axis only the last day climbs; on a log axis the climb is steady
+        assert!(first_high_column(&log) + 50 < first_high_column(&linear));
+    }
+
+    #[test]
+    fn test_numeric_x_domain_spans_actual_values() {
+        let day = Series::new("day", &[5i64, 1, 7, 3]);
+        let xs = numeric_x_values(&day).unwrap();
+        assert_eq!(xs, vec![5.0, 1.0, 7.0, 3.0]);
+
+        // 10% of the 1..7 span on either side, not the 0..4 row indices
+        let domain = x_domain(&xs);
+        assert!((domain.start - 0.4).abs() < 1e-6);
+        assert!((domain.end - 7.6).abs() < 1e-6);
+
+        let channel = Series::new("channel", &["organic", "direct"]);
+        assert_eq!(numeric_x_values(&channel), None);
+    }
+}
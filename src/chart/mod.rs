@@ -1,11 +1,19 @@
+pub mod annotate;
 pub mod area;
+pub mod axis;
 pub mod bar;
 pub mod bar_stacked;
+pub mod boxplot;
 pub mod funnel;
 pub mod heatmap;
+pub mod hierarchy;
 pub mod line;
+pub mod pie;
 pub mod retention;
+pub mod rolling;
 pub mod scatter;
+pub mod stack;
+pub mod trend;
 pub mod types;
 
 // pub use types::*;
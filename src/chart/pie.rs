@@ -0,0 +1,241 @@
+//! Part-to-whole pie and donut charts: a wedge per x category, sized by its share of the y total
+use crate::render::styling::get_chart_style_for;
+use crate::spec::{ChartConfig, LegendPosition};
+use anyhow::{Context, Result};
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use polars::prelude::*;
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+/// Radius of a donut's hole as a share of the outer radius
+const DONUT_HOLE: f64 = 0.55;
+
+/// Wedges smaller than this share of the whole are too thin to hold their percentage
+const MIN_LABELED_SHARE: f64 = 0.03;
+
+pub fn render<DB: DrawingBackend>(
+    df: &DataFrame,
+    config: &ChartConfig,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+    _legend_position: &LegendPosition,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let PieSlices { slices, skipped } = pie_slices(df, config)?;
+    if skipped > 0 {
+        crate::warnings::warn(format!(
+            "Pie: skipped {} categor{} with a zero, negative or missing value",
+            skipped,
+            if skipped == 1 { "y" } else { "ies" }
+        ))?;
+    }
+    let total: f64 = slices.iter().map(|(_, value)| value).sum();
+    if total <= 0.0 {
+        return Ok(());
+    }
+
+    let style = get_chart_style_for(config);
+    let area = root
+        .titled(title, style.title_font())
+        .context("Failed to draw title")?;
+
+    let (width, height) = area.dim_in_pixel();
+    let center = (width as f64 / 2.0, height as f64 / 2.0);
+    let radius = width.min(height) as f64 * 0.4;
+    let hole = if config.donut.unwrap_or(false) {
+        radius * DONUT_HOLE
+    } else {
+        0.0
+    };
+
+    // Wedges run clockwise from twelve o'clock, in row order, colored like their legend swatch
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    let mut start = -FRAC_PI_2;
+    for (slot, (label, value)) in slices.iter().enumerate() {
+        let share = value / total;
+        let end = start + share * TAU;
+        area.draw(&Polygon::new(
+            wedge_points(center, radius, hole, start, end),
+            style.get_group_color(slot, label, &color_mode).filled(),
+        ))
+        .context("Failed to draw pie wedge")?;
+
+        if share >= MIN_LABELED_SHARE {
+            // Halfway across the ring, or two thirds out on a full pie
+            let label_radius = if hole > 0.0 {
+                (radius + hole) / 2.0
+            } else {
+                radius * 0.65
+            };
+            let middle = (start + end) / 2.0;
+            let position = (
+                (center.0 + label_radius * middle.cos()).round() as i32,
+                (center.1 + label_radius * middle.sin()).round() as i32,
+            );
+            let font = style
+                .axis_label_font()
+                .pos(Pos::new(HPos::Center, VPos::Center));
+            area.draw(&Text::new(percent_label(share), position, font))
+                .context("Failed to draw pie label")?;
+        }
+        start = end;
+    }
+
+    root.present().context("Failed to present chart")?;
+    Ok(())
+}
+
+/// The wedges a pie draws, plus how many categories were left out
+pub struct PieSlices {
+    /// Category label and its total, in row order; each slice's index is its color slot
+    pub slices: Vec<(String, f64)>,
+    /// Categories whose total is zero, negative or missing, which take no part of the circle
+    pub skipped: usize,
+}
+
+/// Total y per x category, repeated categories summed
+pub fn pie_slices(df: &DataFrame, config: &ChartConfig) -> Result<PieSlices> {
+    let x_col = df
+        .column(config.x.as_ref().unwrap())
+        .context("X column not found")?;
+    let y_col = df
+        .column(config.y.as_ref().unwrap())
+        .context("Y column not found")?;
+
+    let mut totals: Vec<(String, f64)> = Vec::new();
+    for i in 0..df.height() {
+        let Ok(x_val) = x_col.get(i) else {
+            continue;
+        };
+        let label = value_label(x_val);
+        let value = y_col.get(i).ok().and_then(extract_numeric_value);
+        match totals.iter_mut().find(|(existing, _)| *existing == label) {
+            Some((_, total)) => *total += value.unwrap_or(0.0),
+            None => totals.push((label, value.unwrap_or(0.0))),
+        }
+    }
+
+    let categories = totals.len();
+    totals.retain(|(_, total)| *total > 0.0 && total.is_finite());
+    Ok(PieSlices {
+        skipped: categories - totals.len(),
+        slices: totals,
+    })
+}
+
+/// Outline of the wedge between angles `start` and `end` (radians, clockwise on screen), with
+/// the inner `hole` radius cut out for donuts
+fn wedge_points(
+    center: (f64, f64),
+    radius: f64,
+    hole: f64,
+    start: f64,
+    end: f64,
+) -> Vec<(i32, i32)> {
+    // About one point per degree keeps the arc smooth at any size
+    let steps = ((end - start).to_degrees().ceil() as usize).max(1);
+    let arc = |r: f64| {
+        (0..=steps).map(move |i| {
+            let angle = start + (end - start) * i as f64 / steps as f64;
+            (
+                (center.0 + r * angle.cos()).round() as i32,
+                (center.1 + r * angle.sin()).round() as i32,
+            )
+        })
+    };
+
+    let mut points: Vec<(i32, i32)> = arc(radius).collect();
+    if hole > 0.0 {
+        let inner: Vec<(i32, i32)> = arc(hole).collect();
+        points.extend(inner.into_iter().rev());
+    } else {
+        points.push((center.0.round() as i32, center.1.round() as i32));
+    }
+    points
+}
+
+fn percent_label(share: f64) -> String {
+    format!("{:.0}%", share * 100.0)
+}
+
+fn value_label(value: AnyValue) -> String {
+    match value {
+        AnyValue::Utf8(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn extract_numeric_value(value: AnyValue) -> Option<f64> {
+    match value {
+        AnyValue::Int32(i) => Some(i as f64),
+        AnyValue::Int64(i) => Some(i as f64),
+        AnyValue::UInt32(u) => Some(u as f64),
+        AnyValue::UInt64(u) => Some(u as f64),
+        AnyValue::Float32(f) => Some(f as f64),
+        AnyValue::Float64(f) => Some(f),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::styling::get_chart_style;
+
+    #[test]
+    fn test_three_slice_pie_draws_a_wedge_and_share_each() {
+        let df = df! {
+            "channel" => &["organic", "direct", "paid"],
+            "users" => &[500i64, 300, 200],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Pie,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            ..Default::default()
+        };
+        let draw = |config: &ChartConfig| {
+            let mut svg = String::new();
+            {
+                let root = SVGBackend::with_string(&mut svg, (600, 600)).into_drawing_area();
+                root.fill(&WHITE).unwrap();
+                render(&df, config, root, "Users", &LegendPosition::Right).unwrap();
+            }
+            svg
+        };
+
+        let svg = draw(&config);
+        let style = get_chart_style();
+        for slot in 0..3 {
+            let RGBColor(r, g, b) = *style.get_primary_color(slot);
+            let fill = format!("fill=\"#{:02X}{:02X}{:02X}\"", r, g, b);
+            assert_eq!(svg.matches(&fill).count(), 1, "slice {}", slot);
+        }
+        for label in [">50%<", ">30%<", ">20%<"] {
+            assert!(svg.contains(label), "missing {}", label);
+        }
+
+        // A donut keeps the same wedges and labels around an empty middle
+        let donut = draw(&ChartConfig {
+            donut: Some(true),
+            ..config.clone()
+        });
+        assert_eq!(donut.matches("<polygon").count(), 3);
+        assert!(donut.contains(">50%<"));
+    }
+
+    #[test]
+    fn test_wedge_closes_at_the_center_or_around_the_hole() {
+        let pie = wedge_points((100.0, 100.0), 50.0, 0.0, 0.0, FRAC_PI_2);
+        assert_eq!(pie.first(), Some(&(150, 100)));
+        assert_eq!(pie[pie.len() - 2], (100, 150));
+        assert_eq!(pie.last(), Some(&(100, 100)));
+
+        let donut = wedge_points((100.0, 100.0), 50.0, 20.0, 0.0, FRAC_PI_2);
+        assert_eq!(donut.first(), Some(&(150, 100)));
+        assert_eq!(donut.last(), Some(&(120, 100)));
+    }
+}
@@ -1,8 +1,11 @@
-use crate::render::styling::{get_chart_style, get_heatmap_style};
-use crate::spec::{ChartConfig, LegendPosition};
+use crate::chart::axis::YAxisSideExt;
+use crate::render::styling::{color_positions, get_chart_style_for, get_heatmap_style};
+use crate::spec::{ChartConfig, LegendPosition, RetentionBaseline};
+use crate::warnings::warn_row_cap;
 use anyhow::{Context, Result};
 use plotters::prelude::*;
 use polars::prelude::*;
+use std::collections::HashMap;
 
 pub fn render<DB: DrawingBackend>(
     df: &DataFrame,
@@ -14,107 +17,87 @@ pub fn render<DB: DrawingBackend>(
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    // For retention charts, we need cohort_date, period_number, and users
-    let cohort_date_col = config
-        .cohort_date
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Retention charts require a 'cohort_date' field"))?;
-    let period_number_col = config
-        .period_number
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Retention charts require a 'period_number' field"))?;
-    let users_col = config
-        .users
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("Retention charts require a 'users' field"))?;
+    warn_row_cap("Retention", df.height(), 100)?;
 
-    let cohort_col = df
-        .column(cohort_date_col)
-        .context("Cohort date column not found")?;
-    let period_col = df
-        .column(period_number_col)
-        .context("Period number column not found")?;
-    let users_data_col = df.column(users_col).context("Users column not found")?;
-
-    // Collect retention data
-    let mut retention_data: std::collections::HashMap<String, std::collections::HashMap<i32, f32>> =
-        std::collections::HashMap::new();
-    let mut all_cohorts = std::collections::HashSet::new();
-    let mut all_periods = std::collections::HashSet::new();
-
-    for i in 0..df.height().min(100) {
-        // Limit for performance
-        if let (Ok(cohort_val), Ok(period_val), Ok(users_val)) =
-            (cohort_col.get(i), period_col.get(i), users_data_col.get(i))
-        {
-            let cohort_str = format!("{:?}", cohort_val);
-            let period_num = extract_numeric_value(period_val).unwrap_or(0.0) as i32;
-            let users_count = extract_numeric_value(users_val).unwrap_or(0.0);
-
-            retention_data
-                .entry(cohort_str.clone())
-                .or_default()
-                .insert(period_num, users_count);
-            all_cohorts.insert(cohort_str);
-            all_periods.insert(period_num);
-        }
-    }
-
-    if retention_data.is_empty() {
+    let Some(table) = retention_table(df, config)? else {
         return Ok(());
+    };
+    if config.survival.unwrap_or(false) {
+        return render_survival(&table, config, root, title);
     }
+    let averages = config
+        .average_row
+        .unwrap_or(false)
+        .then(|| table.period_averages());
+    let RetentionTable {
+        cohorts,
+        periods,
+        values: retention_matrix,
+    } = table;
 
-    // Convert to sorted vectors for consistent ordering
-    let mut cohorts: Vec<String> = all_cohorts.into_iter().collect();
-    cohorts.sort();
-    let mut periods: Vec<i32> = all_periods.into_iter().collect();
-    periods.sort();
-
-    // Calculate retention percentages (normalize to first period = 100%)
-    let mut retention_matrix = Vec::new();
-    for cohort in &cohorts {
-        let cohort_data = retention_data.get(cohort).unwrap();
-        let mut cohort_retention = Vec::new();
-
-        // Find the first period value (baseline)
-        let baseline = periods
-            .iter()
-            .filter_map(|&p| cohort_data.get(&p))
-            .next()
-            .unwrap_or(&0.0);
-
-        for &period in &periods {
-            let value = cohort_data.get(&period).unwrap_or(&0.0);
-            let retention_pct = if *baseline > 0.0 {
-                (value / baseline) * 100.0
-            } else {
-                0.0
-            };
-            cohort_retention.push(retention_pct);
+    if config.baseline.clone().unwrap_or_default() == RetentionBaseline::PeriodZero {
+        for cohort in missing_period_zero(df, config)? {
+            crate::warnings::warn(format!(
+                "cohort {} has no period 0 row; its retention is shown as 0%",
+                cohort
+            ))?;
         }
-
-        retention_matrix.push(cohort_retention);
     }
 
     // Find max retention for scaling
     let max_retention = retention_matrix
         .iter()
-        .flat_map(|row| row.iter())
+        .flatten()
+        .flatten()
         .fold(0.0f32, |max, &val| max.max(val));
 
     if max_retention == 0.0 {
         return Ok(());
     }
 
-    let style = get_chart_style();
+    let style = get_chart_style_for(config);
     let heatmap_style = get_heatmap_style();
 
+    // The average row shares the cohorts' color scale, so its cells are scaled alongside them;
+    // periods nobody reached have no color and stay empty
+    let cells: Vec<Option<f32>> = retention_matrix
+        .iter()
+        .flatten()
+        .chain(averages.iter().flatten())
+        .copied()
+        .collect();
+    let cell_values: Vec<f32> = cells.iter().flatten().copied().collect();
+    let mut scaled = color_positions(
+        &cell_values,
+        &config.color_scale.clone().unwrap_or_default(),
+    )
+    .into_iter();
+    let positions: Vec<Option<f32>> = cells
+        .iter()
+        .map(|cell| cell.and_then(|_| scaled.next()))
+        .collect();
+    let cell_color = |intensity: f32| {
+        let base_color = heatmap_style.shade(intensity);
+        RGBColor(
+            base_color as u8,
+            (base_color * 0.8) as u8,
+            (base_color * 0.6) as u8,
+        )
+    };
+
+    // The average row sits below the cohorts, separated by a gap
+    let y_start = if averages.is_some() {
+        -(1.0 + AVERAGE_ROW_GAP)
+    } else {
+        0.0
+    };
+
     let mut chart = ChartBuilder::on(&root)
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
-        .build_cartesian_2d(0.0f32..periods.len() as f32, 0.0f32..cohorts.len() as f32)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
+        .build_cartesian_2d(0.0f32..periods.len() as f32, y_start..cohorts.len() as f32)
         .context("Failed to build chart")?;
 
     chart
@@ -129,17 +112,11 @@ where
     // Draw retention matrix cells
     for (cohort_idx, _cohort) in cohorts.iter().enumerate() {
         for (period_idx, &_period) in periods.iter().enumerate() {
-            let retention_pct = retention_matrix[cohort_idx][period_idx];
-
             // Calculate color intensity based on retention percentage
-            let intensity = retention_pct / max_retention;
-            let base_color = heatmap_style.intensity_range.0
-                + (intensity * (heatmap_style.intensity_range.1 - heatmap_style.intensity_range.0));
-            let color = RGBColor(
-                base_color as u8,
-                (base_color * 0.8) as u8,
-                (base_color * 0.6) as u8,
-            );
+            let Some(intensity) = positions[cohort_idx * periods.len() + period_idx] else {
+                continue;
+            };
+            let color = cell_color(intensity);
 
             // Draw retention cell
             chart
@@ -156,12 +133,281 @@ where
         }
     }
 
+    if averages.is_some() {
+        let offset = cohorts.len() * periods.len();
+        let top = -AVERAGE_ROW_GAP;
+        chart
+            .draw_series((0..periods.len()).filter_map(|period_idx| {
+                let intensity = positions[offset + period_idx]?;
+                Some(Rectangle::new(
+                    [
+                        (period_idx as f32, top - 1.0),
+                        ((period_idx + 1) as f32, top),
+                    ],
+                    cell_color(intensity).filled(),
+                ))
+            }))
+            .context("Failed to draw average row")?;
+        chart
+            .draw_series(std::iter::once(Text::new(
+                "Average",
+                (0.05, top - 0.35),
+                style.axis_label_font(),
+            )))
+            .context("Failed to draw average row label")?;
+    }
+
+    // Legend is now handled externally
+
+    root.present().context("Failed to present chart")?;
+    Ok(())
+}
+
+/// One Kaplan-Meier-style step curve per cohort: the share still active, flat through each
+/// period and dropping at the next
+fn render_survival<DB: DrawingBackend>(
+    table: &RetentionTable,
+    config: &ChartConfig,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let style = get_chart_style_for(config);
+    let first = *table.periods.first().unwrap_or(&0) as f32;
+    let last = *table.periods.last().unwrap_or(&0) as f32;
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption(title, style.title_font())
+        .margin(style.layout.margins.chart as i32)
+        .x_label_area_size(style.layout.areas.x_label_area)
+        .y_label_area_for(config, style.layout.areas.y_label_area)
+        .build_cartesian_2d(first..(last + 1.0), 0f32..105f32)
+        .context("Failed to build chart")?;
+
+    chart
+        .configure_mesh()
+        .x_desc("Period")
+        .y_desc("Still active")
+        .y_label_formatter(&|v| format!("{:.0}%", v))
+        .axis_desc_style(style.axis_desc_font())
+        .label_style(style.axis_label_font())
+        .draw()
+        .context("Failed to draw mesh")?;
+
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    for (cohort_idx, (cohort, curve)) in table
+        .cohorts
+        .iter()
+        .zip(table.survival_curves())
+        .enumerate()
+    {
+        let color = *style.get_group_color(cohort_idx, cohort, &color_mode);
+        chart
+            .draw_series(LineSeries::new(
+                step_points(&table.periods, &curve),
+                color.stroke_width(2),
+            ))
+            .context("Failed to draw survival curve")?;
+    }
+
     // Legend is now handled externally
 
     root.present().context("Failed to present chart")?;
     Ok(())
 }
 
+/// Corners of a step curve: each value holds from its period up to the next one
+fn step_points(periods: &[i32], curve: &[f32]) -> Vec<(f32, f32)> {
+    let mut points = Vec::with_capacity(curve.len() * 2);
+    for (i, &value) in curve.iter().enumerate() {
+        let start = periods[i] as f32;
+        let end = periods.get(i + 1).map_or(start + 1.0, |&next| next as f32);
+        points.push((start, value));
+        points.push((end, value));
+    }
+    points
+}
+
+/// Space between the cohorts and the average row, as a fraction of a row
+const AVERAGE_ROW_GAP: f32 = 0.25;
+
+/// Retention percentages per cohort (rows) and period (columns), sorted by cohort and period
+///
+/// Periods after a cohort's last row are `None`: the cohort hasn't reached them yet.
+#[derive(Debug, Clone)]
+pub struct RetentionTable {
+    pub cohorts: Vec<String>,
+    pub periods: Vec<i32>,
+    pub values: Vec<Vec<Option<f32>>>,
+}
+
+impl RetentionTable {
+    /// Mean retention % of each period across the cohorts that have reached it; `None` when
+    /// none has
+    pub fn period_averages(&self) -> Vec<Option<f32>> {
+        (0..self.periods.len())
+            .map(|period_idx| {
+                let reached: Vec<f32> = self
+                    .values
+                    .iter()
+                    .filter_map(|row| row[period_idx])
+                    .collect();
+                (!reached.is_empty()).then(|| reached.iter().sum::<f32>() / reached.len() as f32)
+            })
+            .collect()
+    }
+
+    /// Share of each cohort still active by each period: its retention, never rising once
+    /// it has fallen, and capped at 100%
+    ///
+    /// A curve stops at the last period the cohort has reached, so young cohorts aren't
+    /// shown dropping to zero in periods they haven't reached.
+    pub fn survival_curves(&self) -> Vec<Vec<f32>> {
+        self.values
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map_while(|value| *value)
+                    .scan(100.0f32, |still_active, value| {
+                        *still_active = still_active.min(value);
+                        Some(*still_active)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Build the retention matrix the chart draws; `None` when no row could be read
+pub fn retention_table(df: &DataFrame, config: &ChartConfig) -> Result<Option<RetentionTable>> {
+    let retention_data = cohort_users(df, config)?;
+    if retention_data.is_empty() {
+        return Ok(None);
+    }
+
+    // Convert to sorted vectors for consistent ordering
+    let mut cohorts: Vec<String> = retention_data.keys().cloned().collect();
+    cohorts.sort();
+    let mut periods: Vec<i32> = retention_data
+        .values()
+        .flat_map(|cohort_data| cohort_data.keys().copied())
+        .collect();
+    periods.sort();
+    periods.dedup();
+
+    // Calculate retention percentages (normalize to the baseline = 100%)
+    let baseline_mode = config.baseline.clone().unwrap_or_default();
+    let values = cohorts
+        .iter()
+        .map(|cohort| cohort_retention(&retention_data[cohort], &periods, &baseline_mode))
+        .collect();
+
+    Ok(Some(RetentionTable {
+        cohorts,
+        periods,
+        values,
+    }))
+}
+
+/// Cohorts with no period 0 row, sorted
+fn missing_period_zero(df: &DataFrame, config: &ChartConfig) -> Result<Vec<String>> {
+    let mut cohorts: Vec<String> = cohort_users(df, config)?
+        .into_iter()
+        .filter(|(_, cohort_data)| !cohort_data.contains_key(&0))
+        .map(|(cohort, _)| cohort)
+        .collect();
+    cohorts.sort();
+    Ok(cohorts)
+}
+
+/// Users per period for each cohort, from the first 100 rows
+fn cohort_users(
+    df: &DataFrame,
+    config: &ChartConfig,
+) -> Result<HashMap<String, HashMap<i32, f32>>> {
+    // For retention charts, we need cohort_date, period_number, and users
+    let cohort_date_col = config
+        .cohort_date
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Retention charts require a 'cohort_date' field"))?;
+    let period_number_col = config
+        .period_number
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Retention charts require a 'period_number' field"))?;
+    let users_col = config
+        .users
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Retention charts require a 'users' field"))?;
+
+    let cohort_col = df
+        .column(cohort_date_col)
+        .context("Cohort date column not found")?;
+    let period_col = df
+        .column(period_number_col)
+        .context("Period number column not found")?;
+    let users_data_col = df.column(users_col).context("Users column not found")?;
+
+    let mut retention_data: HashMap<String, HashMap<i32, f32>> = HashMap::new();
+    for i in 0..df.height().min(100) {
+        // Limit for performance
+        if let (Ok(cohort_val), Ok(period_val), Ok(users_val)) =
+            (cohort_col.get(i), period_col.get(i), users_data_col.get(i))
+        {
+            let cohort_str = format!("{:?}", cohort_val);
+            let period_num = extract_numeric_value(period_val).unwrap_or(0.0) as i32;
+            let users_count = extract_numeric_value(users_val).unwrap_or(0.0);
+
+            retention_data
+                .entry(cohort_str)
+                .or_default()
+                .insert(period_num, users_count);
+        }
+    }
+
+    Ok(retention_data)
+}
+
+/// Users value that a cohort's retention is measured against
+fn cohort_baseline(cohort_data: &HashMap<i32, f32>, mode: &RetentionBaseline) -> Option<f32> {
+    match mode {
+        RetentionBaseline::PeriodZero => cohort_data.get(&0).copied(),
+        RetentionBaseline::FirstPresent => cohort_data
+            .iter()
+            .min_by_key(|(period, _)| **period)
+            .map(|(_, users)| *users),
+        RetentionBaseline::CohortSize => cohort_data.values().copied().reduce(f32::max),
+    }
+}
+
+/// Retention percentage of a cohort for each period, relative to its baseline
+///
+/// Periods past the cohort's last row are `None`; missing rows before it count as 0%.
+fn cohort_retention(
+    cohort_data: &HashMap<i32, f32>,
+    periods: &[i32],
+    mode: &RetentionBaseline,
+) -> Vec<Option<f32>> {
+    let baseline = cohort_baseline(cohort_data, mode).unwrap_or(0.0);
+    let last_period = cohort_data.keys().copied().max();
+
+    periods
+        .iter()
+        .map(|period| {
+            if last_period.is_none_or(|last| *period > last) {
+                return None;
+            }
+            let value = cohort_data.get(period).unwrap_or(&0.0);
+            Some(if baseline > 0.0 {
+                (value / baseline) * 100.0
+            } else {
+                0.0
+            })
+        })
+        .collect()
+}
+
 fn extract_numeric_value(value: AnyValue) -> Option<f32> {
     match value {
         AnyValue::Int32(i) => Some(i as f32),
@@ -173,3 +419,121 @@ fn extract_numeric_value(value: AnyValue) -> Option<f32> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_baseline_modes_with_missing_period_zero() {
+        // Cohort without a period 0 row: 80 users in period 1, 40 in period 2
+        let cohort_data: HashMap<i32, f32> = [(1, 80.0), (2, 40.0)].into_iter().collect();
+        let periods = vec![0, 1, 2];
+
+        let period_zero = cohort_retention(&cohort_data, &periods, &RetentionBaseline::PeriodZero);
+        let first_present =
+            cohort_retention(&cohort_data, &periods, &RetentionBaseline::FirstPresent);
+
+        assert_eq!(period_zero, vec![Some(0.0), Some(0.0), Some(0.0)]);
+        assert_eq!(first_present, vec![Some(0.0), Some(100.0), Some(50.0)]);
+        assert_ne!(period_zero, first_present);
+    }
+
+    #[test]
+    fn test_cohort_size_baseline() {
+        let cohort_data: HashMap<i32, f32> =
+            [(0, 90.0), (1, 120.0), (2, 60.0)].into_iter().collect();
+
+        assert_eq!(
+            cohort_baseline(&cohort_data, &RetentionBaseline::CohortSize),
+            Some(120.0)
+        );
+        assert_eq!(
+            cohort_baseline(&cohort_data, &RetentionBaseline::PeriodZero),
+            Some(90.0)
+        );
+    }
+
+    #[test]
+    fn test_average_row_is_the_mean_of_each_period() {
+        let table = RetentionTable {
+            cohorts: vec!["2023-01".into(), "2023-02".into(), "2023-03".into()],
+            periods: vec![0, 1, 2],
+            values: vec![
+                vec![Some(100.0), Some(60.0), Some(30.0)],
+                vec![Some(100.0), Some(50.0), Some(20.0)],
+                vec![Some(100.0), Some(40.0), Some(10.0)],
+            ],
+        };
+
+        let averages = table.period_averages();
+
+        assert_eq!(averages.len(), table.periods.len());
+        for (period_idx, average) in averages.iter().enumerate() {
+            let column: Vec<f32> = table
+                .values
+                .iter()
+                .flat_map(|row| row[period_idx])
+                .collect();
+            let mean = column.iter().sum::<f32>() / column.len() as f32;
+            assert!((average.unwrap() - mean).abs() < 1e-4);
+        }
+        assert_eq!(averages, vec![Some(100.0), Some(50.0), Some(20.0)]);
+    }
+
+    #[test]
+    fn test_average_row_skips_cohorts_that_have_not_reached_a_period() {
+        // A triangle: each younger cohort has one period fewer
+        let df = df! {
+            "cohort" => &["2023-01", "2023-01", "2023-01", "2023-02", "2023-02", "2023-03"],
+            "period" => &[0i64, 1, 2, 0, 1, 0],
+            "users" => &[100i64, 60, 30, 200, 100, 50],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Retention,
+            cohort_date: Some("cohort".to_string()),
+            period_number: Some("period".to_string()),
+            users: Some("users".to_string()),
+            ..Default::default()
+        };
+
+        let table = retention_table(&df, &config).unwrap().unwrap();
+        assert_eq!(table.values[2], vec![Some(100.0), None, None]);
+
+        let averages = table.period_averages();
+        assert_eq!(averages[0], Some(100.0));
+        assert_eq!(averages[1], Some(55.0));
+        // Only the oldest cohort has reached period 2
+        assert_eq!(averages[2], table.values[0][2]);
+        assert_eq!(averages[2], Some(30.0));
+    }
+
+    #[test]
+    fn test_survival_curves_never_rise() {
+        let table = RetentionTable {
+            cohorts: vec!["2023-01".into(), "2023-02".into(), "2023-03".into()],
+            periods: vec![0, 1, 2, 3],
+            values: vec![
+                vec![Some(100.0), Some(50.0), Some(60.0), Some(30.0)],
+                vec![Some(100.0), Some(70.0), Some(40.0), Some(45.0)],
+                // Has only reached period 1
+                vec![Some(100.0), Some(80.0), None, None],
+            ],
+        };
+
+        let curves = table.survival_curves();
+        for curve in &curves {
+            assert!(curve.windows(2).all(|pair| pair[1] <= pair[0]));
+        }
+        assert_eq!(curves[0], vec![100.0, 50.0, 50.0, 30.0]);
+        assert_eq!(curves[1], vec![100.0, 70.0, 40.0, 40.0]);
+        assert_eq!(curves[2], vec![100.0, 80.0]);
+
+        // Each value holds until the next period, then steps down
+        assert_eq!(
+            step_points(&table.periods[..2], &curves[2]),
+            vec![(0.0, 100.0), (1.0, 100.0), (1.0, 80.0), (2.0, 80.0)]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+//! Rolling mean and standard deviation for `rolling_band`
+
+/// The rolling mean at one point and the band `k` standard deviations either side of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BandPoint {
+    pub mean: f32,
+    pub lower: f32,
+    pub upper: f32,
+}
+
+/// Mean and sample standard deviation of each `window` values ending at a point
+///
+/// The first `window - 1` points have no full window and get `None`.
+pub fn rolling_stats(values: &[f32], window: usize) -> Vec<Option<(f32, f32)>> {
+    let window = window.max(2);
+    (0..values.len())
+        .map(|i| {
+            let start = (i + 1).checked_sub(window)?;
+            let slice = &values[start..=i];
+            let n = slice.len() as f64;
+            let mean = slice.iter().map(|&v| v as f64).sum::<f64>() / n;
+            let variance = slice
+                .iter()
+                .map(|&v| (v as f64 - mean).powi(2))
+                .sum::<f64>()
+                / (n - 1.0);
+            Some((mean as f32, variance.sqrt() as f32))
+        })
+        .collect()
+}
+
+/// The rolling mean of `values` with a band `k` rolling standard deviations either side
+pub fn rolling_band(values: &[f32], window: usize, k: f32) -> Vec<Option<BandPoint>> {
+    rolling_stats(values, window)
+        .into_iter()
+        .map(|stats| {
+            stats.map(|(mean, std)| BandPoint {
+                mean,
+                lower: mean - k * std,
+                upper: mean + k * std,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_band_is_k_rolling_deviations_wide() {
+        let values = [10.0, 12.0, 9.0, 14.0, 11.0, 13.0];
+        let band = rolling_band(&values, 3, 2.0);
+        let stats = rolling_stats(&values, 3);
+
+        assert!(band[0].is_none() && band[1].is_none());
+        // Window 9, 14, 11: mean 11.33, sample std 2.52
+        let point = band[4].unwrap();
+        let (mean, std) = stats[4].unwrap();
+        assert!((mean - 34.0 / 3.0).abs() < 1e-4);
+        assert!((std - 2.5166).abs() < 1e-3);
+        assert!((point.upper - point.mean - 2.0 * std).abs() < 1e-4);
+        assert!((point.mean - point.lower - 2.0 * std).abs() < 1e-4);
+    }
+}
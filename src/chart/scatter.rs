@@ -1,6 +1,12 @@
-use crate::render::styling::get_chart_style;
+use crate::chart::annotate::draw_extremes;
+use crate::chart::axis::{TickCountExt, YAxisSideExt, YCoord, drop_non_positive};
+use crate::chart::trend::{LinearFit, Trend};
+use crate::render::format::select_label_format;
+use crate::render::styling::{ChartStyle, get_chart_style_for, group_color_slots};
 use crate::spec::{ChartConfig, LegendPosition};
+use crate::warnings::warn_row_cap;
 use anyhow::{Context, Result};
+use plotters::coord::ranged1d::ValueFormatter;
 use plotters::prelude::*;
 use polars::prelude::*;
 
@@ -14,28 +20,38 @@ pub fn render<DB: DrawingBackend>(
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    let x_col = df
-        .column(config.x.as_ref().unwrap())
-        .context("X column not found")?;
-    let y_col = df
-        .column(config.y.as_ref().unwrap())
-        .context("Y column not found")?;
+    warn_row_cap("Scatter", df.height(), 1000)?;
 
-    let mut data_points = Vec::new();
+    let (mut points, groups) = scatter_points(df, config)?;
 
-    for i in 0..df.height().min(1000) {
-        // Limit points for performance but allow more than other charts
-        if let (Ok(x_val), Ok(y_val)) = (x_col.get(i), y_col.get(i)) {
-            let x = extract_numeric_value(x_val).unwrap_or(i as f32);
-            let y = extract_numeric_value(y_val).unwrap_or(0.0);
-            data_points.push((x, y));
+    // Log axes can't show zero or negative values
+    let log_x = config.log_x.unwrap_or(false);
+    if log_x {
+        let total = points.len();
+        points.retain(|((x, _), _)| *x > 0.0);
+        let dropped = total - points.len();
+        if dropped > 0 {
+            crate::warnings::warn(format!(
+                "log_x: dropped {} point(s) with non-positive x values",
+                dropped
+            ))?;
         }
     }
+    drop_non_positive(config, &mut points)?;
 
-    if data_points.is_empty() {
+    if points.is_empty() {
         return Ok(());
     }
 
+    let (data_points, point_groups): (Vec<(f32, f32)>, Vec<usize>) = points
+        .into_iter()
+        .map(|((x, group_idx), y)| ((x, y), group_idx))
+        .unzip();
+    let scatter_groups = ScatterGroups {
+        names: config.group_by.as_ref().map(|_| groups),
+        points: &point_groups,
+    };
+
     // Calculate ranges with padding
     let x_min = data_points
         .iter()
@@ -55,65 +71,231 @@ where
         .fold(f32::NEG_INFINITY, f32::max);
 
     // Add 10% padding to ranges
-    let x_range = {
-        let padding = (x_max - x_min) * 0.1;
-        (x_min - padding)..(x_max + padding)
-    };
     let y_range = {
         let padding = (y_max - y_min) * 0.1;
         (y_min - padding)..(y_max + padding)
     };
+    let y_values: Vec<f32> = data_points.iter().map(|(_, y)| *y).collect();
+    let y_range = YCoord::for_chart(config, y_range, &y_values);
 
-    let style = get_chart_style();
+    let style = get_chart_style_for(config);
 
-    let mut chart = ChartBuilder::on(&root)
+    let mut builder = ChartBuilder::on(&root);
+    builder
         .caption(title, style.title_font())
         .margin(style.layout.margins.chart as i32)
         .x_label_area_size(style.layout.areas.x_label_area)
-        .y_label_area_size(style.layout.areas.y_label_area)
-        .build_cartesian_2d(x_range, y_range)
-        .context("Failed to build chart")?;
+        .y_label_area_for(config, style.layout.areas.y_label_area);
+
+    if log_x {
+        let chart = builder
+            .build_cartesian_2d(log_x_range(x_min, x_max), y_range)
+            .context("Failed to build chart")?;
+        draw_scatter(chart, &data_points, &scatter_groups, config, &style)?;
+    } else {
+        let x_range = {
+            let padding = (x_max - x_min) * 0.1;
+            (x_min - padding)..(x_max + padding)
+        };
+        let chart = builder
+            .build_cartesian_2d(x_range, y_range)
+            .context("Failed to build chart")?;
+        draw_scatter(chart, &data_points, &scatter_groups, config, &style)?;
+    }
+
+    // Legend is now handled externally
+
+    root.present().context("Failed to present chart")?;
+    Ok(())
+}
+
+/// Points of the shown groups, from the first 1000 rows: (x, index into the returned group
+/// names) and y
+fn scatter_points(
+    df: &DataFrame,
+    config: &ChartConfig,
+) -> Result<(Vec<((f32, usize), f32)>, Vec<String>)> {
+    let x_col = df
+        .column(config.x.as_ref().unwrap())
+        .context("X column not found")?;
+    let y_col = df
+        .column(config.y.as_ref().unwrap())
+        .context("Y column not found")?;
+
+    let group_col = match &config.group_by {
+        Some(group_by) => Some(df.column(group_by).context("Group column not found")?),
+        None => None,
+    };
+
+    let mut points = Vec::new();
+    let mut groups: Vec<String> = Vec::new();
+
+    for i in 0..df.height().min(1000) {
+        // Limit points for performance but allow more than other charts
+        if let (Ok(x_val), Ok(y_val)) = (x_col.get(i), y_col.get(i)) {
+            let x = extract_numeric_value(x_val).unwrap_or(i as f32);
+            let y = extract_numeric_value(y_val).unwrap_or(0.0);
+            let group = match group_col.map(|column| column.get(i)) {
+                Some(Ok(value)) => value_label(value),
+                _ => String::new(),
+            };
+            let group_idx = groups.iter().position(|g| *g == group).unwrap_or_else(|| {
+                groups.push(group);
+                groups.len() - 1
+            });
+            points.push(((x, group_idx), y));
+        }
+    }
+    if group_col.is_some() {
+        points.retain(|((_, group_idx), _)| config.shows_series(&groups[*group_idx]));
+    }
+
+    Ok((points, groups))
+}
+
+/// The least squares line `trendline` draws through the plotted points, for reporting; `None`
+/// without two points at different x values
+pub fn trendline_fit(df: &DataFrame, config: &ChartConfig) -> Result<Option<LinearFit>> {
+    let log_x = config.log_x.unwrap_or(false);
+    let log_y = config.log_scale.unwrap_or(false);
+    let (points, _) = scatter_points(df, config)?;
+    let data_points: Vec<(f32, f32)> = points
+        .into_iter()
+        .map(|((x, _), y)| (x, y))
+        .filter(|&(x, y)| (!log_x || x > 0.0) && (!log_y || y > 0.0))
+        .collect();
+    Ok(LinearFit::fit(&data_points))
+}
+
+/// Log-scaled x coordinate padded by a constant factor on each side
+fn log_x_range(x_min: f32, x_max: f32) -> LogCoord<f32> {
+    (x_min / 1.5..x_max * 1.5).log_scale().into()
+}
+
+/// Which `group_by` value each point belongs to
+struct ScatterGroups<'a> {
+    /// Group names, or None when the chart is not grouped
+    names: Option<Vec<String>>,
+    /// Index into `names` of each point
+    points: &'a [usize],
+}
+
+fn draw_scatter<DB, X>(
+    mut chart: ChartContext<'_, DB, Cartesian2d<X, YCoord>>,
+    data_points: &[(f32, f32)],
+    groups: &ScatterGroups,
+    config: &ChartConfig,
+    style: &ChartStyle,
+) -> Result<()>
+where
+    DB: DrawingBackend,
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+    X: Ranged<ValueType = f32> + ValueFormatter<f32>,
+{
+    let y_values: Vec<f32> = data_points.iter().map(|(_, y)| *y).collect();
+    let y_labels = select_label_format(
+        config.y_format.as_ref(),
+        config.y.as_ref().unwrap(),
+        &y_values,
+    );
 
     chart
         .configure_mesh()
-        .y_desc(config.y.as_ref().unwrap())
-        .x_desc(config.x.as_ref().unwrap())
+        .ticks_for(config)
+        .y_desc(config.axis_label(config.y.as_ref().unwrap()))
+        .y_label_formatter(&|v| y_labels.format(*v))
+        .x_desc(config.axis_label(config.x.as_ref().unwrap()))
         .axis_desc_style(style.axis_desc_font())
         .label_style(style.axis_label_font())
         .draw()
         .context("Failed to draw mesh")?;
 
-    // Use the primary color for scatter points
-    let point_color = style.get_primary_color(0);
     let point_size = style.layout.elements.line_points; // Reuse line point size
 
-    // Draw scatter points
-    chart
-        .draw_series(
-            data_points
-                .iter()
-                .map(|(x, y)| Circle::new((*x, *y), point_size, point_color.filled())),
-        )
-        .context("Failed to draw scatter points")?
-        .label(format!(
-            "{} vs {}",
-            config.y.as_ref().unwrap(),
-            config.x.as_ref().unwrap()
-        ))
-        .legend(|(x, y)| {
-            Circle::new(
-                (x + 5, y),
-                style.layout.elements.line_points,
-                point_color.filled(),
+    if let Some(names) = &groups.names {
+        // One series per group, colored by the group's place among the sorted groups
+        let color_mode = config.color_mode.clone().unwrap_or_default();
+        let slots = group_color_slots(names);
+        for (group_idx, name) in names.iter().enumerate() {
+            if !config.shows_series(name) {
+                continue;
+            }
+            let color = *style.get_group_color(slots[group_idx], name, &color_mode);
+            chart
+                .draw_series(
+                    data_points
+                        .iter()
+                        .zip(groups.points)
+                        .filter(|(_, g)| **g == group_idx)
+                        .map(|((x, y), _)| Circle::new((*x, *y), point_size, color.filled())),
+                )
+                .context("Failed to draw scatter points")?
+                .label(name)
+                .legend(move |(x, y)| Circle::new((x + 5, y), point_size, color.filled()));
+        }
+    } else {
+        // Use the primary color for scatter points
+        let point_color = style.get_primary_color(0);
+
+        // Draw scatter points
+        chart
+            .draw_series(
+                data_points
+                    .iter()
+                    .map(|(x, y)| Circle::new((*x, *y), point_size, point_color.filled())),
             )
-        });
+            .context("Failed to draw scatter points")?
+            .label(format!(
+                "{} vs {}",
+                config.y.as_ref().unwrap(),
+                config.x.as_ref().unwrap()
+            ))
+            .legend(move |(x, y)| Circle::new((x + 5, y), point_size, point_color.filled()));
+    }
 
-    // Legend is now handled externally
+    // `trendline` draws the least squares line unless `trend_type` picks a curve; the line is
+    // reported by the caller, from `trendline_fit`
+    let trend = match &config.trend_type {
+        Some(kind) => Trend::fit(data_points, kind),
+        None if config.trendline.unwrap_or(false) => {
+            LinearFit::fit(data_points).map(|fit| fit.trend())
+        }
+        None => None,
+    };
+
+    if let Some(trend) = trend {
+        let x_min = data_points
+            .iter()
+            .map(|(x, _)| *x)
+            .fold(f32::INFINITY, f32::min);
+        let x_max = data_points
+            .iter()
+            .map(|(x, _)| *x)
+            .fold(f32::NEG_INFINITY, f32::max);
+
+        // Dense sampling so curved fits render smoothly
+        chart
+            .draw_series(LineSeries::new(
+                trend.curve(x_min, x_max, 200),
+                style.get_primary_color(1).stroke_width(2),
+            ))
+            .context("Failed to draw trend line")?;
+    }
+
+    if config.mark_extremes.unwrap_or(false) {
+        draw_extremes(&mut chart, data_points, style, |x| x.to_string())?;
+    }
 
-    root.present().context("Failed to present chart")?;
     Ok(())
 }
 
+fn value_label(value: AnyValue) -> String {
+    match value {
+        AnyValue::Utf8(s) => s.to_string(),
+        other => other.to_string(),
+    }
+}
+
 fn extract_numeric_value(value: AnyValue) -> Option<f32> {
     match value {
         AnyValue::Int32(i) => Some(i as f32),
@@ -125,3 +307,78 @@ fn extract_numeric_value(value: AnyValue) -> Option<f32> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_x_spacing_is_logarithmic() {
+        let coord = log_x_range(1.0, 1000.0);
+        let pixels: Vec<i32> = [1.0f32, 10.0, 100.0, 1000.0]
+            .iter()
+            .map(|x| coord.map(x, (0, 900)))
+            .collect();
+        let gaps: Vec<i32> = pixels.windows(2).map(|w| w[1] - w[0]).collect();
+
+        // Each decade takes the same horizontal distance
+        assert!(gaps[0] > 0);
+        assert!(gaps.iter().all(|gap| (gap - gaps[0]).abs() <= 1));
+    }
+
+    #[test]
+    fn test_trendline_fit_uses_the_plotted_points() {
+        let df = df! {
+            "spend" => &[0.0, 1.0, 2.0, 3.0],
+            "conversions" => &[50.0, 2.0, 4.0, 6.0],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Scatter,
+            x: Some("spend".to_string()),
+            y: Some("conversions".to_string()),
+            trendline: Some(true),
+            log_x: Some(true),
+            ..Default::default()
+        };
+
+        // The zero-spend point isn't drawn on a log axis, so it takes no part in the fit
+        let fit = trendline_fit(&df, &config).unwrap().unwrap();
+        assert!((fit.slope - 2.0).abs() < 1e-9);
+        assert!(fit.intercept.abs() < 1e-9);
+
+        let config = ChartConfig {
+            log_x: None,
+            ..config
+        };
+        assert!(trendline_fit(&df.head(Some(1)), &config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_log_x_render_succeeds() {
+        let df = df! {
+            "price" => &[1.0, 10.0, 100.0, 1000.0],
+            "volume" => &[5.0, 3.0, 8.0, 2.0],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: crate::spec::ChartType::Scatter,
+            x: Some("price".to_string()),
+            y: Some("volume".to_string()),
+            log_x: Some(true),
+            ..Default::default()
+        };
+
+        let mut buffer = vec![0u8; 400 * 300 * 3];
+        let root = BitMapBackend::with_buffer(&mut buffer, (400, 300)).into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        render(
+            &df,
+            &config,
+            root,
+            "Price vs Volume",
+            &LegendPosition::Right,
+        )
+        .unwrap();
+    }
+}
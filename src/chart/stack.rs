@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+
+/// Order stack groups bottom-to-top: groups named in `stack_order` first, in that order,
+/// followed by the remaining groups alphabetically
+pub fn order_groups(mut groups: Vec<String>, stack_order: Option<&[String]>) -> Vec<String> {
+    groups.sort();
+
+    let Some(stack_order) = stack_order else {
+        return groups;
+    };
+
+    let mut ordered: Vec<String> = stack_order
+        .iter()
+        .filter(|name| groups.contains(name))
+        .cloned()
+        .collect();
+    ordered.dedup();
+    for group in groups {
+        if !ordered.contains(&group) {
+            ordered.push(group);
+        }
+    }
+    ordered
+}
+
+/// Cumulative (start, end) segments per category, one per group in stacking order
+///
+/// Stacks diverge from zero: positive values build upward and negative values downward,
+/// so mixed-sign groups never overlap.
+pub fn stack_segments(
+    categories: &[String],
+    groups: &[String],
+    values: &HashMap<String, HashMap<String, f32>>,
+) -> Vec<Vec<(f32, f32)>> {
+    categories
+        .iter()
+        .map(|category| {
+            let category_values: Vec<f32> = groups
+                .iter()
+                .map(|group| {
+                    values
+                        .get(category)
+                        .and_then(|cat_map| cat_map.get(group))
+                        .copied()
+                        .unwrap_or(0.0)
+                })
+                .collect();
+            diverging_segments(&category_values)
+        })
+        .collect()
+}
+
+/// Stack `values` in order, positives above zero and negatives below it
+pub fn diverging_segments(values: &[f32]) -> Vec<(f32, f32)> {
+    let (mut above, mut below) = (0.0, 0.0);
+    values
+        .iter()
+        .map(|&value| {
+            let top = if value < 0.0 { &mut below } else { &mut above };
+            let segment = (*top, *top + value);
+            *top += value;
+            segment
+        })
+        .collect()
+}
+
+/// Lowest and highest stack edge across all categories, always including zero
+pub fn stack_extent<'a>(segments: impl IntoIterator<Item = &'a (f32, f32)>) -> (f32, f32) {
+    segments
+        .into_iter()
+        .fold((0.0f32, 0.0f32), |(low, high), &(start, end)| {
+            (low.min(start).min(end), high.max(start).max(end))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_order_groups_follows_stack_order() {
+        let groups = strings(&["churned", "new", "returning"]);
+        let stack_order = strings(&["returning", "new"]);
+
+        let ordered = order_groups(groups.clone(), Some(&stack_order));
+        assert_eq!(ordered, strings(&["returning", "new", "churned"]));
+
+        // Without an explicit order, groups stack alphabetically
+        assert_eq!(
+            order_groups(groups, None),
+            strings(&["churned", "new", "returning"])
+        );
+    }
+
+    #[test]
+    fn test_stack_segments_in_specified_order() {
+        let categories = strings(&["week 1"]);
+        let groups = order_groups(
+            strings(&["new", "returning"]),
+            Some(&strings(&["returning"])),
+        );
+        let values: HashMap<String, HashMap<String, f32>> = [(
+            "week 1".to_string(),
+            [("new".to_string(), 20.0), ("returning".to_string(), 30.0)]
+                .into_iter()
+                .collect(),
+        )]
+        .into_iter()
+        .collect();
+
+        let segments = stack_segments(&categories, &groups, &values);
+
+        // "returning" sits at the bottom even though "new" sorts first
+        assert_eq!(groups, strings(&["returning", "new"]));
+        assert_eq!(segments, vec![vec![(0.0, 30.0), (30.0, 50.0)]]);
+    }
+
+    #[test]
+    fn test_negative_values_stack_below_zero() {
+        // Churn is negative: it hangs below the baseline while the positives keep stacking up
+        let segments = diverging_segments(&[30.0, -10.0, 20.0, -5.0]);
+
+        assert_eq!(
+            segments,
+            vec![(0.0, 30.0), (0.0, -10.0), (30.0, 50.0), (-10.0, -15.0)]
+        );
+        assert_eq!(stack_extent(&segments), (-15.0, 50.0));
+    }
+}
@@ -0,0 +1,247 @@
+use crate::spec::TrendType;
+
+/// Fraction of the points that contribute to each local LOESS fit
+const LOESS_SPAN: f64 = 0.75;
+
+/// A curve fitted through scatter points
+#[derive(Debug, Clone)]
+pub enum Trend {
+    /// Least-squares polynomial coefficients, lowest degree first
+    Polynomial(Vec<f64>),
+    /// Local regression keeps the points and fits a weighted line around each x it is evaluated at
+    Loess(Vec<(f64, f64)>),
+}
+
+impl Trend {
+    /// Fit `points` with the given method; `None` when there are too few distinct x values
+    pub fn fit(points: &[(f32, f32)], kind: &TrendType) -> Option<Self> {
+        let points: Vec<(f64, f64)> = points.iter().map(|&(x, y)| (x as f64, y as f64)).collect();
+
+        match kind {
+            TrendType::Linear => fit_polynomial(&points, 1).map(Trend::Polynomial),
+            TrendType::Poly2 => fit_polynomial(&points, 2).map(Trend::Polynomial),
+            TrendType::Loess if points.len() >= 3 => Some(Trend::Loess(points)),
+            TrendType::Loess => None,
+        }
+    }
+
+    pub fn predict(&self, x: f32) -> f32 {
+        let x = x as f64;
+        let y = match self {
+            Trend::Polynomial(coefficients) => coefficients
+                .iter()
+                .rev()
+                .fold(0.0, |acc, coefficient| acc * x + coefficient),
+            Trend::Loess(points) => loess_at(points, x),
+        };
+        y as f32
+    }
+
+    /// The fitted curve sampled at `samples` evenly spaced x values from `x_min` to `x_max`
+    pub fn curve(&self, x_min: f32, x_max: f32, samples: usize) -> Vec<(f32, f32)> {
+        let steps = samples.max(2) - 1;
+        (0..=steps)
+            .map(|i| {
+                let x = x_min + (x_max - x_min) * i as f32 / steps as f32;
+                (x, self.predict(x))
+            })
+            .collect()
+    }
+
+    /// Sum of squared differences between the points and the fitted curve
+    pub fn residual(&self, points: &[(f32, f32)]) -> f64 {
+        points
+            .iter()
+            .map(|&(x, y)| (y as f64 - self.predict(x) as f64).powi(2))
+            .sum()
+    }
+}
+
+/// Ordinary least squares line through scatter points, as reported by `trendline`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinearFit {
+    pub slope: f64,
+    pub intercept: f64,
+    pub r_squared: f64,
+}
+
+impl LinearFit {
+    /// `None` with fewer than two points or when every point shares one x value
+    pub fn fit(points: &[(f32, f32)]) -> Option<Self> {
+        if points.len() < 2 {
+            return None;
+        }
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|&(x, _)| x as f64).sum::<f64>() / n;
+        let mean_y = points.iter().map(|&(_, y)| y as f64).sum::<f64>() / n;
+
+        let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+        for &(x, y) in points {
+            let (dx, dy) = (x as f64 - mean_x, y as f64 - mean_y);
+            sxx += dx * dx;
+            sxy += dx * dy;
+            syy += dy * dy;
+        }
+        if sxx < 1e-12 {
+            return None;
+        }
+
+        let slope = sxy / sxx;
+        // A flat y is fitted exactly by the flat line
+        let r_squared = if syy < 1e-12 {
+            1.0
+        } else {
+            sxy * sxy / (sxx * syy)
+        };
+        Some(LinearFit {
+            slope,
+            intercept: mean_y - slope * mean_x,
+            r_squared,
+        })
+    }
+
+    pub fn trend(&self) -> Trend {
+        Trend::Polynomial(vec![self.intercept, self.slope])
+    }
+}
+
+/// Least-squares polynomial of `degree` via the normal equations
+fn fit_polynomial(points: &[(f64, f64)], degree: usize) -> Option<Vec<f64>> {
+    let size = degree + 1;
+    if points.len() < size {
+        return None;
+    }
+
+    // Augmented normal-equation matrix: sum(x^(i+j)) | sum(y * x^i)
+    let mut matrix = vec![vec![0.0; size + 1]; size];
+    for &(x, y) in points {
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().take(size).enumerate() {
+                *cell += x.powi((i + j) as i32);
+            }
+            row[size] += y * x.powi(i as i32);
+        }
+    }
+
+    solve(matrix)
+}
+
+/// Gaussian elimination with partial pivoting on an augmented n x (n + 1) matrix
+fn solve(mut matrix: Vec<Vec<f64>>) -> Option<Vec<f64>> {
+    let n = matrix.len();
+
+    for column in 0..n {
+        let pivot = (column..n)
+            .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+
+        let pivot_row = matrix[column].clone();
+        for row in matrix.iter_mut().skip(column + 1) {
+            let factor = row[column] / pivot_row[column];
+            for (cell, pivot) in row.iter_mut().zip(&pivot_row).skip(column) {
+                *cell -= factor * pivot;
+            }
+        }
+    }
+
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let known: f64 = (row + 1..n).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (matrix[row][n] - known) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+/// Tricube-weighted linear fit over the nearest `LOESS_SPAN` of the points, evaluated at `x0`
+fn loess_at(points: &[(f64, f64)], x0: f64) -> f64 {
+    let mut distances: Vec<f64> = points.iter().map(|(x, _)| (x - x0).abs()).collect();
+    distances.sort_by(f64::total_cmp);
+    let neighbours = ((LOESS_SPAN * points.len() as f64).ceil() as usize).clamp(2, points.len());
+    let bandwidth = distances[neighbours - 1].max(f64::EPSILON) * 1.0001;
+
+    let (mut sw, mut sx, mut sy, mut sxx, mut sxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+    for &(x, y) in points {
+        let u = (x - x0).abs() / bandwidth;
+        if u >= 1.0 {
+            continue;
+        }
+        let w = (1.0 - u.powi(3)).powi(3);
+        sw += w;
+        sx += w * x;
+        sy += w * y;
+        sxx += w * x * x;
+        sxy += w * x * y;
+    }
+
+    if sw == 0.0 {
+        return points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+    }
+
+    let denominator = sw * sxx - sx * sx;
+    if denominator.abs() < 1e-12 {
+        return sy / sw;
+    }
+    let slope = (sw * sxy - sx * sy) / denominator;
+    let intercept = (sy - slope * sx) / sw;
+    intercept + slope * x0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quadratic_points() -> Vec<(f32, f32)> {
+        (-10..=10)
+            .map(|x| {
+                let x = x as f32;
+                (x, 2.0 * x * x - 3.0 * x + 1.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_poly2_fits_quadratic_better_than_linear() {
+        let points = quadratic_points();
+        let linear = Trend::fit(&points, &TrendType::Linear).unwrap();
+        let poly2 = Trend::fit(&points, &TrendType::Poly2).unwrap();
+
+        let linear_residual = linear.residual(&points);
+        let poly2_residual = poly2.residual(&points);
+        assert!(poly2_residual < 1e-3, "poly2 residual {}", poly2_residual);
+        assert!(linear_residual > 1000.0 * poly2_residual.max(1e-6));
+        assert!((poly2.predict(3.0) - 10.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_loess_follows_curve() {
+        let points = quadratic_points();
+        let loess = Trend::fit(&points, &TrendType::Loess).unwrap();
+        let linear = Trend::fit(&points, &TrendType::Linear).unwrap();
+        assert!(loess.residual(&points) < linear.residual(&points));
+    }
+
+    #[test]
+    fn test_linear_fit_recovers_a_straight_line() {
+        let points: Vec<(f32, f32)> = (0..10).map(|x| (x as f32, 3.0 * x as f32 - 2.0)).collect();
+        let fit = LinearFit::fit(&points).unwrap();
+        assert!((fit.slope - 3.0).abs() < 1e-9);
+        assert!((fit.intercept + 2.0).abs() < 1e-9);
+        assert!((fit.r_squared - 1.0).abs() < 1e-9);
+
+        // Too few points, or no spread in x, have no line
+        assert_eq!(LinearFit::fit(&points[..1]), None);
+        assert_eq!(LinearFit::fit(&[(2.0, 1.0), (2.0, 5.0), (2.0, 3.0)]), None);
+    }
+
+    #[test]
+    fn test_curve_spans_range() {
+        let trend = Trend::Polynomial(vec![1.0, 2.0]);
+        let curve = trend.curve(0.0, 10.0, 11);
+        assert_eq!(curve.len(), 11);
+        assert_eq!(curve[0], (0.0, 1.0));
+        assert_eq!(curve[10], (10.0, 21.0));
+    }
+}
@@ -36,6 +36,8 @@ pub enum OutputFormat {
     Png,
     Svg,
     Pdf,
+    Webp,
+    Html,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
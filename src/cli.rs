@@ -1,3 +1,4 @@
+use crate::error::ErrorKindExt;
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use std::fs;
@@ -25,13 +26,101 @@ pub struct Cli {
     #[arg(long, global = true, default_value = "light")]
     pub theme: Theme,
 
-    /// Canvas scale factor
+    /// Scale factor for the output size, text and lines of charts that don't set their own
     #[arg(long, global = true, default_value = "1.0")]
     pub scale: f64,
 
     /// Output format
     #[arg(long, global = true, default_value = "png")]
     pub format: OutputFormat,
+
+    /// Quote character used in the input CSV (e.g. "'")
+    #[arg(long, global = true)]
+    pub quote: Option<char>,
+
+    /// Treat warnings as errors
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Check the chart configuration and required columns without rendering
+    #[arg(long, global = true)]
+    pub validate_only: bool,
+
+    /// Render the legend to this file instead of beside the chart
+    #[arg(long, global = true)]
+    pub legend_out: Option<PathBuf>,
+
+    /// Round SVG coordinates and sizes to this many decimals to shrink the file
+    #[arg(long, global = true)]
+    pub svg_precision: Option<usize>,
+
+    /// WebP quality (0-100, 100 is lossless) for charts that don't set their own `webp_quality`
+    #[arg(long, global = true, value_parser = clap::value_parser!(u8).range(0..=100))]
+    pub webp_quality: Option<u8>,
+
+    /// Also write the plotted series as JSON to this file
+    #[arg(long, global = true)]
+    pub emit_data: Option<PathBuf>,
+
+    /// Canvas background as a hex color (#rrggbb) or "transparent", overriding the theme
+    #[arg(long, global = true)]
+    pub background: Option<String>,
+
+    /// Recolor PNG output as seen with a color-vision deficiency, to check accessibility
+    #[arg(long, global = true)]
+    pub simulate: Option<crate::render::simulate::ColorVision>,
+
+    /// How a fatal error is written to stderr
+    #[arg(long, global = true, default_value = "text")]
+    pub error_format: ErrorFormat,
+
+    /// How ambiguous dates such as 03/04/2023 are read: us (month first), eu (day first) or auto
+    #[arg(long, global = true, default_value = "us")]
+    pub date_order: crate::data::DateOrder,
+
+    /// Physical canvas size in inches (e.g. 6x4in), converted to pixels at --dpi; replaces width/height
+    #[arg(long, global = true, value_parser = parse_print_size)]
+    pub size: Option<PrintSize>,
+
+    /// Dots per inch used with --size [default: 300]
+    #[arg(long, global = true, requires = "size")]
+    pub dpi: Option<u32>,
+}
+
+/// A canvas size in inches, as given to `--size`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrintSize {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl PrintSize {
+    /// Pixel dimensions of this size printed at `dpi`
+    pub fn pixels(self, dpi: u32) -> (u32, u32) {
+        let to_pixels = |inches: f64| (inches * dpi as f64).round() as u32;
+        (to_pixels(self.width), to_pixels(self.height))
+    }
+}
+
+const DEFAULT_DPI: u32 = 300;
+
+/// Parse `--size` values such as `6x4in` or `8.5x11in`
+fn parse_print_size(value: &str) -> std::result::Result<PrintSize, String> {
+    let invalid = || format!("expected WIDTHxHEIGHTin such as 6x4in, got '{}'", value);
+    let dimensions = value.trim().strip_suffix("in").ok_or_else(invalid)?;
+    let (width, height) = dimensions.split_once(['x', 'X']).ok_or_else(invalid)?;
+    let inches = |text: &str| {
+        text.trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|inches| inches.is_finite() && *inches > 0.0)
+            .ok_or_else(invalid)
+    };
+
+    Ok(PrintSize {
+        width: inches(width)?,
+        height: inches(height)?,
+    })
 }
 
 #[derive(Subcommand)]
@@ -52,21 +141,31 @@ pub enum Commands {
     Funnel(FunnelArgs),
     /// Generate retention matrix for cohort analysis
     Retention(RetentionArgs),
+    /// Generate pie and donut charts for part-to-whole comparisons
+    Pie(PieArgs),
+    /// Generate box plots comparing the distribution of a value across groups
+    Box(BoxArgs),
     /// Batch render multiple charts from specification file
     Render(RenderArgs),
+    /// Lint a specification file for unused or redundant fields
+    Lint(LintArgs),
+    /// Write a starter specification file based on a CSV's columns
+    Init(InitArgs),
+    /// Compare two rendered PNGs and report the share of differing pixels
+    Diff(DiffArgs),
 }
 
 #[derive(Parser)]
 pub struct LineArgs {
-    /// Input CSV file path
-    #[arg(short, long)]
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
     pub input: PathBuf,
 
     /// X-axis column name
     #[arg(short, long)]
     pub x: String,
 
-    /// Y-axis column name
+    /// Y-axis column name, or a comma-separated list to draw a line per column
     #[arg(short, long)]
     pub y: String,
 
@@ -74,6 +173,30 @@ pub struct LineArgs {
     #[arg(short, long)]
     pub group: Option<String>,
 
+    /// Outer category column drawn as bracketed labels beneath the x labels (e.g. quarter over month)
+    #[arg(long)]
+    pub x2: Option<String>,
+
+    /// Use a logarithmic y-axis for values spanning orders of magnitude (non-positive values are skipped)
+    #[arg(long)]
+    pub log_scale: bool,
+
+    /// Y-axis label format: plain, thousands (1,500,000), si-compact (1.5M) or percent
+    #[arg(long, value_name = "FORMAT")]
+    pub number_format: Option<crate::spec::NumberFormat>,
+
+    /// Fix the bottom of the y-axis instead of fitting it to the data
+    #[arg(long, allow_negative_numbers = true)]
+    pub y_min: Option<f64>,
+
+    /// Fix the top of the y-axis instead of fitting it to the data
+    #[arg(long, allow_negative_numbers = true)]
+    pub y_max: Option<f64>,
+
+    /// Label each line's first and last value
+    #[arg(long)]
+    pub endpoint_labels: bool,
+
     /// Aggregation function
     #[arg(short, long, default_value = "sum")]
     pub agg: AggregationType,
@@ -101,8 +224,8 @@ pub struct LineArgs {
 
 #[derive(Parser)]
 pub struct AreaArgs {
-    /// Input CSV file path
-    #[arg(short, long)]
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
     pub input: PathBuf,
 
     /// X-axis column name
@@ -129,6 +252,22 @@ pub struct AreaArgs {
     #[arg(long)]
     pub normalize: bool,
 
+    /// Use a logarithmic y-axis for values spanning orders of magnitude (non-positive values are skipped)
+    #[arg(long)]
+    pub log_scale: bool,
+
+    /// Y-axis label format: plain, thousands (1,500,000), si-compact (1.5M) or percent
+    #[arg(long, value_name = "FORMAT")]
+    pub number_format: Option<crate::spec::NumberFormat>,
+
+    /// Fix the bottom of the y-axis instead of fitting it to the data
+    #[arg(long, allow_negative_numbers = true)]
+    pub y_min: Option<f64>,
+
+    /// Fix the top of the y-axis instead of fitting it to the data
+    #[arg(long, allow_negative_numbers = true)]
+    pub y_max: Option<f64>,
+
     /// Filter expression
     #[arg(short, long)]
     pub filter: Option<String>,
@@ -152,22 +291,49 @@ pub struct AreaArgs {
 
 #[derive(Parser)]
 pub struct BarArgs {
-    /// Input CSV file path
-    #[arg(short, long)]
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
     pub input: PathBuf,
 
     /// X-axis column name
     #[arg(short, long)]
     pub x: String,
 
-    /// Y-axis column name
+    /// Y-axis column name, or `column:agg` pairs (e.g. `sessions:count,revenue:sum`)
+    /// drawn as bars for the first series and lines for the rest; optional with `--agg count`,
+    /// which then counts rows per x
     #[arg(short, long)]
-    pub y: String,
+    pub y: Option<String>,
 
     /// Group by column
     #[arg(short, long)]
     pub group: Option<String>,
 
+    /// Outer category column drawn as bracketed labels beneath the x labels (e.g. quarter over month)
+    #[arg(long)]
+    pub x2: Option<String>,
+
+    /// Use a logarithmic y-axis for values spanning orders of magnitude (non-positive values are skipped)
+    #[arg(long)]
+    pub log_scale: bool,
+
+    /// Y-axis label format: plain, thousands (1,500,000), si-compact (1.5M) or percent
+    #[arg(long, value_name = "FORMAT")]
+    pub number_format: Option<crate::spec::NumberFormat>,
+
+    /// Fix the bottom of the y-axis instead of fitting it to the data
+    #[arg(long, allow_negative_numbers = true)]
+    pub y_min: Option<f64>,
+
+    /// Fix the top of the y-axis instead of fitting it to the data
+    #[arg(long, allow_negative_numbers = true)]
+    pub y_max: Option<f64>,
+
+    /// Second measure as `column:agg` (e.g. `revenue:mean`), drawn as a line over the bars on a
+    /// secondary axis
+    #[arg(long, value_name = "COLUMN:AGG")]
+    pub overlay: Option<String>,
+
     /// Aggregation function
     #[arg(short, long, default_value = "sum")]
     pub agg: AggregationType,
@@ -180,6 +346,10 @@ pub struct BarArgs {
     #[arg(long)]
     pub horizontal: bool,
 
+    /// Write each bar's value on it, formatted with thousands separators
+    #[arg(long)]
+    pub data_labels: bool,
+
     /// Filter expression
     #[arg(short, long)]
     pub filter: Option<String>,
@@ -203,8 +373,8 @@ pub struct BarArgs {
 
 #[derive(Parser)]
 pub struct HeatmapArgs {
-    /// Input CSV file path
-    #[arg(short, long)]
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
     pub input: PathBuf,
 
     /// X-axis column name
@@ -246,8 +416,8 @@ pub struct HeatmapArgs {
 
 #[derive(Parser)]
 pub struct FunnelArgs {
-    /// Input CSV file path
-    #[arg(short, long)]
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
     pub input: PathBuf,
 
     /// Comma-separated step names in order
@@ -262,14 +432,27 @@ pub struct FunnelArgs {
     #[arg(long, default_value = "right")]
     pub value_labels: crate::spec::ValueLabelPosition,
 
-    /// Value column name
+    /// Value column name (one pre-aggregated value per step, aligned by row)
     #[arg(long)]
-    pub values: String,
+    pub values: Option<String>,
+
+    /// Column holding step names: with --user-column, users are counted per step from raw
+    /// events; with --values, each step's value is read from the row carrying its name
+    #[arg(long)]
+    pub step_column: Option<String>,
+
+    /// User id column counted per step (used with --step-column)
+    #[arg(long, requires = "step_column")]
+    pub user_column: Option<String>,
 
     /// Show conversion rates between steps
     #[arg(long)]
     pub conversion_rates: bool,
 
+    /// Color steps by how many users they lose, deepest red for the largest drop
+    #[arg(long)]
+    pub highlight_dropoff: bool,
+
     /// Chart title
     #[arg(short, long)]
     pub title: Option<String>,
@@ -278,19 +461,19 @@ pub struct FunnelArgs {
     #[arg(short, long)]
     pub out: Option<PathBuf>,
 
-    /// Canvas width in pixels
-    #[arg(long, default_value = "1400")]
-    pub width: u32,
+    /// Canvas width in pixels [default: 1000 for funnels]
+    #[arg(long)]
+    pub width: Option<u32>,
 
-    /// Canvas height in pixels
-    #[arg(long, default_value = "800")]
-    pub height: u32,
+    /// Canvas height in pixels [default: 1200 for funnels]
+    #[arg(long)]
+    pub height: Option<u32>,
 }
 
 #[derive(Parser)]
 pub struct RetentionArgs {
-    /// Input CSV file path
-    #[arg(short, long)]
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
     pub input: PathBuf,
 
     /// Cohort start date column
@@ -309,6 +492,10 @@ pub struct RetentionArgs {
     #[arg(long)]
     pub percentage: bool,
 
+    /// Draw each cohort's share still active as a step curve instead of the matrix
+    #[arg(long)]
+    pub survival: bool,
+
     /// Chart title
     #[arg(short, long)]
     pub title: Option<String>,
@@ -317,13 +504,13 @@ pub struct RetentionArgs {
     #[arg(short, long)]
     pub out: Option<PathBuf>,
 
-    /// Canvas width in pixels
-    #[arg(long, default_value = "1400")]
-    pub width: u32,
+    /// Canvas width in pixels [default: 1800 for retention matrices]
+    #[arg(long)]
+    pub width: Option<u32>,
 
-    /// Canvas height in pixels
-    #[arg(long, default_value = "800")]
-    pub height: u32,
+    /// Canvas height in pixels [default: 900 for retention matrices]
+    #[arg(long)]
+    pub height: Option<u32>,
 }
 
 #[derive(Parser)]
@@ -340,17 +527,65 @@ pub struct RenderArgs {
     #[arg(short, long)]
     pub out: Option<PathBuf>,
 
-    /// Number of parallel renders
+    /// Number of charts rendered at once (defaults to the number of CPU cores)
     #[arg(short, long)]
     pub parallel: Option<usize>,
+
+    /// Fail any chart that takes longer than this many seconds to render and move on
+    #[arg(long)]
+    pub timeout: Option<u64>,
+
+    /// Only render these charts (comma-separated titles or 1-based indices, e.g. "2,Weekly Users")
+    #[arg(long)]
+    pub only: Option<String>,
+
+    /// Write a JSON manifest of every chart and whether it rendered, to PATH or to manifest.json
+    /// in the output directory
+    #[arg(long, value_name = "PATH", num_args = 0..=1)]
+    pub manifest: Option<Option<PathBuf>>,
 }
 
 #[derive(Parser)]
-pub struct BarStackedArgs {
+pub struct LintArgs {
+    /// YAML or JSON specification file
+    #[arg(short, long)]
+    pub spec: PathBuf,
+}
+
+#[derive(Parser)]
+pub struct InitArgs {
     /// Input CSV file path
     #[arg(short, long)]
     pub input: PathBuf,
 
+    /// Spec file to write
+    #[arg(short, long, default_value = "charts.yaml")]
+    pub out: PathBuf,
+
+    /// Overwrite the spec file if it already exists
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Parser)]
+pub struct DiffArgs {
+    /// First image
+    pub a: PathBuf,
+
+    /// Second image, the same size as the first
+    pub b: PathBuf,
+
+    /// Write an image highlighting the differing pixels in red
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+}
+
+#[derive(Parser)]
+pub struct BarStackedArgs {
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
+    pub input: PathBuf,
+
     /// X-axis column name
     #[arg(short, long)]
     pub x: String,
@@ -367,6 +602,10 @@ pub struct BarStackedArgs {
     #[arg(short, long, default_value = "sum")]
     pub agg: AggregationType,
 
+    /// Write each segment's value on it and each stack's total above it
+    #[arg(long)]
+    pub data_labels: bool,
+
     /// Filter expression
     #[arg(short, long)]
     pub filter: Option<String>,
@@ -389,9 +628,87 @@ pub struct BarStackedArgs {
 }
 
 #[derive(Parser)]
-pub struct ScatterArgs {
-    /// Input CSV file path
+pub struct PieArgs {
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
+    pub input: PathBuf,
+
+    /// Category column, one wedge per value
+    #[arg(short, long)]
+    pub x: String,
+
+    /// Value column sizing the wedges; optional with `--agg count`, which then counts rows
+    #[arg(short, long)]
+    pub y: Option<String>,
+
+    /// Aggregation function
+    #[arg(short, long, default_value = "sum")]
+    pub agg: AggregationType,
+
+    /// Leave the center hollow
+    #[arg(long)]
+    pub donut: bool,
+
+    /// Filter expression
+    #[arg(short, long)]
+    pub filter: Option<String>,
+
+    /// Chart title
+    #[arg(short, long)]
+    pub title: Option<String>,
+
+    /// Output file path
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+
+    /// Canvas width in pixels
+    #[arg(long, default_value = "1400")]
+    pub width: u32,
+
+    /// Canvas height in pixels
+    #[arg(long, default_value = "800")]
+    pub height: u32,
+}
+
+#[derive(Parser)]
+pub struct BoxArgs {
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
+    pub input: PathBuf,
+
+    /// Category column, one box per value
+    #[arg(short, long)]
+    pub group: String,
+
+    /// Value column whose spread each box shows
+    #[arg(short, long)]
+    pub y: String,
+
+    /// Filter expression
+    #[arg(short, long)]
+    pub filter: Option<String>,
+
+    /// Chart title
+    #[arg(short, long)]
+    pub title: Option<String>,
+
+    /// Output file path
     #[arg(short, long)]
+    pub out: Option<PathBuf>,
+
+    /// Canvas width in pixels
+    #[arg(long, default_value = "1400")]
+    pub width: u32,
+
+    /// Canvas height in pixels
+    #[arg(long, default_value = "800")]
+    pub height: u32,
+}
+
+#[derive(Parser)]
+pub struct ScatterArgs {
+    /// Input CSV file path; `-` or omitting it reads CSV from stdin
+    #[arg(short, long, default_value = "-")]
     pub input: PathBuf,
 
     /// X-axis column name
@@ -406,6 +723,34 @@ pub struct ScatterArgs {
     #[arg(short, long)]
     pub group: Option<String>,
 
+    /// Use a logarithmic x-axis (non-positive x values are dropped)
+    #[arg(long)]
+    pub log_x: bool,
+
+    /// Use a logarithmic y-axis for values spanning orders of magnitude (non-positive values are skipped)
+    #[arg(long)]
+    pub log_scale: bool,
+
+    /// Y-axis label format: plain, thousands (1,500,000), si-compact (1.5M) or percent
+    #[arg(long, value_name = "FORMAT")]
+    pub number_format: Option<crate::spec::NumberFormat>,
+
+    /// Fix the bottom of the y-axis instead of fitting it to the data
+    #[arg(long, allow_negative_numbers = true)]
+    pub y_min: Option<f64>,
+
+    /// Fix the top of the y-axis instead of fitting it to the data
+    #[arg(long, allow_negative_numbers = true)]
+    pub y_max: Option<f64>,
+
+    /// Draw a fitted trend curve
+    #[arg(long, value_enum)]
+    pub trend: Option<crate::spec::TrendType>,
+
+    /// Draw a least squares line and print its slope, intercept and R²
+    #[arg(long)]
+    pub trendline: bool,
+
     /// Filter expression
     #[arg(short, long)]
     pub filter: Option<String>,
@@ -439,11 +784,21 @@ pub enum ValueLabelPosition {
     Right,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum ErrorFormat {
+    /// The message and its causes as plain text
+    Text,
+    /// `{ "error": ..., "context": [...] }` on one line
+    Json,
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum OutputFormat {
     Png,
     Svg,
     Pdf,
+    Webp,
+    Html,
 }
 
 #[derive(clap::ValueEnum, Clone, Debug)]
@@ -477,6 +832,59 @@ fn convert_agg_type(cli_agg: &AggregationType) -> crate::spec::AggregationType {
     }
 }
 
+/// Parse `sessions:count,revenue:sum` into per-series aggregations;
+/// `None` when `y` is a plain column name
+fn parse_series_list(y: &str) -> Result<Option<Vec<crate::spec::SeriesSpec>>> {
+    if !y.contains(':') {
+        return Ok(None);
+    }
+
+    y.split(',')
+        .map(|part| {
+            let (column, agg) = part
+                .trim()
+                .split_once(':')
+                .ok_or_else(|| anyhow::anyhow!("Expected 'column:agg', got '{}'", part))?;
+            let agg = <AggregationType as clap::ValueEnum>::from_str(agg.trim(), true)
+                .map_err(|_| anyhow::anyhow!("Unknown aggregation '{}' in '{}'", agg, part))?;
+            Ok(crate::spec::SeriesSpec {
+                column: column.trim().to_string(),
+                agg: convert_agg_type(&agg),
+            })
+        })
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
+}
+
+/// Combo series for a bar chart: `--y` as parsed by [`parse_series_list`], plus the `--overlay`
+/// measure drawn as a line after them
+fn bar_series(
+    y: &str,
+    agg: &AggregationType,
+    overlay: Option<&str>,
+) -> Result<Option<Vec<crate::spec::SeriesSpec>>> {
+    let series = parse_series_list(y)?;
+    let Some(overlay) = overlay else {
+        return Ok(series);
+    };
+
+    let overlay = match parse_series_list(overlay)? {
+        Some(overlay) if overlay.len() == 1 => overlay,
+        _ => anyhow::bail!(
+            "Expected --overlay as a single 'column:agg', got '{}'",
+            overlay
+        ),
+    };
+    let mut series = series.unwrap_or_else(|| {
+        vec![crate::spec::SeriesSpec {
+            column: y.to_string(),
+            agg: convert_agg_type(agg),
+        }]
+    });
+    series.extend(overlay);
+    Ok(Some(series))
+}
+
 fn convert_colormap_type(cli_colormap: &ColorMap) -> crate::spec::ColorMap {
     match cli_colormap {
         ColorMap::Viridis => crate::spec::ColorMap::Viridis,
@@ -494,17 +902,20 @@ fn convert_theme_type(cli_theme: &Theme) -> crate::spec::Theme {
     }
 }
 
-fn parse_filter_string(filter_str: &str) -> Result<crate::spec::FilterConfig> {
-    // Simple filter parsing - for now just create a basic filter
-    // This could be enhanced to parse more complex filter expressions
-    let mut include = std::collections::HashMap::new();
-    include.insert(
-        "expression".to_string(),
-        crate::spec::FilterValue::Single(filter_str.to_string()),
-    );
+fn convert_format_type(cli_format: &OutputFormat) -> crate::spec::OutputFormat {
+    match cli_format {
+        OutputFormat::Png => crate::spec::OutputFormat::Png,
+        OutputFormat::Svg => crate::spec::OutputFormat::Svg,
+        OutputFormat::Pdf => crate::spec::OutputFormat::Pdf,
+        OutputFormat::Webp => crate::spec::OutputFormat::Webp,
+        OutputFormat::Html => crate::spec::OutputFormat::Html,
+    }
+}
 
+fn parse_filter_string(filter_str: &str) -> Result<crate::spec::FilterConfig> {
+    // `--filter` takes the same expression syntax as a spec's `filter.expression`
     Ok(crate::spec::FilterConfig {
-        include: Some(include),
+        include: None,
         exclude: None,
         expression: Some(filter_str.to_string()),
     })
@@ -516,27 +927,155 @@ pub fn run(cli: Cli) -> Result<()> {
         println!("Verbose mode enabled");
     }
 
+    crate::warnings::set_strict(cli.strict);
+    let options = RunOptions {
+        load: build_load_options(&cli)?,
+        validate_only: cli.validate_only,
+        legend_out: cli.legend_out.clone(),
+        svg_precision: cli.svg_precision,
+        emit_data: cli.emit_data.clone(),
+        background: cli.background.clone(),
+        simulate: cli.simulate,
+        format: convert_format_type(&cli.format),
+        size: cli
+            .size
+            .map(|size| size.pixels(cli.dpi.unwrap_or(DEFAULT_DPI))),
+        scale: (cli.scale != 1.0).then_some(cli.scale),
+        webp_quality: cli.webp_quality,
+    };
+
     match cli.command {
-        Commands::Line(args) => render_line_chart_cli(args, &cli.theme),
-        Commands::Area(args) => render_area_chart_cli(args, &cli.theme),
-        Commands::Bar(args) => render_bar_chart_cli(args, &cli.theme),
-        Commands::BarStacked(args) => render_bar_stacked_chart_cli(args, &cli.theme),
-        Commands::Heatmap(args) => render_heatmap_chart_cli(args, &cli.theme),
-        Commands::Scatter(args) => render_scatter_chart_cli(args, &cli.theme),
-        Commands::Funnel(args) => render_funnel_chart_cli(args, &cli.theme),
-        Commands::Retention(args) => render_retention_chart_cli(args, &cli.theme),
-        Commands::Render(args) => render_batch_charts(args),
+        Commands::Line(args) => render_line_chart_cli(args, &cli.theme, &options),
+        Commands::Area(args) => render_area_chart_cli(args, &cli.theme, &options),
+        Commands::Bar(args) => render_bar_chart_cli(args, &cli.theme, &options),
+        Commands::BarStacked(args) => render_bar_stacked_chart_cli(args, &cli.theme, &options),
+        Commands::Heatmap(args) => render_heatmap_chart_cli(args, &cli.theme, &options),
+        Commands::Scatter(args) => render_scatter_chart_cli(args, &cli.theme, &options),
+        Commands::Funnel(args) => render_funnel_chart_cli(args, &cli.theme, &options),
+        Commands::Retention(args) => render_retention_chart_cli(args, &cli.theme, &options),
+        Commands::Pie(args) => render_pie_chart_cli(args, &cli.theme, &options),
+        Commands::Box(args) => render_box_chart_cli(args, &cli.theme, &options),
+        Commands::Render(args) => render_batch_charts(args, &options),
+        Commands::Lint(args) => lint_spec(args),
+        Commands::Init(args) => init_spec(args, &options),
+        Commands::Diff(args) => diff_images_cli(args),
+    }
+}
+
+/// Options from global flags that apply to every chart processed in a run
+#[derive(Clone)]
+pub struct RunOptions {
+    pub load: crate::data::LoadOptions,
+    /// Check config and columns without rendering
+    pub validate_only: bool,
+    /// Separate file for the legend; charts render without one
+    pub legend_out: Option<PathBuf>,
+    /// Decimal places kept in SVG output numbers
+    pub svg_precision: Option<usize>,
+    /// JSON file for the plotted series
+    pub emit_data: Option<PathBuf>,
+    /// Background for every chart, replacing the spec's
+    pub background: Option<String>,
+    /// Color-vision deficiency applied to PNG output
+    pub simulate: Option<crate::render::simulate::ColorVision>,
+    /// Output format for single-chart commands; specs set their own per chart
+    pub format: crate::spec::OutputFormat,
+    /// Canvas size in pixels from --size and --dpi, replacing every chart's width and height
+    pub size: Option<(u32, u32)>,
+    /// --scale for charts that don't set their own `scale`
+    pub scale: Option<f64>,
+    /// --webp-quality for charts that don't set their own `webp_quality`
+    pub webp_quality: Option<u8>,
+}
+
+impl RunOptions {
+    /// `chart_config` with the run-wide --background and --size applied over the chart's own,
+    /// and --scale and --webp-quality where the chart has none
+    fn apply_overrides(&self, chart_config: &crate::spec::ChartConfig) -> crate::spec::ChartConfig {
+        let mut chart_config = chart_config.clone();
+        if let Some(background) = &self.background {
+            chart_config.background = Some(background.clone());
+        }
+        if chart_config.scale.is_none() {
+            chart_config.scale = self.scale;
+        }
+        if chart_config.webp_quality.is_none() {
+            chart_config.webp_quality = self.webp_quality;
+        }
+        // --size is the exact output size, so nothing scales it further
+        if let Some((width, height)) = self.size {
+            chart_config.width = Some(width);
+            chart_config.height = Some(height);
+            chart_config.scale = None;
+            chart_config.format_sizes = None;
+        }
+        chart_config
+    }
+}
+
+/// Where a single-chart command reads its CSV: the `--input` file, or stdin for `-`
+fn input_source(input: &Path) -> Result<crate::data::DataSource> {
+    use std::io::IsTerminal;
+
+    if input != Path::new("-") {
+        return Ok(crate::data::DataSource::File(input.to_path_buf()));
+    }
+    if std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "No input: pass --input <FILE> or pipe CSV to stdin"
+        ))
+        .usage_error();
+    }
+    Ok(crate::data::DataSource::Stdin)
+}
+
+/// Where a single-chart command writes without `--out`: `<input stem>-<kind>.<ext>`, or just
+/// `<kind>.<ext>` when reading stdin
+fn default_output_path(input: &Path, kind: &str, format: &crate::spec::OutputFormat) -> PathBuf {
+    let stem = input
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .filter(|_| input != Path::new("-"));
+    match stem {
+        Some(stem) => PathBuf::from(format!("{}-{}.{}", stem, kind, format.extension())),
+        None => PathBuf::from(format!("{}.{}", kind, format.extension())),
+    }
+}
+
+fn build_load_options(cli: &Cli) -> Result<crate::data::LoadOptions> {
+    let mut options = crate::data::LoadOptions::default();
+
+    if let Some(quote) = cli.quote {
+        if !quote.is_ascii() {
+            anyhow::bail!(
+                "Quote character must be a single ASCII character, got '{}'",
+                quote
+            );
+        }
+        options.quote_char = Some(quote as u8);
     }
+    options.date_order = cli.date_order;
+
+    Ok(options)
 }
 
-fn render_line_chart_cli(args: LineArgs, theme: &Theme) -> Result<()> {
+fn render_line_chart_cli(args: LineArgs, theme: &Theme, options: &RunOptions) -> Result<()> {
+    // `--y sessions,users` draws a line per column
+    let (y, ys) = match args.y.split_once(',') {
+        Some(_) => (
+            None,
+            Some(args.y.split(',').map(|y| y.trim().to_string()).collect()),
+        ),
+        None => (Some(args.y.clone()), None),
+    };
+
     // Create chart configuration
     let chart_config = crate::spec::ChartConfig {
         chart_type: crate::spec::ChartType::Line,
         title: args.title,
         data: Some(args.input.clone()),
         x: Some(args.x.clone()),
-        y: Some(args.y.clone()),
+        y,
         z: None,
         group_by: args.group.clone(),
         agg: Some(convert_agg_type(&args.agg)),
@@ -551,7 +1090,7 @@ fn render_line_chart_cli(args: LineArgs, theme: &Theme) -> Result<()> {
         width: Some(args.width),
         height: Some(args.height),
         theme: Some(convert_theme_type(theme)),
-        format: Some(crate::spec::OutputFormat::Png),
+        format: Some(options.format.clone()),
         scale: None,
         stacked: None,
         horizontal: None,
@@ -568,28 +1107,79 @@ fn render_line_chart_cli(args: LineArgs, theme: &Theme) -> Result<()> {
         users: None,
         percentage: None,
         legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: args.number_format,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: args.x2,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: args.log_scale.then_some(true),
+        survival: None,
+        endpoint_labels: args.endpoint_labels.then_some(true),
+        webp_quality: None,
+        data_labels: None,
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: args.y_min,
+        y_max: args.y_max,
+        donut: None,
     };
 
     // Determine output path
-    let output_path = if let Some(out_path) = &args.out {
-        out_path.clone()
-    } else {
-        let input_stem = args
-            .input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("line");
-        PathBuf::from(format!("{}-line.png", input_stem))
-    };
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "line", &options.format));
 
     // Render the chart using the existing pipeline
-    process_single_chart(&args.input, &chart_config, &output_path)?;
-
-    println!("✅ Generated line chart: {}", output_path.display());
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("line chart", &output_path, options);
     Ok(())
 }
 
-fn render_area_chart_cli(args: AreaArgs, theme: &Theme) -> Result<()> {
+fn render_area_chart_cli(args: AreaArgs, theme: &Theme, options: &RunOptions) -> Result<()> {
     // Create chart configuration
     let chart_config = crate::spec::ChartConfig {
         chart_type: crate::spec::ChartType::Area,
@@ -611,7 +1201,7 @@ fn render_area_chart_cli(args: AreaArgs, theme: &Theme) -> Result<()> {
         width: Some(args.width),
         height: Some(args.height),
         theme: Some(convert_theme_type(theme)),
-        format: Some(crate::spec::OutputFormat::Png),
+        format: Some(options.format.clone()),
         scale: None,
         stacked: Some(args.stacked),
         horizontal: None,
@@ -628,35 +1218,102 @@ fn render_area_chart_cli(args: AreaArgs, theme: &Theme) -> Result<()> {
         users: None,
         percentage: None,
         legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: args.number_format,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: None,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: args.log_scale.then_some(true),
+        survival: None,
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: None,
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: args.y_min,
+        y_max: args.y_max,
+        donut: None,
     };
 
     // Determine output path
-    let output_path = if let Some(out_path) = &args.out {
-        out_path.clone()
-    } else {
-        let input_stem = args
-            .input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("area");
-        PathBuf::from(format!("{}-area.png", input_stem))
-    };
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "area", &options.format));
 
     // Render the chart using the existing pipeline
-    process_single_chart(&args.input, &chart_config, &output_path)?;
-
-    println!("✅ Generated area chart: {}", output_path.display());
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("area chart", &output_path, options);
     Ok(())
 }
 
-fn render_bar_chart_cli(args: BarArgs, theme: &Theme) -> Result<()> {
+fn render_bar_chart_cli(args: BarArgs, theme: &Theme, options: &RunOptions) -> Result<()> {
+    // `--y sessions:count,revenue:sum` describes a combo of aggregated series, as does `--overlay`
+    if args.overlay.is_some() && args.group.is_some() {
+        return Err(anyhow::anyhow!("--overlay cannot be combined with --group")).usage_error();
+    }
+    let series = match &args.y {
+        Some(y) => bar_series(y, &args.agg, args.overlay.as_deref()).usage_error()?,
+        None if args.overlay.is_some() => {
+            return Err(anyhow::anyhow!("--overlay needs a --y column for the bars")).usage_error();
+        }
+        None => None,
+    };
+    let y = series
+        .as_ref()
+        .map(|series| series[0].column.clone())
+        .or_else(|| args.y.clone());
+
     // Create chart configuration
     let chart_config = crate::spec::ChartConfig {
         chart_type: crate::spec::ChartType::Bar,
         title: args.title,
         data: Some(args.input.clone()),
         x: Some(args.x.clone()),
-        y: Some(args.y.clone()),
+        y,
         z: None,
         group_by: args.group.clone(),
         agg: Some(convert_agg_type(&args.agg)),
@@ -671,7 +1328,7 @@ fn render_bar_chart_cli(args: BarArgs, theme: &Theme) -> Result<()> {
         width: Some(args.width),
         height: Some(args.height),
         theme: Some(convert_theme_type(theme)),
-        format: Some(crate::spec::OutputFormat::Png),
+        format: Some(options.format.clone()),
         scale: None,
         stacked: Some(args.stacked),
         horizontal: Some(args.horizontal),
@@ -688,28 +1345,79 @@ fn render_bar_chart_cli(args: BarArgs, theme: &Theme) -> Result<()> {
         users: None,
         percentage: None,
         legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: args.number_format,
+        y_lower: None,
+        y_upper: None,
+        series,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: args.x2,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: args.log_scale.then_some(true),
+        survival: None,
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: args.data_labels.then_some(true),
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: args.y_min,
+        y_max: args.y_max,
+        donut: None,
     };
 
     // Determine output path
-    let output_path = if let Some(out_path) = &args.out {
-        out_path.clone()
-    } else {
-        let input_stem = args
-            .input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("bar");
-        PathBuf::from(format!("{}-bar.png", input_stem))
-    };
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "bar", &options.format));
 
     // Render the chart using the existing pipeline
-    process_single_chart(&args.input, &chart_config, &output_path)?;
-
-    println!("✅ Generated bar chart: {}", output_path.display());
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("bar chart", &output_path, options);
     Ok(())
 }
 
-fn render_heatmap_chart_cli(args: HeatmapArgs, theme: &Theme) -> Result<()> {
+fn render_heatmap_chart_cli(args: HeatmapArgs, theme: &Theme, options: &RunOptions) -> Result<()> {
     // Create chart configuration
     let chart_config = crate::spec::ChartConfig {
         chart_type: crate::spec::ChartType::Heatmap,
@@ -727,7 +1435,7 @@ fn render_heatmap_chart_cli(args: HeatmapArgs, theme: &Theme) -> Result<()> {
         width: Some(args.width),
         height: Some(args.height),
         theme: Some(convert_theme_type(theme)),
-        format: Some(crate::spec::OutputFormat::Png),
+        format: Some(options.format.clone()),
         scale: None,
         stacked: None,
         horizontal: None,
@@ -744,28 +1452,83 @@ fn render_heatmap_chart_cli(args: HeatmapArgs, theme: &Theme) -> Result<()> {
         users: None,
         percentage: None,
         legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: None,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: None,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: None,
+        survival: None,
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: None,
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: None,
+        y_max: None,
+        donut: None,
     };
 
     // Determine output path
-    let output_path = if let Some(out_path) = &args.out {
-        out_path.clone()
-    } else {
-        let input_stem = args
-            .input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("heatmap");
-        PathBuf::from(format!("{}-heatmap.png", input_stem))
-    };
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "heatmap", &options.format));
 
     // Render the chart using the existing pipeline
-    process_single_chart(&args.input, &chart_config, &output_path)?;
-
-    println!("✅ Generated heatmap: {}", output_path.display());
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("heatmap", &output_path, options);
     Ok(())
 }
 
-fn render_retention_chart_cli(args: RetentionArgs, theme: &Theme) -> Result<()> {
+fn render_retention_chart_cli(
+    args: RetentionArgs,
+    theme: &Theme,
+    options: &RunOptions,
+) -> Result<()> {
     // Create chart configuration
     let chart_config = crate::spec::ChartConfig {
         chart_type: crate::spec::ChartType::Retention,
@@ -780,10 +1543,10 @@ fn render_retention_chart_cli(args: RetentionArgs, theme: &Theme) -> Result<()>
         derive: None,
         sort: None,
         limit: None,
-        width: Some(args.width),
-        height: Some(args.height),
+        width: args.width,
+        height: args.height,
         theme: Some(convert_theme_type(theme)),
-        format: Some(crate::spec::OutputFormat::Png),
+        format: Some(options.format.clone()),
         scale: None,
         stacked: None,
         horizontal: None,
@@ -800,28 +1563,83 @@ fn render_retention_chart_cli(args: RetentionArgs, theme: &Theme) -> Result<()>
         users: Some(args.users.clone()),
         percentage: Some(args.percentage),
         legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: None,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: None,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: None,
+        survival: args.survival.then_some(true),
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: None,
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: None,
+        y_max: None,
+        donut: None,
     };
 
     // Determine output path
-    let output_path = if let Some(out_path) = &args.out {
-        out_path.clone()
-    } else {
-        let input_stem = args
-            .input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("retention");
-        PathBuf::from(format!("{}-retention.png", input_stem))
-    };
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "retention", &options.format));
 
     // Render the chart using the existing pipeline
-    process_single_chart(&args.input, &chart_config, &output_path)?;
-
-    println!("✅ Generated retention chart: {}", output_path.display());
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("retention chart", &output_path, options);
     Ok(())
 }
 
-fn render_bar_stacked_chart_cli(args: BarStackedArgs, theme: &Theme) -> Result<()> {
+fn render_bar_stacked_chart_cli(
+    args: BarStackedArgs,
+    theme: &Theme,
+    options: &RunOptions,
+) -> Result<()> {
     // Create chart configuration
     let chart_config = crate::spec::ChartConfig {
         chart_type: crate::spec::ChartType::BarStacked,
@@ -843,7 +1661,7 @@ fn render_bar_stacked_chart_cli(args: BarStackedArgs, theme: &Theme) -> Result<(
         width: Some(args.width),
         height: Some(args.height),
         theme: Some(convert_theme_type(theme)),
-        format: Some(crate::spec::OutputFormat::Png),
+        format: Some(options.format.clone()),
         scale: None,
         stacked: Some(true), // Always true for stacked bars
         horizontal: None,
@@ -860,28 +1678,301 @@ fn render_bar_stacked_chart_cli(args: BarStackedArgs, theme: &Theme) -> Result<(
         users: None,
         percentage: None,
         legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: None,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: None,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: None,
+        survival: None,
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: args.data_labels.then_some(true),
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: None,
+        y_max: None,
+        donut: None,
     };
 
     // Determine output path
-    let output_path = if let Some(out_path) = &args.out {
-        out_path.clone()
-    } else {
-        let input_stem = args
-            .input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("bar-stacked");
-        PathBuf::from(format!("{}-bar-stacked.png", input_stem))
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "bar-stacked", &options.format));
+
+    // Render the chart using the existing pipeline
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("stacked bar chart", &output_path, options);
+    Ok(())
+}
+
+fn render_pie_chart_cli(args: PieArgs, theme: &Theme, options: &RunOptions) -> Result<()> {
+    // Create chart configuration
+    let chart_config = crate::spec::ChartConfig {
+        chart_type: crate::spec::ChartType::Pie,
+        title: args.title,
+        data: Some(args.input.clone()),
+        x: Some(args.x.clone()),
+        y: args.y.clone(),
+        z: None,
+        group_by: None,
+        agg: Some(convert_agg_type(&args.agg)),
+        filter: args
+            .filter
+            .as_ref()
+            .map(|f| parse_filter_string(f))
+            .transpose()?,
+        derive: None,
+        sort: None,
+        limit: None,
+        width: Some(args.width),
+        height: Some(args.height),
+        theme: Some(convert_theme_type(theme)),
+        format: Some(options.format.clone()),
+        scale: None,
+        stacked: None,
+        horizontal: None,
+        normalize: None,
+        bins: None,
+        colormap: None,
+        steps: None,
+        step_order: None,
+        value_labels: None,
+        values: None,
+        conversion_rates: None,
+        cohort_date: None,
+        period_number: None,
+        users: None,
+        percentage: None,
+        legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: None,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: None,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: None,
+        survival: None,
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: None,
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: None,
+        y_max: None,
+        donut: args.donut.then_some(true),
     };
 
+    // Determine output path
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "pie", &options.format));
+
     // Render the chart using the existing pipeline
-    process_single_chart(&args.input, &chart_config, &output_path)?;
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("pie chart", &output_path, options);
+    Ok(())
+}
 
-    println!("✅ Generated stacked bar chart: {}", output_path.display());
+fn render_box_chart_cli(args: BoxArgs, theme: &Theme, options: &RunOptions) -> Result<()> {
+    // Create chart configuration
+    let chart_config = crate::spec::ChartConfig {
+        chart_type: crate::spec::ChartType::Box,
+        title: args.title,
+        data: Some(args.input.clone()),
+        x: None,
+        y: Some(args.y.clone()),
+        z: None,
+        group_by: Some(args.group.clone()),
+        agg: None, // Boxes summarize the raw rows
+        filter: args
+            .filter
+            .as_ref()
+            .map(|f| parse_filter_string(f))
+            .transpose()?,
+        derive: None,
+        sort: None,
+        limit: None,
+        width: Some(args.width),
+        height: Some(args.height),
+        theme: Some(convert_theme_type(theme)),
+        format: Some(options.format.clone()),
+        scale: None,
+        stacked: None,
+        horizontal: None,
+        normalize: None,
+        bins: None,
+        colormap: None,
+        steps: None,
+        step_order: None,
+        value_labels: None,
+        values: None,
+        conversion_rates: None,
+        cohort_date: None,
+        period_number: None,
+        users: None,
+        percentage: None,
+        legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: None,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: None,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: None,
+        survival: None,
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: None,
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: None,
+        y_max: None,
+        donut: None,
+    };
+
+    // Determine output path
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "box", &options.format));
+
+    // Render the chart using the existing pipeline
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("box plot", &output_path, options);
     Ok(())
 }
 
-fn render_scatter_chart_cli(args: ScatterArgs, theme: &Theme) -> Result<()> {
+fn render_scatter_chart_cli(args: ScatterArgs, theme: &Theme, options: &RunOptions) -> Result<()> {
     // Create chart configuration
     let chart_config = crate::spec::ChartConfig {
         chart_type: crate::spec::ChartType::Scatter,
@@ -903,7 +1994,7 @@ fn render_scatter_chart_cli(args: ScatterArgs, theme: &Theme) -> Result<()> {
         width: Some(args.width),
         height: Some(args.height),
         theme: Some(convert_theme_type(theme)),
-        format: Some(crate::spec::OutputFormat::Png),
+        format: Some(options.format.clone()),
         scale: None,
         stacked: None,
         horizontal: None,
@@ -920,28 +2011,82 @@ fn render_scatter_chart_cli(args: ScatterArgs, theme: &Theme) -> Result<()> {
         users: None,
         percentage: None,
         legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: None,
+        user_column: None,
+        stack_order: None,
+        log_x: Some(args.log_x),
+        logo: None,
+        logo_position: None,
+        y_format: args.number_format,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: args.trend,
+        x2: None,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: None,
+        legend_columns: None,
+        log_scale: args.log_scale.then_some(true),
+        survival: None,
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: None,
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: args.trendline.then_some(true),
+        format_sizes: None,
+        colors: None,
+        y_min: args.y_min,
+        y_max: args.y_max,
+        donut: None,
     };
 
     // Determine output path
-    let output_path = if let Some(out_path) = &args.out {
-        out_path.clone()
-    } else {
-        let input_stem = args
-            .input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("scatter");
-        PathBuf::from(format!("{}-scatter.png", input_stem))
-    };
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "scatter", &options.format));
 
     // Render the chart using the existing pipeline
-    process_single_chart(&args.input, &chart_config, &output_path)?;
-
-    println!("✅ Generated scatter plot: {}", output_path.display());
+    let trendline = process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("scatter plot", &output_path, options);
+    if let Some(fit) = &trendline {
+        report_trendline(chart_config.title.as_deref().unwrap_or("scatter plot"), fit);
+    }
     Ok(())
 }
 
-fn render_funnel_chart_cli(args: FunnelArgs, theme: &Theme) -> Result<()> {
+fn render_funnel_chart_cli(args: FunnelArgs, theme: &Theme, options: &RunOptions) -> Result<()> {
     // Parse steps from comma-separated string
     let steps: Vec<String> = args
         .steps
@@ -971,10 +2116,10 @@ fn render_funnel_chart_cli(args: FunnelArgs, theme: &Theme) -> Result<()> {
         derive: None,
         sort: None,
         limit: None,
-        width: Some(args.width),
-        height: Some(args.height),
+        width: args.width,
+        height: args.height,
         theme: Some(convert_theme_type(theme)),
-        format: Some(crate::spec::OutputFormat::Png),
+        format: Some(options.format.clone()),
         scale: None,
         stacked: None,
         horizontal: None,
@@ -984,31 +2129,82 @@ fn render_funnel_chart_cli(args: FunnelArgs, theme: &Theme) -> Result<()> {
         steps: Some(steps),
         step_order: Some(step_order),
         value_labels: Some(args.value_labels),
-        values: Some(args.values),
+        values: args.values,
         conversion_rates: None,
         cohort_date: None,
         period_number: None,
         users: None,
         percentage: None,
         legend_position: None,
+        mark_extremes: None,
+        baseline: None,
+        color_scale: None,
+        mean_line: None,
+        step_column: args.step_column,
+        user_column: args.user_column,
+        stack_order: None,
+        log_x: None,
+        logo: None,
+        logo_position: None,
+        y_format: None,
+        y_lower: None,
+        y_upper: None,
+        series: None,
+        color_mode: None,
+        labels: None,
+        margins: None,
+        trend_type: None,
+        x2: None,
+        y_axis_side: None,
+        join: None,
+        nan_policy: None,
+        legend_ratio: None,
+        growth: None,
+        label_max_len: None,
+        background: None,
+        ys: None,
+        x_ticks: None,
+        y_ticks: None,
+        average_row: None,
+        inline: None,
+        quality: None,
+        date_from: None,
+        date_to: None,
+        highlight_dropoff: args.highlight_dropoff.then_some(true),
+        legend_columns: None,
+        log_scale: None,
+        survival: None,
+        endpoint_labels: None,
+        webp_quality: None,
+        data_labels: None,
+        hide_series: None,
+        null_label: None,
+        rolling_band: None,
+        trendline: None,
+        format_sizes: None,
+        colors: None,
+        y_min: None,
+        y_max: None,
+        donut: None,
     };
 
     // Determine output path
-    let output_path = if let Some(out_path) = &args.out {
-        out_path.clone()
-    } else {
-        let input_stem = args
-            .input
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("funnel");
-        PathBuf::from(format!("{}-funnel.png", input_stem))
-    };
+    let output_path = args
+        .out
+        .clone()
+        .unwrap_or_else(|| default_output_path(&args.input, "funnel", &options.format));
 
     // Render the chart using the existing pipeline
-    process_single_chart(&args.input, &chart_config, &output_path)?;
-
-    println!("✅ Generated funnel chart: {}", output_path.display());
+    process_single_chart(
+        &input_source(&args.input)?,
+        &chart_config,
+        &output_path,
+        options.legend_out.as_deref(),
+        None,
+        options,
+    )?;
+
+    report_single_chart("funnel chart", &output_path, options);
     Ok(())
 }
 
@@ -1080,22 +2276,108 @@ fn validate_step_order(step_order: &[usize], num_steps: usize) -> Result<()> {
     Ok(())
 }
 
-fn render_batch_charts(args: RenderArgs) -> Result<()> {
-    println!("Loading spec file: {}", args.spec.display());
-
+fn load_spec(path: &Path) -> Result<crate::spec::ChartSpec> {
     // Read and parse the spec file
-    let spec_content = fs::read_to_string(&args.spec).map_err(|e| {
-        anyhow::anyhow!("Failed to read spec file '{}': {}", args.spec.display(), e)
-    })?;
+    let spec_content = fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read spec file '{}': {}", path.display(), e))
+        .data_error()?;
 
-    let spec = if args.spec.extension().and_then(|s| s.to_str()) == Some("json") {
-        crate::spec::ChartSpec::from_json(&spec_content)?
+    if path.extension().and_then(|s| s.to_str()) == Some("json") {
+        crate::spec::ChartSpec::from_json(&spec_content).usage_error()
     } else {
-        crate::spec::ChartSpec::from_yaml(&spec_content)?
-    };
+        crate::spec::ChartSpec::from_yaml(&spec_content).usage_error()
+    }
+}
+
+fn lint_spec(args: LintArgs) -> Result<()> {
+    let spec = load_spec(&args.spec)?;
+    let warnings = spec.lint();
+
+    if warnings.is_empty() {
+        println!("No lint warnings in {}", args.spec.display());
+    } else {
+        for warning in &warnings {
+            println!("⚠️  {}", warning);
+        }
+        println!(
+            "{} lint warning(s) in {}",
+            warnings.len(),
+            args.spec.display()
+        );
+
+        if crate::warnings::is_strict() {
+            anyhow::bail!("Lint warnings treated as errors in --strict mode");
+        }
+    }
+
+    Ok(())
+}
+
+fn init_spec(args: InitArgs, options: &RunOptions) -> Result<()> {
+    if args.out.exists() && !args.force {
+        return Err(anyhow::anyhow!(
+            "{} already exists (use --force to overwrite it)",
+            args.out.display()
+        ))
+        .usage_error();
+    }
+
+    let lf = crate::data::load_csv(&args.input, &options.load)
+        .data_error()
+        .with_context(|| format!("Failed to load data from {}", args.input.display()))?;
+    let schema = lf.schema()?;
+
+    let spec = crate::scaffold::starter_spec(&args.input, &schema);
+    fs::write(&args.out, spec)
+        .with_context(|| format!("Failed to write spec file: {}", args.out.display()))?;
+
+    println!("✅ Wrote starter spec: {}", args.out.display());
+    println!(
+        "   Render it with: graff render --spec {} --out charts/",
+        args.out.display()
+    );
+    Ok(())
+}
+
+fn diff_images_cli(args: DiffArgs) -> Result<()> {
+    let diff =
+        crate::render::diff::diff_images(&args.a, &args.b, args.out.as_deref()).data_error()?;
+
+    println!(
+        "{:.4}% of pixels differ ({} of {})",
+        diff.fraction() * 100.0,
+        diff.differing,
+        diff.total
+    );
+    if let Some(out) = &args.out {
+        println!("✅ Generated diff image: {}", out.display());
+    }
+    Ok(())
+}
+
+fn render_batch_charts(args: RenderArgs, options: &RunOptions) -> Result<()> {
+    println!("Loading spec file: {}", args.spec.display());
+
+    let spec = load_spec(&args.spec)?;
 
     println!("Parsed spec with {} charts", spec.charts.len());
 
+    let only = parse_only_list(args.only.as_deref());
+    let timeout = args.timeout.map(std::time::Duration::from_secs);
+    if !only.is_empty()
+        && !spec
+            .charts
+            .iter()
+            .enumerate()
+            .any(|(index, chart)| is_chart_selected(&only, index, chart.title.as_deref()))
+    {
+        return Err(anyhow::anyhow!(
+            "No charts match --only '{}'",
+            args.only.as_deref().unwrap_or_default()
+        ))
+        .usage_error();
+    }
+
     // Use user-specified output directory, or default to ~/Desktop/graff
     let output_dir = if let Some(out_path) = &args.out {
         out_path.clone()
@@ -1120,13 +2402,32 @@ fn render_batch_charts(args: RenderArgs) -> Result<()> {
         println!("Created output directory: {}", output_dir.display());
     }
 
-    // Process each chart
     let mut successful_charts = 0;
     let mut failed_charts = 0;
-
+    let mut first_failure_code = None;
+    let mut used_output_paths = std::collections::HashSet::new();
+    let mut used_legend_paths = std::collections::HashSet::new();
+    let mut used_data_paths = std::collections::HashSet::new();
+    let mut manifest = crate::render::manifest::Manifest::default();
+    let manifest_path = args.manifest.as_ref().map(|path| {
+        path.clone()
+            .unwrap_or_else(|| output_dir.join("manifest.json"))
+    });
+    let manifest_dir = manifest_path
+        .as_deref()
+        .and_then(Path::parent)
+        .unwrap_or(&output_dir)
+        .to_path_buf();
+
+    // Resolve every chart's data and output paths first, so names are claimed in spec order
+    let mut jobs = Vec::new();
     for (index, chart_config) in spec.charts.iter().enumerate() {
         let default_name = format!("chart_{}", index + 1);
-        let chart_name = chart_config.title.as_deref().unwrap_or(&default_name);
+        let chart_name = chart_config.title.clone().unwrap_or(default_name);
+
+        if !is_chart_selected(&only, index, chart_config.title.as_deref()) {
+            continue;
+        }
 
         println!(
             "Processing chart {}: {} ({:?})",
@@ -1135,84 +2436,448 @@ fn render_batch_charts(args: RenderArgs) -> Result<()> {
             chart_config.chart_type
         );
 
-        // Determine data source
-        let data_path = chart_config
+        // Determine data source: the chart's own file or inline rows, then the spec defaults
+        let default_data = spec.data.as_ref();
+        let data_source = chart_config
             .data
-            .as_ref()
-            .or(spec.data.as_ref().and_then(|d| d.default.as_ref()))
-            .ok_or_else(|| {
-                anyhow::anyhow!("No data source specified for chart '{}'", chart_name)
-            })?;
-
-        println!("  Data source: {}", data_path.display());
-
-        // Generate output filename
-        let output_format = chart_config
-            .format
-            .as_ref()
-            .unwrap_or(&crate::spec::OutputFormat::Png);
-        let extension = match output_format {
-            crate::spec::OutputFormat::Png => "png",
-            crate::spec::OutputFormat::Svg => "svg",
-            crate::spec::OutputFormat::Pdf => "pdf",
-        };
-
-        let filename = format!(
-            "{}-{:?}.{}",
-            chart_name.to_lowercase().replace(' ', "-"),
-            chart_config.chart_type,
-            extension
-        );
-        let output_path = output_dir.join(filename);
+            .clone()
+            .map(crate::data::DataSource::File)
+            .or_else(|| {
+                chart_config
+                    .inline
+                    .clone()
+                    .map(crate::data::DataSource::Inline)
+            })
+            .or_else(|| {
+                default_data
+                    .and_then(|d| d.default.clone())
+                    .map(crate::data::DataSource::File)
+            })
+            .or_else(|| {
+                default_data
+                    .and_then(|d| d.inline.clone())
+                    .map(crate::data::DataSource::Inline)
+            })
+            .ok_or_else(|| anyhow::anyhow!("No data source specified for chart '{}'", chart_name))
+            .usage_error()?;
+
+        println!("  Data source: {}", data_source);
+
+        // Generate output filename, avoiding names already used in this batch
+        let output_path = crate::render::generate_unique_output_filename(
+            chart_config,
+            &output_dir,
+            &used_output_paths,
+        )?;
+        used_output_paths.insert(output_path.clone());
+
+        let legend_path = options.legend_out.as_ref().map(|path| {
+            let path = crate::render::unique_output_path(path, &used_legend_paths);
+            used_legend_paths.insert(path.clone());
+            path
+        });
+
+        // Each chart writes its series to its own file
+        let mut chart_options = options.clone();
+        chart_options.emit_data = options.emit_data.as_ref().map(|path| {
+            let path = crate::render::unique_output_path(path, &used_data_paths);
+            used_data_paths.insert(path.clone());
+            path
+        });
+
+        jobs.push(BatchJob {
+            chart_name,
+            chart_config,
+            data_source,
+            output_path,
+            legend_path,
+            options: chart_options,
+        });
+    }
 
-        // For now, just log what we would do
-        // TODO: Implement actual chart rendering
-        match process_single_chart(data_path, chart_config, &output_path) {
-            Ok(()) => {
+    // Render up to --parallel charts at once (0 lets rayon use every core); results come back
+    // in spec order
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.parallel.unwrap_or(0))
+        .build()
+        .context("Failed to start render threads")?;
+    let sources = spec.data.as_ref().and_then(|d| d.sources.clone());
+    let results: Vec<Result<BatchOutput>> = pool.install(|| {
+        use rayon::prelude::*;
+
+        jobs.par_iter()
+            .map(|job| {
+                let data_source = job.data_source.clone();
+                let chart_config = job.chart_config.clone();
+                let output_path = job.output_path.clone();
+                let legend_path = job.legend_path.clone();
+                let sources = sources.clone();
+                let mut options = job.options.clone();
+                run_with_timeout(timeout, move || {
+                    // Files are written beside their final paths and only moved into place once
+                    // the chart finishes in time, so a timed-out render leaves nothing behind
+                    let staged = StagedOutputs::new(
+                        [
+                            Some(&output_path),
+                            legend_path.as_ref(),
+                            options.emit_data.as_ref(),
+                        ]
+                        .into_iter()
+                        .flatten(),
+                    );
+                    options.emit_data = options.emit_data.as_deref().map(staging_path);
+                    let trendline = process_single_chart(
+                        &data_source,
+                        &chart_config,
+                        &staging_path(&output_path),
+                        legend_path.as_deref().map(staging_path).as_deref(),
+                        sources.as_ref(),
+                        &options,
+                    )?;
+                    Ok((staged, trendline))
+                })
+            })
+            .collect()
+    });
+
+    for (job, result) in jobs.iter().zip(results) {
+        let result = result.and_then(|(staged, trendline)| {
+            staged.commit().render_error()?;
+            // A chart whose output can't be read back for the manifest counts as failed
+            let entry = match &manifest_path {
+                Some(_) if !options.validate_only => Some(
+                    crate::render::manifest::ManifestEntry::rendered(
+                        &options.apply_overrides(job.chart_config),
+                        &job.data_source,
+                        &manifest_dir,
+                        &job.output_path,
+                    )
+                    .render_error()?,
+                ),
+                _ => None,
+            };
+            Ok((entry, trendline))
+        });
+        match result {
+            Ok((entry, trendline)) => {
                 successful_charts += 1;
-                println!("✓ Generated: {}", output_path.display());
+                if options.validate_only {
+                    println!("✓ Valid: {}", job.chart_name);
+                } else {
+                    println!("✓ Generated: {}", job.output_path.display());
+                    if let Some(legend_path) = &job.legend_path {
+                        println!("✓ Generated legend: {}", legend_path.display());
+                    }
+                    if let Some(data_path) = &job.options.emit_data {
+                        println!("✓ Generated data: {}", data_path.display());
+                    }
+                    if let Some(fit) = &trendline {
+                        report_trendline(&job.chart_name, fit);
+                    }
+                    manifest.charts.extend(entry);
+                }
             }
             Err(e) => {
                 failed_charts += 1;
-                eprintln!("✗ Failed to generate '{}': {:?}", chart_name, e);
+                first_failure_code.get_or_insert(crate::error::exit_code(&e));
+                eprintln!("✗ Failed to generate '{}': {:?}", job.chart_name, e);
+                if manifest_path.is_some() {
+                    manifest
+                        .charts
+                        .push(crate::render::manifest::ManifestEntry::failed(
+                            &options.apply_overrides(job.chart_config),
+                            &job.data_source,
+                            &manifest_dir,
+                            &job.output_path,
+                            &e,
+                        ));
+                }
             }
         }
     }
 
+    // Written before exiting on failures, so pipelines can see which charts broke
+    if let Some(manifest_path) = &manifest_path
+        && !options.validate_only
+    {
+        manifest.write(manifest_path)?;
+        println!("✓ Generated manifest: {}", manifest_path.display());
+    }
+
     // Print summary
     println!(
         "\nSummary: {} successful, {} failed",
         successful_charts, failed_charts
     );
 
-    if failed_charts > 0 {
-        std::process::exit(1);
+    // Exit with the code of the first failure so scripts see its category
+    if let Some(code) = first_failure_code {
+        std::process::exit(code);
     }
 
     Ok(())
 }
 
+/// A finished batch chart: its staged files, and the trendline to report for it
+type BatchOutput = (StagedOutputs, Option<crate::chart::trend::LinearFit>);
+
+/// One chart of a `render` batch, with the paths it was given
+struct BatchJob<'a> {
+    chart_name: String,
+    chart_config: &'a crate::spec::ChartConfig,
+    data_source: crate::data::DataSource,
+    output_path: PathBuf,
+    legend_path: Option<PathBuf>,
+    options: RunOptions,
+}
+
+/// Run `task` on a worker thread, failing with a render error if it takes longer than `timeout`
+///
+/// A timed-out worker can't be stopped; it finishes in the background while the batch moves on,
+/// and its result is dropped there.
+fn run_with_timeout<T, F>(timeout: Option<std::time::Duration>, task: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    let Some(timeout) = timeout else {
+        return task();
+    };
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        // The receiver is gone once the deadline has passed
+        let _ = sender.send(task());
+    });
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Err(anyhow::anyhow!(
+            "Timed out after {:.3}s",
+            timeout.as_secs_f64()
+        ))
+        .render_error(),
+        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+            Err(anyhow::anyhow!("Render worker stopped unexpectedly")).render_error()
+        }
+    }
+}
+
+/// Hidden file beside `path` that a batch chart writes to until it is committed; the extension
+/// is kept, since backends pick the image format from it
+fn staging_path(path: &Path) -> PathBuf {
+    let name = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(ext)) => format!(
+            ".{}.partial.{}",
+            stem.to_string_lossy(),
+            ext.to_string_lossy()
+        ),
+        _ => format!(
+            ".{}.partial",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        ),
+    };
+    path.with_file_name(name)
+}
+
+/// The files of one batch chart, written to their staging paths; `commit` moves them into place,
+/// and dropping them uncommitted removes whatever was written
+struct StagedOutputs {
+    /// (staging path, final path) of each output
+    paths: Vec<(PathBuf, PathBuf)>,
+}
+
+impl StagedOutputs {
+    fn new<'a>(outputs: impl IntoIterator<Item = &'a PathBuf>) -> Self {
+        Self {
+            paths: outputs
+                .into_iter()
+                .map(|path| (staging_path(path), path.clone()))
+                .collect(),
+        }
+    }
+
+    /// Move each written file to its final path; outputs never written (e.g. with
+    /// --validate-only) are skipped
+    fn commit(mut self) -> Result<()> {
+        for (staged, path) in std::mem::take(&mut self.paths) {
+            if staged.exists() {
+                std::fs::rename(&staged, &path)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagedOutputs {
+    fn drop(&mut self) {
+        for (staged, _) in &self.paths {
+            let _ = std::fs::remove_file(staged);
+        }
+    }
+}
+
+/// Split a `--only` value into lowercased selectors; empty means every chart is selected
+fn parse_only_list(only: Option<&str>) -> Vec<String> {
+    only.map(|only| {
+        only.split(',')
+            .map(|s| s.trim().to_lowercase())
+            .filter(|s| !s.is_empty())
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// A chart is selected when a selector is its 1-based index or its title (case-insensitive)
+fn is_chart_selected(only: &[String], index: usize, title: Option<&str>) -> bool {
+    only.is_empty()
+        || only.iter().any(|selector| {
+            selector.parse::<usize>().ok() == Some(index + 1)
+                || title.is_some_and(|title| title.to_lowercase() == *selector)
+        })
+}
+
+fn report_single_chart(kind: &str, output_path: &Path, options: &RunOptions) {
+    if options.validate_only {
+        println!("✅ Valid {} configuration (nothing rendered)", kind);
+    } else {
+        println!("✅ Generated {}: {}", kind, output_path.display());
+        if let Some(legend_path) = &options.legend_out {
+            println!("✅ Generated legend: {}", legend_path.display());
+        }
+        if let Some(data_path) = &options.emit_data {
+            println!("✅ Generated data: {}", data_path.display());
+        }
+    }
+}
+
+/// Suggest plotting wide data as series when a line, area or bar chart draws one `y` but the
+/// data has other numeric columns and neither `group_by` nor `ys` is set
+fn wide_data_hint(
+    schema: &polars::prelude::Schema,
+    chart_config: &crate::spec::ChartConfig,
+) -> Option<String> {
+    use crate::spec::ChartType;
+
+    let plots_series = matches!(
+        chart_config.chart_type,
+        ChartType::Line | ChartType::Area | ChartType::Bar
+    );
+    if !plots_series
+        || chart_config.group_by.is_some()
+        || chart_config.ys.is_some()
+        || chart_config.series.is_some()
+    {
+        return None;
+    }
+
+    let numeric: Vec<&str> = schema
+        .iter()
+        .filter(|(name, dtype)| {
+            dtype.is_numeric() && Some(name.as_str()) != chart_config.x.as_deref()
+        })
+        .map(|(name, _)| name.as_str())
+        .collect();
+    if numeric.len() < 2 {
+        return None;
+    }
+
+    Some(format!(
+        "the data looks wide: numeric columns {} could each be plotted as a series \
+         (list them in ys for an area chart, or reshape to long form and set group_by)",
+        numeric.join(", ")
+    ))
+}
+
 fn process_single_chart(
-    data_path: &Path,
+    data_source: &crate::data::DataSource,
     chart_config: &crate::spec::ChartConfig,
     output_path: &Path,
-) -> Result<()> {
+    legend_path: Option<&Path>,
+    sources: Option<&std::collections::HashMap<String, PathBuf>>,
+    options: &RunOptions,
+) -> Result<Option<crate::chart::trend::LinearFit>> {
+    // --background and --size apply to every chart, whatever the spec says
+    let chart_config = &options.apply_overrides(chart_config);
+
     // Validate the chart config
-    chart_config.validate()?;
+    chart_config.validate().usage_error()?;
+
+    // Grouping by the x column splits nothing into series, so the chart is drawn ungrouped
+    let ungrouped_config;
+    let chart_config = match (&chart_config.group_by, &chart_config.x) {
+        (Some(group_by), Some(x)) if group_by == x => {
+            crate::warnings::warn(format!(
+                "group_by '{}' is the x column and has no effect; drawing the chart ungrouped",
+                group_by
+            ))?;
+            ungrouped_config = crate::spec::ChartConfig {
+                group_by: None,
+                ..chart_config.clone()
+            };
+            &ungrouped_config
+        }
+        _ => chart_config,
+    };
 
     // Load CSV data
-    let load_options = crate::data::LoadOptions::default();
-    let lf = crate::data::load_csv(data_path, &load_options)
-        .with_context(|| format!("Failed to load data from {}", data_path.display()))?;
-
-    // Validate required columns exist
-    let required_columns = get_required_columns(chart_config);
-    crate::data::validate_columns(&lf, &required_columns).with_context(|| {
-        format!(
-            "Column validation failed for chart '{}'",
-            chart_config.title.as_deref().unwrap_or("unnamed")
-        )
-    })?;
+    let crate::data::Loaded { lf, sidecar } = data_source
+        .load(&options.load)
+        .data_error()
+        .with_context(|| format!("Failed to load data from {}", data_source))?;
+
+    // Axis label hints from the data's sidecar apply unless the chart sets its own
+    let labeled_config;
+    let chart_config = match sidecar {
+        Some(sidecar) if !sidecar.labels.is_empty() => {
+            labeled_config = chart_config.with_label_hints(&sidecar.labels);
+            &labeled_config
+        }
+        _ => chart_config,
+    };
+
+    // Join the lookup source before validating, so its columns can be used like any other
+    let lf = match &chart_config.join {
+        Some(join) => {
+            let source_path = sources
+                .and_then(|sources| sources.get(&join.source))
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown data source '{}' in join (define it under data.sources)",
+                        join.source
+                    )
+                })
+                .usage_error()?;
+            let other = crate::data::load_csv(source_path, &options.load)
+                .data_error()
+                .with_context(|| format!("Failed to load data from {}", source_path.display()))?;
+            crate::data::transform::join_source(lf, join, other)
+        }
+        None => lf,
+    };
+
+    // Validate required columns exist; derived columns are only added during transformation
+    let mut required_columns = get_required_columns(chart_config);
+    if let Some(derive) = &chart_config.derive {
+        required_columns.retain(|column| !derive.contains_key(column));
+    }
+    crate::data::validate_columns(&lf, &required_columns)
+        .usage_error()
+        .with_context(|| {
+            format!(
+                "Column validation failed for chart '{}'",
+                chart_config.title.as_deref().unwrap_or("unnamed")
+            )
+        })?;
+
+    if let Some(hint) = wide_data_hint(&lf.schema()?, chart_config) {
+        crate::warnings::advise(hint);
+    }
+
+    if options.validate_only {
+        return Ok(None);
+    }
 
     // Get column info for reporting
     let available_columns = crate::data::get_column_names(&lf)?;
@@ -1223,46 +2888,235 @@ fn process_single_chart(
     );
 
     // Apply transformations (filters, grouping, aggregation)
-    let processed_lf = apply_chart_transformations(lf, chart_config)?;
+    let processed_lf = apply_chart_transformations(lf, chart_config, options.load.date_order)?;
+
+    // `agg: count` without a y plots the row counts it added as a `count` column
+    let counted_config;
+    let chart_config = if chart_config.counts_rows() {
+        counted_config = crate::spec::ChartConfig {
+            y: Some(crate::spec::COUNT_COLUMN.to_string()),
+            ..chart_config.clone()
+        };
+        &counted_config
+    } else {
+        chart_config
+    };
+
+    // Write the plotted series, and fit the reported trendline, from the same frame the chart is
+    // drawn from
+    let reports_trendline = chart_config.chart_type == crate::spec::ChartType::Scatter
+        && chart_config.trendline == Some(true);
+    let (processed_lf, trendline) = if options.emit_data.is_some() || reports_trendline {
+        use polars::prelude::IntoLazy;
+
+        let df = processed_lf
+            .collect()
+            .context("Failed to collect data for rendering")?;
+        if let Some(emit_path) = &options.emit_data {
+            crate::render::data::write_chart_data(&df, chart_config, emit_path).render_error()?;
+        }
+        let mut trendline = None;
+        if reports_trendline {
+            trendline = crate::chart::scatter::trendline_fit(&df, chart_config).render_error()?;
+            if trendline.is_none() {
+                crate::warnings::warn(
+                    "trendline: needs at least two points with different x values",
+                )?;
+            }
+        }
+        (df.lazy(), trendline)
+    } else {
+        (processed_lf, None)
+    };
 
     // Render chart with Plotters
-    crate::render::render_chart(processed_lf, chart_config, output_path)
+    crate::render::render_chart(processed_lf, chart_config, output_path, legend_path)
+        .render_error()
         .with_context(|| format!("Failed to render chart to {}", output_path.display()))?;
 
-    Ok(())
+    if let Some(decimals) = options.svg_precision {
+        let svg_outputs = [Some(output_path), legend_path]
+            .into_iter()
+            .flatten()
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+            });
+        for path in svg_outputs {
+            crate::render::svg::round_svg_file(path, decimals).render_error()?;
+        }
+    }
+
+    if let Some(vision) = options.simulate {
+        let png_outputs = [Some(output_path), legend_path]
+            .into_iter()
+            .flatten()
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("png"))
+            });
+        for path in png_outputs {
+            crate::render::simulate::simulate_file(path, vision).render_error()?;
+        }
+    }
+
+    Ok(trendline)
+}
+
+/// Print the least squares line a scatter `trendline` drew
+fn report_trendline(chart_name: &str, fit: &crate::chart::trend::LinearFit) {
+    println!(
+        "📈 Trendline for '{}': y = {:.4}x + {:.4} (R² = {:.4})",
+        chart_name, fit.slope, fit.intercept, fit.r_squared
+    );
 }
 
 fn apply_chart_transformations(
     mut lf: polars::prelude::LazyFrame,
     config: &crate::spec::ChartConfig,
+    date_order: crate::data::DateOrder,
 ) -> Result<polars::prelude::LazyFrame> {
     // Apply filters if specified
     if let Some(filter) = &config.filter {
         lf = apply_filter_config(lf, filter)?;
     }
 
-    // Apply grouping and aggregation if specified
-    if let Some(agg) = &config.agg {
-        // For charts with aggregation, group by the x-axis column unless explicitly specified
-        let group_by_col = config
-            .group_by
+    // Derived columns can then be used as x, y or group_by like any loaded column
+    if let Some(derive) = &config.derive {
+        lf = crate::data::derive::apply_derived_columns(lf, derive).usage_error()?;
+    }
+
+    // Inclusive date bounds on the x column, which the loader has already read as dates
+    if config.date_from.is_some() || config.date_to.is_some() {
+        let x = config
+            .x
             .as_ref()
-            .unwrap_or(config.x.as_ref().unwrap());
-        lf = apply_aggregation(lf, group_by_col, config.y.as_ref().unwrap(), agg)?;
+            .context("date_from/date_to need an x column")
+            .usage_error()?;
+        let bound = |field: &str, value: &Option<String>| {
+            value
+                .as_deref()
+                .map(|date| crate::spec::parse_iso_date(field, date))
+                .transpose()
+        };
+        let from = bound("date_from", &config.date_from).usage_error()?;
+        let to = bound("date_to", &config.date_to).usage_error()?;
+        lf = crate::data::transform::apply_date_range(lf, x, from, to, date_order).usage_error()?;
+    }
+
+    // NaN and infinite values would poison the axis range, so deal with them before aggregating
+    let policy = config.nan_policy.clone().unwrap_or_default();
+    let (handled, found) =
+        crate::data::transform::apply_nan_policy(lf, &get_required_columns(config), &policy)?;
+    lf = handled;
+    if found > 0 {
+        let action = match policy {
+            crate::spec::NanPolicy::Drop => "dropped",
+            crate::spec::NanPolicy::Zero => "replaced with 0",
+            crate::spec::NanPolicy::Clamp => "clamped (NaN rows dropped)",
+        };
+        crate::warnings::warn(format!(
+            "{} NaN or infinite value(s) {} (nan_policy: drop, zero, clamp)",
+            found, action
+        ))?;
+    }
+
+    // Missing categories are drawn under a placeholder rather than as "null"
+    let y_category = match config.chart_type {
+        crate::spec::ChartType::Heatmap => config.y.as_ref(),
+        _ => None,
+    };
+    let categories: Vec<&str> = [&config.x, &config.group_by, &config.x2]
+        .into_iter()
+        .flatten()
+        .chain(y_category)
+        .map(String::as_str)
+        .collect();
+    let null_label = config
+        .null_label
+        .as_deref()
+        .unwrap_or(crate::data::transform::DEFAULT_NULL_LABEL);
+    lf = crate::data::transform::label_null_categories(lf, &categories, null_label)?;
+
+    // Funnels computed from raw events count distinct users per step
+    if config.chart_type == crate::spec::ChartType::Funnel
+        && let (Some(step_col), Some(user_col)) = (&config.step_column, &config.user_column)
+    {
+        lf = crate::chart::funnel::count_step_users(lf, step_col, user_col);
+    }
+
+    // Apply grouping and aggregation if specified; combo series carry their own aggregations
+    if let Some(series) = &config.series {
+        lf = crate::data::transform::aggregate_series(lf, config.x.as_ref().unwrap(), series);
+    } else if let (Some(ys), Some(agg)) = (&config.ys, &config.agg) {
+        // Wide columns are each aggregated per x under their own name
+        let values: Vec<&str> = ys.iter().map(String::as_str).collect();
+        lf = apply_aggregation(lf, &[config.x.as_ref().unwrap().as_str()], &values, agg)?;
+    } else if let Some(agg) = &config.agg {
+        // Aggregate per x, and per group within each x so every group keeps its own series;
+        // the outer x2 category is kept alongside x
+        let mut keys: Vec<&str> = [&config.x2, &config.x, &config.group_by]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .collect();
+        // A group_by naming the x column adds nothing to the key
+        keys.dedup();
+        if *agg == crate::spec::AggregationType::Count
+            && let Some(y) = &config.y
+            && keys_already_unique(&lf, &keys)?
+        {
+            crate::warnings::warn(format!(
+                "Each '{}' value appears only once, so 'agg: count' will plot 1 for every point; \
+                 the data looks already aggregated (drop 'agg' to plot '{}' as-is)",
+                keys.join("/"),
+                y
+            ))?;
+        }
+        // Without a y, `agg: count` counts the rows under each key
+        let values: Vec<&str> = config.y.as_deref().into_iter().collect();
+        lf = apply_aggregation(lf, &keys, &values, agg)?;
     } else if let Some(_group_by) = &config.group_by {
         // Handle grouping without aggregation (for line charts, etc.)
         // For now, just pass through - we might want to implement grouping logic here
     }
 
+    // group_by returns groups in no particular order, so aggregated rows are put back in x
+    // order unless an explicit sort is given; x2 groups already keep first-seen order
+    let aggregated = config.series.is_some() || config.agg.is_some();
+    if aggregated
+        && config.sort.is_none()
+        && config.x2.is_none()
+        && let Some(x) = &config.x
+        && lf.schema()?.contains(x)
+    {
+        lf = crate::data::transform::sort_by_column(lf, x, false, date_order)?;
+    }
+
+    // Growth replaces each y, or the row count of a count-only chart, with its change from the
+    // previous x
+    let growth_column = match &config.y {
+        Some(y) => Some(y.as_str()),
+        None if config.counts_rows() => Some(crate::spec::COUNT_COLUMN),
+        None => None,
+    };
+    if config.growth == Some(true)
+        && let (Some(x), Some(y)) = (&config.x, growth_column)
+    {
+        let group = config.group_by.as_deref().filter(|group| group != x);
+        lf = crate::data::transform::growth_rates(lf, x, y, group);
+    }
+
     // Apply sorting if specified
     if let Some(sort) = &config.sort {
         for sort_config in sort {
             let ascending = sort_config.ascending.unwrap_or(true);
-            let options = polars::prelude::SortOptions {
-                descending: !ascending,
-                ..Default::default()
-            };
-            lf = lf.sort(&sort_config.column, options);
+            lf = crate::data::transform::sort_by_column(
+                lf,
+                &sort_config.column,
+                !ascending,
+                date_order,
+            )?;
         }
     }
 
@@ -1314,29 +3168,68 @@ fn apply_filter_config(
         }
     }
 
+    // Apply the expression filter, checked against the columns it names
+    if let Some(expression) = &filter.expression {
+        let predicate = crate::data::filter::parse_filter_expression(expression, &lf.schema()?)
+            .usage_error()?;
+        lf = lf.filter(predicate);
+    }
+
     Ok(lf)
 }
 
 fn apply_aggregation(
     lf: polars::prelude::LazyFrame,
-    group_by: &str,
-    value_col: &str,
+    keys: &[&str],
+    value_cols: &[&str],
     agg_type: &crate::spec::AggregationType,
 ) -> Result<polars::prelude::LazyFrame> {
     use polars::prelude::*;
 
-    let agg_expr = match agg_type {
-        crate::spec::AggregationType::Sum => col(value_col).sum(),
-        crate::spec::AggregationType::Mean => col(value_col).mean(),
-        crate::spec::AggregationType::Count => col(value_col).count(),
-        crate::spec::AggregationType::Min => col(value_col).min(),
-        crate::spec::AggregationType::Max => col(value_col).max(),
-        crate::spec::AggregationType::Median => col(value_col).median(),
+    let mut agg_exprs: Vec<Expr> = value_cols
+        .iter()
+        .map(|value_col| {
+            let agg_expr = match agg_type {
+                crate::spec::AggregationType::Sum => col(value_col).sum(),
+                crate::spec::AggregationType::Mean => col(value_col).mean(),
+                crate::spec::AggregationType::Count => col(value_col).count(),
+                crate::spec::AggregationType::Min => col(value_col).min(),
+                crate::spec::AggregationType::Max => col(value_col).max(),
+                crate::spec::AggregationType::Median => col(value_col).median(),
+            };
+            agg_expr.alias(value_col)
+        })
+        .collect();
+    // No value columns: count the rows under each key
+    if value_cols.is_empty() {
+        agg_exprs.push(count().alias(crate::spec::COUNT_COLUMN));
+    }
+
+    let keys: Vec<Expr> = keys.iter().map(|key| col(key)).collect();
+    // With several keys (x2, x and group_by), groups keep first-seen order so x2 runs stay
+    // together
+    let grouped = if keys.len() > 1 {
+        lf.group_by_stable(keys)
+    } else {
+        lf.group_by(keys)
     };
 
-    Ok(lf
-        .group_by([col(group_by)])
-        .agg([agg_expr.alias(value_col)]))
+    Ok(grouped.agg(agg_exprs))
+}
+
+/// True when no two rows share the same key values, i.e. there is nothing left to aggregate
+fn keys_already_unique(lf: &polars::prelude::LazyFrame, keys: &[&str]) -> Result<bool> {
+    use polars::prelude::*;
+
+    let key_exprs: Vec<Expr> = keys.iter().map(|key| col(key)).collect();
+    let rows = lf.clone().select(key_exprs.clone()).collect()?.height();
+    let distinct = lf
+        .clone()
+        .select(key_exprs)
+        .unique(None, UniqueKeepStrategy::Any)
+        .collect()?
+        .height();
+    Ok(rows > 0 && rows == distinct)
 }
 
 fn get_required_columns(chart_config: &crate::spec::ChartConfig) -> Vec<String> {
@@ -1349,6 +3242,25 @@ fn get_required_columns(chart_config: &crate::spec::ChartConfig) -> Vec<String>
     if let Some(y) = &chart_config.y {
         columns.push(y.clone());
     }
+    for bound in [&chart_config.y_lower, &chart_config.y_upper]
+        .into_iter()
+        .flatten()
+    {
+        columns.push(bound.clone());
+    }
+    if let Some(x2) = &chart_config.x2 {
+        columns.push(x2.clone());
+    }
+    for series in chart_config.series.iter().flatten() {
+        if !columns.contains(&series.column) {
+            columns.push(series.column.clone());
+        }
+    }
+    for y in chart_config.ys.iter().flatten() {
+        if !columns.contains(y) {
+            columns.push(y.clone());
+        }
+    }
 
     // Add chart-type specific required columns
     match chart_config.chart_type {
@@ -1357,6 +3269,18 @@ fn get_required_columns(chart_config: &crate::spec::ChartConfig) -> Vec<String>
                 columns.push(z.clone());
             }
         }
+        crate::spec::ChartType::Funnel => {
+            for column in [
+                &chart_config.values,
+                &chart_config.step_column,
+                &chart_config.user_column,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                columns.push(column.clone());
+            }
+        }
         crate::spec::ChartType::Retention => {
             if let Some(cohort_date) = &chart_config.cohort_date {
                 columns.push(cohort_date.clone());
@@ -1378,3 +3302,500 @@ fn get_required_columns(chart_config: &crate::spec::ChartConfig) -> Vec<String>
 
     columns
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_slow_render_times_out_while_others_succeed() {
+        let timeout = Some(Duration::from_millis(100));
+        let slow = || {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(())
+        };
+
+        let results = [
+            run_with_timeout(timeout, || Ok(())),
+            run_with_timeout(timeout, slow),
+            run_with_timeout(timeout, || Ok(())),
+        ];
+
+        assert!(results[0].is_ok());
+        let err = results[1].as_ref().unwrap_err();
+        assert!(err.to_string().contains("Timed out after 0.100s"));
+        assert_eq!(crate::error::exit_code(err), 4);
+        assert!(results[2].is_ok());
+    }
+
+    #[test]
+    fn test_timed_out_chart_leaves_no_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output_path = dir.path().join("chart.png");
+
+        // Finished in time: the staged file is moved into place
+        let path = output_path.clone();
+        let staged = run_with_timeout(Some(Duration::from_secs(5)), move || {
+            let staged = StagedOutputs::new([&path]);
+            std::fs::write(staging_path(&path), b"chart")?;
+            Ok(staged)
+        })
+        .unwrap();
+        staged.commit().unwrap();
+        assert_eq!(std::fs::read(&output_path).unwrap(), b"chart");
+        std::fs::remove_file(&output_path).unwrap();
+
+        // The worker writes after the deadline, and its file is removed when it finishes
+        let path = output_path.clone();
+        let result = run_with_timeout(Some(Duration::from_millis(100)), move || {
+            let staged = StagedOutputs::new([&path]);
+            std::thread::sleep(Duration::from_millis(300));
+            std::fs::write(staging_path(&path), b"late")?;
+            Ok(staged)
+        });
+        assert!(result.is_err());
+        std::thread::sleep(Duration::from_millis(700));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_overlay_adds_a_line_series_after_the_bars() {
+        let series = bar_series("users", &AggregationType::Sum, Some("revenue:mean"))
+            .unwrap()
+            .unwrap();
+        let columns: Vec<String> = series
+            .iter()
+            .map(crate::spec::SeriesSpec::output_column)
+            .collect();
+        assert_eq!(columns, vec!["users_sum", "revenue_mean"]);
+
+        // Appended to an explicit combo, and left out when not asked for
+        let combo = bar_series(
+            "sessions:count",
+            &AggregationType::Sum,
+            Some("revenue:mean"),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(combo.len(), 2);
+        assert_eq!(
+            bar_series("users", &AggregationType::Sum, None).unwrap(),
+            None
+        );
+        assert!(bar_series("users", &AggregationType::Sum, Some("revenue")).is_err());
+
+        // Drawn, the overlay is a line on top of the bars
+        let df = polars::prelude::df! {
+            "month" => &["Jan", "Jan", "Feb", "Mar"],
+            "users" => &[100i64, 50, 120, 90],
+            "revenue" => &[10.0, 20.0, 12.0, 9.0],
+        }
+        .unwrap();
+        let render_svg = |series| {
+            use plotters::prelude::*;
+            use polars::prelude::IntoLazy;
+
+            let config = crate::spec::ChartConfig {
+                chart_type: crate::spec::ChartType::Bar,
+                x: Some("month".to_string()),
+                series,
+                ..Default::default()
+            };
+            let data = apply_chart_transformations(df.clone().lazy(), &config, Default::default())
+                .unwrap()
+                .collect()
+                .unwrap();
+            let mut svg = String::new();
+            {
+                let root = SVGBackend::with_string(&mut svg, (600, 400)).into_drawing_area();
+                root.fill(&WHITE).unwrap();
+                crate::chart::bar::render(
+                    &data,
+                    &config,
+                    root,
+                    "Users",
+                    &crate::spec::LegendPosition::Right,
+                )
+                .unwrap();
+            }
+            svg
+        };
+        let bars_only = render_svg(bar_series("users:sum", &AggregationType::Sum, None).unwrap());
+        let overlaid = render_svg(Some(series));
+        // The axes are drawn before the bars, so only the overlay line follows them
+        assert!(bars_only.rfind("<polyline").unwrap() < bars_only.rfind("<rect").unwrap());
+        assert!(overlaid.rfind("<polyline").unwrap() > overlaid.rfind("<rect").unwrap());
+    }
+
+    #[test]
+    fn test_print_size_converts_inches_at_dpi() {
+        let size = parse_print_size("6x4in").unwrap();
+        assert_eq!(size.pixels(300), (1800, 1200));
+        assert_eq!(
+            parse_print_size("8.5x11in").unwrap().pixels(100),
+            (850, 1100)
+        );
+
+        assert!(parse_print_size("6x4").is_err());
+        assert!(parse_print_size("0x4in").is_err());
+        assert!(parse_print_size("1800x1200px").is_err());
+    }
+
+    #[test]
+    fn test_wide_data_hint_names_numeric_columns() {
+        use polars::prelude::*;
+
+        let wide = df! {
+            "date" => &["2023-01-01", "2023-01-02"],
+            "organic" => &[120i64, 130],
+            "paid" => &[40i64, 55],
+            "referral" => &[8.5, 9.0],
+        }
+        .unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("organic".to_string()),
+            ..Default::default()
+        };
+
+        let hint = wide_data_hint(&wide.schema(), &config).unwrap();
+        assert!(hint.contains("organic, paid, referral"));
+
+        // Already plotting the columns as series, or a chart that uses several numbers anyway
+        let grouped = crate::spec::ChartConfig {
+            ys: Some(vec!["organic".to_string(), "paid".to_string()]),
+            ..config.clone()
+        };
+        assert_eq!(wide_data_hint(&wide.schema(), &grouped), None);
+        let scatter = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Scatter,
+            ..config
+        };
+        assert_eq!(wide_data_hint(&wide.schema(), &scatter), None);
+    }
+
+    #[test]
+    fn test_nan_and_infinity_are_dropped_before_rendering() {
+        use polars::prelude::*;
+
+        let df = df! {
+            "date" => &["2023-01-01", "2023-01-02", "2023-01-03", "2023-01-04"],
+            "users" => &[100.0, f64::NAN, f64::INFINITY, 150.0],
+        }
+        .unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            format: Some(crate::spec::OutputFormat::Svg),
+            ..Default::default()
+        };
+
+        let processed = apply_chart_transformations(df.lazy(), &config, Default::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+        let users: Vec<f64> = processed
+            .column("users")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(users, vec![100.0, 150.0]);
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("chart.svg");
+        crate::render::render_chart(processed.lazy(), &config, &path, None).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        // Axis labels come from a finite range
+        assert!(!svg.contains(">NaN") && !svg.contains(">inf"));
+    }
+
+    #[test]
+    fn test_growth_of_doubling_series_is_100_percent() {
+        use polars::prelude::*;
+
+        // Out of order on purpose: growth is measured after sorting by x
+        let df = df! {
+            "month" => &["2023-03", "2023-01", "2023-02"],
+            "revenue" => &[400i64, 100, 200],
+        }
+        .unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("month".to_string()),
+            y: Some("revenue".to_string()),
+            growth: Some(true),
+            ..Default::default()
+        };
+
+        let processed = apply_chart_transformations(df.lazy(), &config, Default::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+
+        // The first month has nothing to grow from, so it is omitted
+        let months: Vec<&str> = processed
+            .column("month")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(months, vec!["2023-02", "2023-03"]);
+        let growth: Vec<f64> = processed
+            .column("revenue")
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(growth, vec![100.0, 100.0]);
+    }
+
+    #[test]
+    fn test_null_categories_render_as_a_placeholder() {
+        use polars::prelude::*;
+
+        let df = df! {
+            "channel" => &[Some("organic"), None, Some("direct"), None],
+            "users" => &[100i64, 40, 60, 20],
+        }
+        .unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Bar,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            agg: Some(crate::spec::AggregationType::Sum),
+            format: Some(crate::spec::OutputFormat::Svg),
+            ..Default::default()
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let render = |config: &crate::spec::ChartConfig| {
+            let processed =
+                apply_chart_transformations(df.clone().lazy(), config, Default::default()).unwrap();
+            let path = dir.path().join("chart.svg");
+            crate::render::render_chart(processed, config, &path, None).unwrap();
+            std::fs::read_to_string(&path).unwrap()
+        };
+
+        let svg = render(&config);
+        assert!(svg.contains(">(none)<"));
+        assert!(!svg.contains(">null<"));
+
+        let svg = render(&crate::spec::ChartConfig {
+            null_label: Some("Unknown".to_string()),
+            ..config.clone()
+        });
+        assert!(svg.contains(">Unknown<") && !svg.contains(">(none)<"));
+    }
+
+    #[test]
+    fn test_count_without_y_counts_rows_per_category() {
+        use polars::prelude::*;
+
+        let df = df! {
+            "channel" => &["organic", "direct", "organic", "paid", "organic", "direct"],
+            "page" => &["/", "/pricing", "/blog", "/", "/", "/docs"],
+        }
+        .unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Bar,
+            x: Some("channel".to_string()),
+            agg: Some(crate::spec::AggregationType::Count),
+            ..Default::default()
+        };
+        config.validate().unwrap();
+        assert!(config.counts_rows());
+
+        let counts = apply_chart_transformations(df.lazy(), &config, Default::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+        let channels: Vec<&str> = counts
+            .column("channel")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        let rows: Vec<u32> = counts
+            .column(crate::spec::COUNT_COLUMN)
+            .unwrap()
+            .cast(&DataType::UInt32)
+            .unwrap()
+            .u32()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(channels, vec!["direct", "organic", "paid"]);
+        assert_eq!(rows, vec![2, 3, 1]);
+
+        // Any other aggregation still needs a y to aggregate
+        let summed = crate::spec::ChartConfig {
+            agg: Some(crate::spec::AggregationType::Sum),
+            ..config
+        };
+        assert!(summed.validate().is_err());
+    }
+
+    #[test]
+    fn test_growth_applies_to_counted_rows() {
+        use polars::prelude::*;
+
+        let df = df! {
+            "day" => &[1i64, 1, 2, 2, 2, 3],
+        }
+        .unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("day".to_string()),
+            agg: Some(crate::spec::AggregationType::Count),
+            growth: Some(true),
+            ..Default::default()
+        };
+
+        let growth = apply_chart_transformations(df.lazy(), &config, Default::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+        let rates: Vec<f64> = growth
+            .column(crate::spec::COUNT_COLUMN)
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        // 2 -> 3 -> 1 rows per day; the first day has nothing to grow from
+        assert_eq!(rates.len(), 2);
+        assert!((rates[0] - 50.0).abs() < 1e-9);
+        assert!((rates[1] + 200.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_grouped_sum_keeps_a_row_per_x_and_group() {
+        use polars::prelude::*;
+
+        let df = df! {
+            "date" => &["2024-01-01", "2024-01-01", "2024-01-01", "2024-01-02", "2024-01-02", "2024-01-02"],
+            "channel" => &["organic", "paid", "organic", "paid", "organic", "paid"],
+            "sessions" => &[10i64, 5, 20, 7, 30, 8],
+        }
+        .unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("sessions".to_string()),
+            group_by: Some("channel".to_string()),
+            agg: Some(crate::spec::AggregationType::Sum),
+            ..Default::default()
+        };
+
+        let summed = apply_chart_transformations(df.lazy(), &config, Default::default())
+            .unwrap()
+            .sort_by_exprs([col("date"), col("channel")], [false, false], false, false)
+            .collect()
+            .unwrap();
+
+        let column = |name: &str| -> Vec<String> {
+            summed
+                .column(name)
+                .unwrap()
+                .cast(&DataType::Utf8)
+                .unwrap()
+                .utf8()
+                .unwrap()
+                .into_no_null_iter()
+                .map(str::to_string)
+                .collect()
+        };
+        assert_eq!(summed.height(), 4);
+        assert_eq!(
+            column("date"),
+            vec!["2024-01-01", "2024-01-01", "2024-01-02", "2024-01-02"]
+        );
+        assert_eq!(
+            column("channel"),
+            vec!["organic", "paid", "organic", "paid"]
+        );
+        assert_eq!(column("sessions"), vec!["30", "5", "30", "15"]);
+    }
+
+    #[test]
+    fn test_date_range_keeps_only_bounded_month() {
+        use polars::prelude::*;
+
+        // Every day of 2023
+        let days: Vec<i32> = (19358..19358 + 365).collect();
+        let date = Series::new("date", &days).cast(&DataType::Date).unwrap();
+        let users = Series::new("users", &vec![1i64; days.len()]);
+        let df = DataFrame::new(vec![date, users]).unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            date_from: Some("2023-03-01".to_string()),
+            date_to: Some("2023-03-31".to_string()),
+            ..Default::default()
+        };
+
+        let processed = apply_chart_transformations(df.lazy(), &config, Default::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+
+        let months: Vec<String> = processed
+            .column("date")
+            .unwrap()
+            .cast(&DataType::Utf8)
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .map(|date| date[..7].to_string())
+            .collect();
+        assert_eq!(months.len(), 31);
+        assert!(months.iter().all(|month| month == "2023-03"));
+    }
+
+    #[test]
+    fn test_aggregated_line_is_sorted_by_x() {
+        use polars::prelude::*;
+
+        let df = df! {
+            "date" => &["2023-01-05", "2023-01-02", "2023-01-04", "2023-01-01", "2023-01-03",
+                        "2023-01-02", "2023-01-05", "2023-01-01", "2023-01-04", "2023-01-03"],
+            "users" => &[5i64, 2, 4, 1, 3, 2, 5, 1, 4, 3],
+        }
+        .unwrap();
+        let config = crate::spec::ChartConfig {
+            chart_type: crate::spec::ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            agg: Some(crate::spec::AggregationType::Sum),
+            ..Default::default()
+        };
+
+        let processed = apply_chart_transformations(df.lazy(), &config, Default::default())
+            .unwrap()
+            .collect()
+            .unwrap();
+
+        let dates: Vec<&str> = processed
+            .column("date")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(dates.len(), 5);
+        assert!(
+            dates.windows(2).all(|pair| pair[0] < pair[1]),
+            "x values out of order: {:?}",
+            dates
+        );
+    }
+}
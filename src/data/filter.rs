@@ -0,0 +1,322 @@
+//! `filter.expression`: comparisons such as `users > 150` or `channel == "organic"`, joined
+//! with `and`/`or` and grouped with parentheses, turned into a Polars predicate
+use anyhow::Result;
+use polars::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Column(String),
+    Number(String),
+    Text(String),
+    Op(CmpOp),
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    NotEq,
+    Gt,
+    GtEq,
+    Lt,
+    LtEq,
+}
+
+impl CmpOp {
+    fn apply(self, left: Expr, right: Expr) -> Expr {
+        match self {
+            CmpOp::Eq => left.eq(right),
+            CmpOp::NotEq => left.neq(right),
+            CmpOp::Gt => left.gt(right),
+            CmpOp::GtEq => left.gt_eq(right),
+            CmpOp::Lt => left.lt(right),
+            CmpOp::LtEq => left.lt_eq(right),
+        }
+    }
+}
+
+/// Parse `expression` into a predicate over the columns in `schema`
+///
+/// Values are numbers or quoted strings; a string compared with a date column is read as a
+/// date. Column names with spaces go in backticks. `and` binds tighter than `or`.
+pub fn parse_filter_expression(expression: &str, schema: &Schema) -> Result<Expr> {
+    let fail =
+        |reason: String| anyhow::anyhow!("Invalid filter expression '{}': {}", expression, reason);
+
+    let tokens = tokenize(expression).map_err(fail)?;
+    let mut parser = Parser {
+        tokens: &tokens,
+        position: 0,
+        schema,
+    };
+    let predicate = parser.or_expr().map_err(fail)?;
+    if let Some(token) = parser.peek() {
+        return Err(fail(format!("unexpected {}", describe(token))));
+    }
+    Ok(predicate)
+}
+
+fn tokenize(expression: &str) -> std::result::Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expression.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '"' | '\'' | '`' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some(next) => text.push(next),
+                        None => return Err(format!("unterminated {}", c)),
+                    }
+                }
+                tokens.push(if c == '`' {
+                    Token::Column(text)
+                } else {
+                    Token::Text(text)
+                });
+            }
+            '=' | '!' | '<' | '>' => {
+                chars.next();
+                let followed_by = |chars: &mut std::iter::Peekable<std::str::Chars>, expected| {
+                    chars.next_if_eq(&expected).is_some()
+                };
+                let op = match c {
+                    '=' => {
+                        followed_by(&mut chars, '=');
+                        CmpOp::Eq
+                    }
+                    '!' if followed_by(&mut chars, '=') => CmpOp::NotEq,
+                    '<' if followed_by(&mut chars, '=') => CmpOp::LtEq,
+                    '<' if followed_by(&mut chars, '>') => CmpOp::NotEq,
+                    '<' => CmpOp::Lt,
+                    '>' if followed_by(&mut chars, '=') => CmpOp::GtEq,
+                    '>' => CmpOp::Gt,
+                    _ => return Err("'!' must be followed by '='".to_string()),
+                };
+                tokens.push(Token::Op(op));
+            }
+            c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let mut number = String::new();
+                while let Some(next) =
+                    chars.next_if(|&next| next.is_ascii_digit() || next == '.' || next == '-')
+                {
+                    number.push(next);
+                }
+                if number.parse::<f64>().is_err() {
+                    return Err(format!("'{}' is not a number", number));
+                }
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = String::new();
+                while let Some(next) =
+                    chars.next_if(|&next| next.is_alphanumeric() || next == '_' || next == '.')
+                {
+                    word.push(next);
+                }
+                tokens.push(match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    _ => Token::Column(word),
+                });
+            }
+            other => return Err(format!("unexpected character '{}'", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Column(name) => format!("column '{}'", name),
+        Token::Number(number) => format!("number {}", number),
+        Token::Text(text) => format!("string \"{}\"", text),
+        Token::Op(_) => "comparison operator".to_string(),
+        Token::And => "'and'".to_string(),
+        Token::Or => "'or'".to_string(),
+        Token::Open => "'('".to_string(),
+        Token::Close => "')'".to_string(),
+    }
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    schema: &'a Schema,
+}
+
+type ParseResult<T> = std::result::Result<T, String>;
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<&Token> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn or_expr(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.and_expr()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            expr = expr.or(self.and_expr()?);
+        }
+        Ok(expr)
+    }
+
+    fn and_expr(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.term()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            expr = expr.and(self.term()?);
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> ParseResult<Expr> {
+        match self.next().cloned() {
+            Some(Token::Open) => {
+                let expr = self.or_expr()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(expr),
+                    _ => Err("missing ')'".to_string()),
+                }
+            }
+            Some(Token::Column(column)) => self.comparison(&column),
+            Some(token) => Err(format!(
+                "expected a column name, found {}",
+                describe(&token)
+            )),
+            None => Err("expected a comparison".to_string()),
+        }
+    }
+
+    fn comparison(&mut self, column: &str) -> ParseResult<Expr> {
+        let schema = self.schema;
+        let Some(dtype) = schema.get(column) else {
+            let available: Vec<&str> = schema.iter_names().map(|name| name.as_str()).collect();
+            return Err(format!(
+                "unknown column '{}' (available: {})",
+                column,
+                available.join(", ")
+            ));
+        };
+
+        let op = match self.next() {
+            Some(Token::Op(op)) => *op,
+            _ => return Err(format!("expected a comparison operator after '{}'", column)),
+        };
+
+        let value = match self.next().cloned() {
+            Some(Token::Number(number)) => match number.parse::<i64>() {
+                Ok(integer) => lit(integer),
+                Err(_) => lit(number.parse::<f64>().unwrap_or_default()),
+            },
+            // Dates are compared as dates, so "2023-01-01" is not ordered as text
+            Some(Token::Text(text)) => match dtype {
+                DataType::Date | DataType::Datetime(_, _) => lit(text).cast(dtype.clone()),
+                _ => lit(text),
+            },
+            Some(token) => {
+                return Err(format!(
+                    "expected a number or quoted string after '{}', found {}",
+                    column,
+                    describe(&token)
+                ));
+            }
+            None => return Err(format!("expected a value to compare '{}' with", column)),
+        };
+
+        Ok(op.apply(col(column), value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DataFrame {
+        df! {
+            "channel" => &["organic", "direct", "direct", "email"],
+            "users" => &[200i64, 120, 80, 150],
+            "value" => &[10.5, 20.0, 25.0, 5.0],
+        }
+        .unwrap()
+    }
+
+    fn filtered(expression: &str) -> DataFrame {
+        let df = sample();
+        let predicate = parse_filter_expression(expression, &df.schema()).unwrap();
+        df.lazy().filter(predicate).collect().unwrap()
+    }
+
+    fn users(df: &DataFrame) -> Vec<i64> {
+        df.column("users")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect()
+    }
+
+    #[test]
+    fn test_numeric_comparisons() {
+        assert_eq!(users(&filtered("users > 150")), vec![200]);
+        assert_eq!(users(&filtered("users >= 150")), vec![200, 150]);
+        assert_eq!(users(&filtered("value <= 20")), vec![200, 120, 150]);
+    }
+
+    #[test]
+    fn test_string_equality() {
+        assert_eq!(users(&filtered("channel == \"organic\"")), vec![200]);
+        assert_eq!(users(&filtered("channel != 'direct'")), vec![200, 150]);
+    }
+
+    #[test]
+    fn test_compound_expression() {
+        assert_eq!(
+            users(&filtered("users > 100 and channel == \"direct\"")),
+            vec![120]
+        );
+        assert_eq!(
+            users(&filtered(
+                "(channel == 'email' OR users < 100) and value > 1"
+            )),
+            vec![80, 150]
+        );
+    }
+
+    #[test]
+    fn test_errors_name_the_problem() {
+        let schema = sample().schema();
+        let unknown = parse_filter_expression("sessions > 10", &schema).unwrap_err();
+        assert!(unknown.to_string().contains("unknown column 'sessions'"));
+
+        let incomplete = parse_filter_expression("users >", &schema).unwrap_err();
+        assert!(
+            incomplete
+                .to_string()
+                .contains("Invalid filter expression 'users >'")
+        );
+
+        assert!(parse_filter_expression("users > 1 and", &schema).is_err());
+        assert!(parse_filter_expression("(users > 1", &schema).is_err());
+    }
+}
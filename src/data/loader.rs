@@ -1,13 +1,55 @@
 use anyhow::{Context, Result};
 use polars::prelude::*;
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 
+#[derive(Clone)]
 pub struct LoadOptions {
     #[allow(dead_code)]
     pub streaming: bool,
     pub infer_schema_length: Option<usize>,
     pub has_header: bool,
     pub try_parse_dates: bool,
+    /// Quote character for fields containing separators or newlines (`None` disables quoting)
+    pub quote_char: Option<u8>,
+    /// End-of-line character
+    pub eol_char: u8,
+    /// How `NN/NN/YYYY` dates are read
+    pub date_order: DateOrder,
+}
+
+/// Which field comes first in an ambiguous slash-separated date such as `03/04/2023`
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum)]
+pub enum DateOrder {
+    /// Month first (MM/DD/YYYY)
+    #[default]
+    Us,
+    /// Day first (DD/MM/YYYY)
+    Eu,
+    /// Day first if any value starts with a number above 12, otherwise month first
+    Auto,
+}
+
+impl DateOrder {
+    /// Settle `Auto` to `Us` or `Eu` by looking at a column's values
+    fn resolve<'a>(self, values: impl IntoIterator<Item = &'a str>) -> DateOrder {
+        if self != DateOrder::Auto {
+            return self;
+        }
+        let day_first = values.into_iter().any(|value| {
+            value
+                .trim()
+                .split('/')
+                .next()
+                .and_then(|first| first.parse::<u32>().ok())
+                .is_some_and(|first| first > 12)
+        });
+        if day_first {
+            DateOrder::Eu
+        } else {
+            DateOrder::Us
+        }
+    }
 }
 
 impl Default for LoadOptions {
@@ -17,27 +59,109 @@ impl Default for LoadOptions {
             infer_schema_length: Some(1000),
             has_header: true,
             try_parse_dates: true,
+            quote_char: Some(b'"'),
+            eol_char: b'\n',
+            date_order: DateOrder::Us,
         }
     }
 }
 
+/// Rows read from a data source, with the sidecar that shaped them
+pub struct Loaded {
+    pub lf: LazyFrame,
+    /// The file's `<file>.meta.yaml`, already applied to `lf` and kept for its label hints
+    pub sidecar: Option<crate::data::sidecar::Sidecar>,
+}
+
+/// Where a chart reads its rows from: a CSV file, or CSV text written inline in the spec
+#[derive(Debug, Clone)]
+pub enum DataSource {
+    File(PathBuf),
+    Inline(String),
+    /// CSV piped to the process, read once when the chart loads it
+    Stdin,
+}
+
+impl DataSource {
+    pub fn load(&self, options: &LoadOptions) -> Result<Loaded> {
+        let lf = match self {
+            DataSource::File(path) => return load_csv_with_sidecar(path, options),
+            DataSource::Inline(text) => load_csv_str(text, options)?,
+            DataSource::Stdin => load_csv_from_reader(std::io::stdin().lock(), options)?,
+        };
+        Ok(Loaded { lf, sidecar: None })
+    }
+}
+
+impl std::fmt::Display for DataSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataSource::File(path) => write!(f, "{}", path.display()),
+            DataSource::Inline(_) => write!(f, "inline data"),
+            DataSource::Stdin => write!(f, "stdin"),
+        }
+    }
+}
+
+/// Parse CSV text held in memory, with the same options and date detection as files
+pub fn load_csv_str(text: &str, options: &LoadOptions) -> Result<LazyFrame> {
+    load_csv_bytes(text.as_bytes().to_vec(), options).context("Failed to parse inline CSV data")
+}
+
+/// Read CSV from `reader` (such as stdin) to the end, then parse it like a file
+pub fn load_csv_from_reader(mut reader: impl Read, options: &LoadOptions) -> Result<LazyFrame> {
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .context("Failed to read CSV data")?;
+    load_csv_bytes(bytes, options).context("Failed to parse CSV data")
+}
+
+fn load_csv_bytes(bytes: Vec<u8>, options: &LoadOptions) -> Result<LazyFrame> {
+    let df = CsvReader::new(std::io::Cursor::new(bytes))
+        .has_header(options.has_header)
+        .infer_schema(options.infer_schema_length)
+        .with_try_parse_dates(options.try_parse_dates)
+        .with_quote_char(options.quote_char)
+        .with_end_of_line_char(options.eol_char)
+        .finish()?;
+
+    detect_and_parse_dates(df.lazy(), options.date_order)
+}
+
 pub fn load_csv(path: &Path, options: &LoadOptions) -> Result<LazyFrame> {
+    load_csv_with_sidecar(path, options).map(|loaded| loaded.lf)
+}
+
+/// Load a CSV file and apply its sidecar, returning the sidecar too so it is read only once
+pub fn load_csv_with_sidecar(path: &Path, options: &LoadOptions) -> Result<Loaded> {
     // Load CSV with proper error handling
     let df = CsvReader::from_path(path)
         .with_context(|| format!("Failed to open CSV file: {}", path.display()))?
         .has_header(options.has_header)
         .infer_schema(options.infer_schema_length)
         .with_try_parse_dates(options.try_parse_dates)
+        .with_quote_char(options.quote_char)
+        .with_end_of_line_char(options.eol_char)
         .finish()
         .with_context(|| format!("Failed to parse CSV file: {}", path.display()))?;
 
-    let lf = df.lazy();
+    let mut lf = df.lazy();
+
+    // Apply renames, dtype overrides and date formats from a `<file>.meta.yaml` sidecar
+    let sidecar = crate::data::sidecar::load_sidecar(path)?;
+    if let Some(sidecar) = &sidecar {
+        lf = sidecar.apply(lf);
+    }
 
     // Apply date format detection and parsing for common patterns
-    detect_and_parse_dates(lf)
+    Ok(Loaded {
+        lf: detect_and_parse_dates(lf, options.date_order)?,
+        sidecar,
+    })
 }
 
-fn detect_and_parse_dates(lf: LazyFrame) -> Result<LazyFrame> {
+fn detect_and_parse_dates(lf: LazyFrame, date_order: DateOrder) -> Result<LazyFrame> {
     // Get column information to detect date patterns
     let schema = lf
         .schema()
@@ -50,7 +174,7 @@ fn detect_and_parse_dates(lf: LazyFrame) -> Result<LazyFrame> {
             // Check if this looks like a date column
             if is_likely_date_column(col_name) {
                 // Sample the data to detect format
-                if let Ok(detected_format) = detect_date_format(&result, col_name) {
+                if let Ok(detected_format) = detect_date_format(&result, col_name, date_order) {
                     result = try_parse_date_column(result, col_name, &detected_format)?;
                 }
             }
@@ -99,12 +223,10 @@ enum DateFormat {
     IsoDateTime, // YYYY-MM-DD HH:MM:SS
     YyyyMmDd,    // YYYYMMDD
     MmDdYyyy,    // MM/DD/YYYY
-    #[allow(dead_code)]
-    DdMmYyyy, // DD/MM/YYYY
+    DdMmYyyy,    // DD/MM/YYYY
 }
 
 impl DateFormat {
-    #[allow(dead_code)]
     fn to_polars_format(&self) -> &'static str {
         match self {
             DateFormat::Iso => "%Y-%m-%d",
@@ -116,30 +238,54 @@ impl DateFormat {
     }
 }
 
-fn detect_date_format(lf: &LazyFrame, col_name: &str) -> Result<DateFormat> {
-    // Collect a few sample values to detect the format
+/// An expression reading the text column `col_name` as dates, if its `values` are in a
+/// recognised date format; unparseable values become null, and ambiguous ones are read in
+/// `date_order` as the loader reads them
+pub fn parse_dates_expr(col_name: &str, values: &[&str], date_order: DateOrder) -> Option<Expr> {
+    let order = date_order.resolve(values.iter().copied());
+    let format = detect_format_from_string(values.first()?, order).ok()?;
+    Some(strptime_expr(col_name, &format, false))
+}
+
+fn strptime_expr(col_name: &str, format: &DateFormat, strict: bool) -> Expr {
+    let dtype = match format {
+        DateFormat::IsoDateTime => DataType::Datetime(TimeUnit::Milliseconds, None),
+        _ => DataType::Date,
+    };
+    col(col_name).str().strptime(
+        dtype,
+        StrptimeOptions {
+            format: Some(format.to_polars_format().to_string()),
+            strict,
+            ..Default::default()
+        },
+        lit("raise"),
+    )
+}
+
+fn detect_date_format(lf: &LazyFrame, col_name: &str, date_order: DateOrder) -> Result<DateFormat> {
+    // The whole column is read so `Auto` can look for a day above 12 anywhere in it
     let sample_df = lf
         .clone()
         .select([col(col_name)])
-        .limit(10)
         .collect()
         .map_err(|e| anyhow::anyhow!("Failed to sample data for date detection: {}", e))?;
 
     let column = sample_df
         .column(col_name)
-        .map_err(|e| anyhow::anyhow!("Column '{}' not found in sample: {}", col_name, e))?;
+        .map_err(|e| anyhow::anyhow!("Column '{}' not found in sample: {}", col_name, e))?
+        .utf8()?;
 
     // Get the first non-null string value
-    for i in 0..column.len() {
-        if let Ok(AnyValue::Utf8(date_str)) = column.get(i) {
-            return detect_format_from_string(date_str);
-        }
+    if let Some(date_str) = column.into_iter().flatten().next() {
+        let date_order = date_order.resolve(column.into_iter().flatten());
+        return detect_format_from_string(date_str, date_order);
     }
 
     anyhow::bail!("Could not find valid date string in column '{}'", col_name)
 }
 
-fn detect_format_from_string(date_str: &str) -> Result<DateFormat> {
+fn detect_format_from_string(date_str: &str, date_order: DateOrder) -> Result<DateFormat> {
     let trimmed = date_str.trim();
 
     // Check for YYYY-MM-DD HH:MM:SS (ISO datetime)
@@ -169,9 +315,12 @@ fn detect_format_from_string(date_str: &str) -> Result<DateFormat> {
         && trimmed.chars().nth(2) == Some('/')
         && trimmed.chars().nth(5) == Some('/')
     {
-        // This is ambiguous - we'll default to MM/DD/YYYY (US format)
-        // In a real implementation, you might want to make this configurable
-        return Ok(DateFormat::MmDdYyyy);
+        // Ambiguous on its own, so `date_order` decides; an unresolved `Auto` reads as US
+        let date_order = date_order.resolve([trimmed]);
+        return Ok(match date_order {
+            DateOrder::Eu => DateFormat::DdMmYyyy,
+            DateOrder::Us | DateOrder::Auto => DateFormat::MmDdYyyy,
+        });
     }
 
     anyhow::bail!("Could not detect date format for string: '{}'", date_str)
@@ -180,17 +329,26 @@ fn detect_format_from_string(date_str: &str) -> Result<DateFormat> {
 fn try_parse_date_column(lf: LazyFrame, col_name: &str, format: &DateFormat) -> Result<LazyFrame> {
     let parsed_col_name = format!("{}_parsed", col_name);
 
-    // For Polars 0.35, we'll use a simpler approach
-    // In a newer version, we could use strptime more directly
     let result = match format {
         DateFormat::Iso | DateFormat::IsoDateTime => {
             // Polars should auto-detect ISO format
             lf.with_columns([col(col_name).cast(DataType::Date).alias(&parsed_col_name)])
         }
         _ => {
-            // For other formats, we'll keep the original for now
-            // TODO: Implement custom parsing for non-ISO formats
-            lf.with_columns([col(col_name).alias(&parsed_col_name)])
+            // Parse strictly up front so one bad value keeps the column as text instead of
+            // failing the whole load later
+            let parsed = strptime_expr(col_name, format, true);
+            match lf.clone().select([parsed.clone()]).collect() {
+                Ok(_) => lf.with_columns([parsed.alias(&parsed_col_name)]),
+                Err(_) => {
+                    crate::warnings::warn(format!(
+                        "Column '{}' looks like dates ({}) but not every value parses; keeping it as text",
+                        col_name,
+                        format.to_polars_format()
+                    ))?;
+                    lf.with_columns([col(col_name).alias(&parsed_col_name)])
+                }
+            }
         }
     };
 
@@ -357,6 +515,26 @@ mod tests {
         assert!(columns.contains(&"event_date".to_string()));
     }
 
+    #[test]
+    fn test_load_csv_parses_us_dates() {
+        let csv_content = "date,users\n12/25/2023,100\n12/26/2023,150";
+        let temp_file = create_test_csv(csv_content);
+
+        let lf = load_csv(temp_file.path(), &LoadOptions::default()).unwrap();
+        let schema = lf.schema().unwrap();
+        assert_eq!(schema.get("date_parsed"), Some(&DataType::Date));
+    }
+
+    #[test]
+    fn test_load_csv_keeps_unparseable_dates_as_text() {
+        let csv_content = "date,users\n12/25/2023,100\n13/45/2023,150";
+        let temp_file = create_test_csv(csv_content);
+
+        let lf = load_csv(temp_file.path(), &LoadOptions::default()).unwrap();
+        let schema = lf.schema().unwrap();
+        assert_eq!(schema.get("date_parsed"), Some(&DataType::Utf8));
+    }
+
     #[test]
     fn test_load_csv_with_timestamps() {
         let csv_content = "timestamp,users,event_name\n1704067200000000,100,page_view\n1704153600000000,150,click";
@@ -412,25 +590,70 @@ mod tests {
     #[test]
     fn test_detect_format_from_string() {
         assert!(matches!(
-            detect_format_from_string("2023-12-25").unwrap(),
+            detect_format_from_string("2023-12-25", DateOrder::Us).unwrap(),
             DateFormat::Iso
         ));
         assert!(matches!(
-            detect_format_from_string("2023-12-25 14:30:00").unwrap(),
+            detect_format_from_string("2023-12-25 14:30:00", DateOrder::Us).unwrap(),
             DateFormat::IsoDateTime
         ));
         assert!(matches!(
-            detect_format_from_string("20231225").unwrap(),
+            detect_format_from_string("20231225", DateOrder::Us).unwrap(),
             DateFormat::YyyyMmDd
         ));
         assert!(matches!(
-            detect_format_from_string("12/25/2023").unwrap(),
+            detect_format_from_string("12/25/2023", DateOrder::Us).unwrap(),
             DateFormat::MmDdYyyy
         ));
 
         // Invalid formats should fail
-        assert!(detect_format_from_string("invalid-date").is_err());
-        assert!(detect_format_from_string("2023/12/25").is_err());
+        assert!(detect_format_from_string("invalid-date", DateOrder::Us).is_err());
+        assert!(detect_format_from_string("2023/12/25", DateOrder::Us).is_err());
+    }
+
+    #[test]
+    fn test_date_order_for_slash_dates() {
+        assert!(matches!(
+            detect_format_from_string("03/04/2023", DateOrder::Us).unwrap(),
+            DateFormat::MmDdYyyy
+        ));
+        assert!(matches!(
+            detect_format_from_string("03/04/2023", DateOrder::Eu).unwrap(),
+            DateFormat::DdMmYyyy
+        ));
+    }
+
+    #[test]
+    fn test_auto_date_order_looks_for_a_day_above_12() {
+        assert_eq!(
+            DateOrder::Auto.resolve(["03/04/2023", "25/04/2023"]),
+            DateOrder::Eu
+        );
+        assert_eq!(
+            DateOrder::Auto.resolve(["03/04/2023", "12/25/2023"]),
+            DateOrder::Us
+        );
+        // An explicit order is kept whatever the values say
+        assert_eq!(DateOrder::Us.resolve(["25/04/2023"]), DateOrder::Us);
+    }
+
+    #[test]
+    fn test_load_csv_eu_dates() {
+        let csv_content = "date,users\n03/04/2023,100\n25/04/2023,150";
+        let temp_file = create_test_csv(csv_content);
+
+        let options = LoadOptions {
+            date_order: DateOrder::Auto,
+            ..Default::default()
+        };
+        let df = load_csv(temp_file.path(), &options)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let parsed = df.column("date_parsed").unwrap();
+        assert_eq!(parsed.dtype(), &DataType::Date);
+        // 3 April 2023, days since the epoch
+        assert_eq!(parsed.get(0).unwrap(), AnyValue::Date(19450));
     }
 
     #[test]
@@ -520,6 +743,29 @@ mod tests {
         assert_eq!(options.infer_schema_length, Some(1000));
         assert_eq!(options.has_header, true);
         assert_eq!(options.try_parse_dates, true);
+        assert_eq!(options.quote_char, Some(b'"'));
+        assert_eq!(options.eol_char, b'\n');
+        assert_eq!(options.date_order, DateOrder::Us);
+    }
+
+    #[test]
+    fn test_load_csv_single_quote_char() {
+        let csv_content = "name,value\n'Smith, John',10\n'Doe, Jane',20";
+        let temp_file = create_test_csv(csv_content);
+
+        let options = LoadOptions {
+            quote_char: Some(b'\''),
+            ..Default::default()
+        };
+        let df = load_csv(temp_file.path(), &options)
+            .unwrap()
+            .collect()
+            .unwrap();
+
+        assert_eq!(df.shape(), (2, 2));
+        let names = df.column("name").unwrap();
+        assert_eq!(names.get(0).unwrap(), AnyValue::Utf8("Smith, John"));
+        assert_eq!(names.get(1).unwrap(), AnyValue::Utf8("Doe, Jane"));
     }
 
     #[test]
@@ -552,4 +798,28 @@ mod tests {
             Err(_) => (), // Error case is also acceptable
         }
     }
+
+    #[test]
+    fn test_load_csv_applies_sidecar() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let csv_path = dir.path().join("data.csv");
+        fs::write(&csv_path, "day,users\n2023-01-01,100\n2023-01-02,150").unwrap();
+        fs::write(
+            dir.path().join("data.csv.meta.yaml"),
+            "dtypes:\n  users: float\nlabels:\n  users: Active users\n",
+        )
+        .unwrap();
+
+        let loaded = DataSource::File(csv_path)
+            .load(&LoadOptions::default())
+            .unwrap();
+        let df = loaded.lf.collect().unwrap();
+        assert_eq!(df.column("users").unwrap().dtype(), &DataType::Float64);
+
+        // The sidecar that was applied comes back with the data for its label hints
+        let sidecar = loaded.sidecar.unwrap();
+        let config = crate::spec::ChartConfig::default().with_label_hints(&sidecar.labels);
+        assert_eq!(config.axis_label("users"), "Active users");
+        assert_eq!(config.axis_label("day"), "day");
+    }
 }
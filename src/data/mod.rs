@@ -1,5 +1,7 @@
 pub mod derive;
+pub mod filter;
 pub mod loader;
+pub mod sidecar;
 pub mod transform;
 
 pub use loader::*;
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Column hints stored next to a CSV as `<file>.meta.yaml` (e.g. `data.csv.meta.yaml`)
+///
+/// Renames are applied first; every other section refers to columns by their new names.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Sidecar {
+    /// Original column name -> new column name
+    #[serde(default)]
+    pub rename: HashMap<String, String>,
+    /// Column -> type the column is cast to
+    #[serde(default)]
+    pub dtypes: HashMap<String, ColumnType>,
+    /// Column -> strftime format used to parse it as a date (or datetime when `dtypes` says so)
+    #[serde(default)]
+    pub date_formats: HashMap<String, String>,
+    /// Column -> display label used for axis titles
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    Int,
+    Float,
+    String,
+    Bool,
+    Date,
+    Datetime,
+}
+
+impl ColumnType {
+    fn to_polars(&self) -> DataType {
+        match self {
+            ColumnType::Int => DataType::Int64,
+            ColumnType::Float => DataType::Float64,
+            ColumnType::String => DataType::Utf8,
+            ColumnType::Bool => DataType::Boolean,
+            ColumnType::Date => DataType::Date,
+            ColumnType::Datetime => DataType::Datetime(TimeUnit::Milliseconds, None),
+        }
+    }
+}
+
+/// Location of the sidecar for a CSV file: `data.csv` -> `data.csv.meta.yaml`
+pub fn sidecar_path(csv_path: &Path) -> PathBuf {
+    let mut name = csv_path.as_os_str().to_owned();
+    name.push(".meta.yaml");
+    PathBuf::from(name)
+}
+
+/// Load the sidecar next to `csv_path`, if there is one
+pub fn load_sidecar(csv_path: &Path) -> Result<Option<Sidecar>> {
+    let path = sidecar_path(csv_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read sidecar file: {}", path.display()))?;
+    let sidecar = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse sidecar file: {}", path.display()))?;
+    Ok(Some(sidecar))
+}
+
+impl Sidecar {
+    /// Apply renames, date formats and dtype casts to the loaded data
+    pub fn apply(&self, lf: LazyFrame) -> LazyFrame {
+        let mut lf = lf;
+
+        if !self.rename.is_empty() {
+            let (existing, new): (Vec<&String>, Vec<&String>) = self.rename.iter().unzip();
+            lf = lf.rename(existing, new);
+        }
+
+        let mut exprs = Vec::new();
+        for (column, format) in &self.date_formats {
+            let dtype = match self.dtypes.get(column) {
+                Some(ColumnType::Datetime) => ColumnType::Datetime.to_polars(),
+                _ => DataType::Date,
+            };
+            let options = StrptimeOptions {
+                format: Some(format.clone()),
+                ..Default::default()
+            };
+            exprs.push(col(column).str().strptime(dtype, options, lit("raise")));
+        }
+        for (column, dtype) in &self.dtypes {
+            if !self.date_formats.contains_key(column) {
+                exprs.push(col(column).cast(dtype.to_polars()));
+            }
+        }
+
+        if exprs.is_empty() {
+            lf
+        } else {
+            lf.with_columns(exprs)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sidecar_path() {
+        assert_eq!(
+            sidecar_path(Path::new("data/events.csv")),
+            PathBuf::from("data/events.csv.meta.yaml")
+        );
+    }
+
+    #[test]
+    fn test_apply_renames_and_casts() {
+        let sidecar: Sidecar = serde_yaml::from_str(
+            "rename: { usr: users }\ndtypes: { users: float }\nlabels: { users: Active users }",
+        )
+        .unwrap();
+        let df = df! {
+            "day" => &["2023-01-01", "2023-01-02"],
+            "usr" => &[100i64, 150],
+        }
+        .unwrap();
+
+        let result = sidecar.apply(df.lazy()).collect().unwrap();
+        assert_eq!(result.column("users").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(sidecar.labels["users"], "Active users");
+    }
+}
@@ -1,5 +1,9 @@
-use crate::spec::{AggregationType, FilterConfig, FilterValue, SortConfig};
-use anyhow::Result;
+use crate::data::DateOrder;
+use crate::spec::{
+    AggregationType, FilterConfig, FilterValue, JoinConfig, JoinHow, NanPolicy, SeriesSpec,
+    SortConfig,
+};
+use anyhow::{Context, Result};
 use polars::prelude::*;
 use std::collections::HashMap;
 
@@ -89,9 +93,9 @@ fn apply_filters(lf: LazyFrame, filter: &FilterConfig) -> Result<LazyFrame> {
 
     // Apply expression filter
     if let Some(expression) = &filter.expression {
-        // TODO: Parse and apply SQL-like filter expressions
-        // For now, just log that we received an expression
-        println!("Expression filter not yet implemented: {}", expression);
+        let predicate =
+            crate::data::filter::parse_filter_expression(expression, &result.schema()?)?;
+        result = result.filter(predicate);
     }
 
     Ok(result)
@@ -113,22 +117,253 @@ fn apply_grouping(lf: LazyFrame, group_col: &str, agg_type: &AggregationType) ->
     Ok(result)
 }
 
+/// Group by `group_col` and aggregate each series into its own output column,
+/// so one column can be aggregated several ways (e.g. `sessions_count`, `revenue_sum`)
+pub fn aggregate_series(lf: LazyFrame, group_col: &str, series: &[SeriesSpec]) -> LazyFrame {
+    let aggs: Vec<Expr> = series
+        .iter()
+        .map(|s| {
+            let column = col(&s.column);
+            let expr = match s.agg {
+                AggregationType::Sum => column.sum(),
+                AggregationType::Count => column.count(),
+                AggregationType::Mean => column.mean(),
+                AggregationType::Median => column.median(),
+                AggregationType::Min => column.min(),
+                AggregationType::Max => column.max(),
+            };
+            expr.alias(&s.output_column())
+        })
+        .collect();
+
+    lf.group_by([col(group_col)]).agg(aggs)
+}
+
+/// Join a lookup source onto the chart's data on `left_on` = `right_on`
+pub fn join_source(lf: LazyFrame, join: &JoinConfig, other: LazyFrame) -> LazyFrame {
+    let right_on = join.right_on.as_ref().unwrap_or(&join.left_on);
+    let how = match join.how.clone().unwrap_or_default() {
+        JoinHow::Left => JoinType::Left,
+        JoinHow::Inner => JoinType::Inner,
+        JoinHow::Outer => JoinType::Outer,
+    };
+    lf.join(
+        other,
+        [col(&join.left_on)],
+        [col(right_on)],
+        JoinArgs::new(how),
+    )
+}
+
+/// Apply `policy` to NaN and infinite values in the float columns among `columns`
+///
+/// Returns the frame along with how many such values were found. Nulls are left alone.
+pub fn apply_nan_policy(
+    lf: LazyFrame,
+    columns: &[String],
+    policy: &NanPolicy,
+) -> Result<(LazyFrame, usize)> {
+    let schema = lf.schema()?;
+    let float_columns: Vec<&str> = columns
+        .iter()
+        .filter(|column| {
+            matches!(
+                schema.get(column.as_str()),
+                Some(DataType::Float32 | DataType::Float64)
+            )
+        })
+        .map(|column| column.as_str())
+        .collect();
+
+    let Some(count) = float_columns
+        .iter()
+        .map(|column| col(column).is_finite().not().cast(DataType::UInt32).sum())
+        .reduce(|total, count| total + count)
+    else {
+        return Ok((lf, 0));
+    };
+    let found = lf
+        .clone()
+        .select([count.alias("non_finite")])
+        .collect()?
+        .column("non_finite")?
+        .u32()?
+        .get(0)
+        .unwrap_or(0) as usize;
+    if found == 0 {
+        return Ok((lf, 0));
+    }
+
+    let keep_finite = |column: &str| col(column).is_finite().or(col(column).is_null());
+    let mut lf = lf;
+    for column in float_columns {
+        lf = match policy {
+            NanPolicy::Drop => lf.filter(keep_finite(column)),
+            NanPolicy::Zero => lf.with_column(
+                when(col(column).is_finite().not())
+                    .then(lit(0.0))
+                    .otherwise(col(column))
+                    .alias(column),
+            ),
+            NanPolicy::Clamp => {
+                let finite = col(column).filter(col(column).is_finite());
+                lf.with_column(
+                    when(col(column).eq(lit(f64::INFINITY)))
+                        .then(finite.clone().max())
+                        .when(col(column).eq(lit(f64::NEG_INFINITY)))
+                        .then(finite.min())
+                        .otherwise(col(column))
+                        .alias(column),
+                )
+                .filter(col(column).is_nan().not().or(col(column).is_null()))
+            }
+        };
+    }
+
+    Ok((lf, found))
+}
+
+/// Shown in place of a missing category value unless the chart sets `null_label`
+pub const DEFAULT_NULL_LABEL: &str = "(none)";
+
+/// Replace nulls in the text columns among `columns` with `label`, so a missing category is
+/// drawn under a readable name instead of `null`
+pub fn label_null_categories(lf: LazyFrame, columns: &[&str], label: &str) -> Result<LazyFrame> {
+    let schema = lf.schema()?;
+    let mut text_columns: Vec<&str> = columns
+        .iter()
+        .copied()
+        .filter(|column| matches!(schema.get(column), Some(DataType::Utf8)))
+        .collect();
+    text_columns.sort();
+    text_columns.dedup();
+    if text_columns.is_empty() {
+        return Ok(lf);
+    }
+
+    Ok(lf.with_columns(
+        text_columns
+            .into_iter()
+            .map(|column| col(column).fill_null(lit(label)))
+            .collect::<Vec<_>>(),
+    ))
+}
+
+/// Replace `y` with its period-over-period % change, `(y[i] - y[i-1]) / y[i-1] * 100`
+///
+/// Rows are sorted by `x` first, and each `group` value is its own series. The first point of a
+/// series, and any point following a zero, has no growth rate and is dropped.
+pub fn growth_rates(lf: LazyFrame, x: &str, y: &str, group: Option<&str>) -> LazyFrame {
+    let value = col(y).cast(DataType::Float64);
+    let previous = value.clone().shift(lit(1));
+    let mut growth = when(previous.clone().eq(lit(0.0)))
+        .then(lit(NULL))
+        .otherwise((value - previous.clone()) / previous * lit(100.0));
+    if let Some(group) = group {
+        growth = growth.over([col(group)]);
+    }
+
+    lf.sort_by_exprs([col(x)], [false], false, true)
+        .with_column(growth.alias(y))
+        .drop_nulls(Some(vec![col(y)]))
+}
+
 #[allow(dead_code)]
 fn apply_sorting(lf: LazyFrame, sort_configs: &[SortConfig]) -> Result<LazyFrame> {
     let mut result = lf;
 
     for config in sort_configs {
         let ascending = config.ascending.unwrap_or(true);
-        let options = SortOptions {
-            descending: !ascending,
-            ..Default::default()
-        };
-        result = result.sort(&config.column, options);
+        result = sort_by_column(result, &config.column, !ascending, DateOrder::Auto)?;
     }
 
     Ok(result)
 }
 
+/// Keep rows whose `column` date is within `from..=to`; either bound may be left open
+///
+/// Datetimes are compared by their day, and text columns are read as dates first.
+pub fn apply_date_range(
+    lf: LazyFrame,
+    column: &str,
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+    date_order: DateOrder,
+) -> Result<LazyFrame> {
+    let day = match lf.schema()?.get(column) {
+        Some(DataType::Date) => col(column),
+        Some(DataType::Datetime(_, _)) => col(column).cast(DataType::Date),
+        Some(DataType::Utf8) => {
+            let values = lf.clone().select([col(column)]).collect()?;
+            let values: Vec<&str> = values
+                .column(column)?
+                .utf8()?
+                .into_iter()
+                .flatten()
+                .collect();
+            crate::data::loader::parse_dates_expr(column, &values, date_order)
+                .with_context(|| format!("Column '{}' does not hold dates", column))?
+                .cast(DataType::Date)
+        }
+        Some(dtype) => anyhow::bail!(
+            "date_from/date_to need a date column, but '{}' is {}",
+            column,
+            dtype
+        ),
+        None => anyhow::bail!("Column '{}' not found for date_from/date_to", column),
+    };
+
+    let bound = |date: chrono::NaiveDate| lit(date.to_string()).cast(DataType::Date);
+    let mut predicate = lit(true);
+    if let Some(from) = from {
+        predicate = predicate.and(day.clone().gt_eq(bound(from)));
+    }
+    if let Some(to) = to {
+        predicate = predicate.and(day.lt_eq(bound(to)));
+    }
+    Ok(lf.filter(predicate))
+}
+
+/// Sort by `column` in the order of the values it holds: a text column of numbers sorts
+/// numerically and one of dates chronologically, rather than character by character;
+/// ambiguous dates are read in `date_order`, as the loader reads them
+pub fn sort_by_column(
+    lf: LazyFrame,
+    column: &str,
+    descending: bool,
+    date_order: DateOrder,
+) -> Result<LazyFrame> {
+    let key = sort_key(&lf, column, date_order)?;
+    Ok(lf.sort_by_exprs([key], [descending], false, false))
+}
+
+fn sort_key(lf: &LazyFrame, column: &str, date_order: DateOrder) -> Result<Expr> {
+    if lf.schema()?.get(column) != Some(&DataType::Utf8) {
+        return Ok(col(column));
+    }
+
+    let values = lf.clone().select([col(column)]).collect()?;
+    let values = values.column(column)?.utf8()?;
+    let values: Vec<&str> = values.into_iter().flatten().collect();
+    if values.is_empty() {
+        return Ok(col(column));
+    }
+
+    if values
+        .iter()
+        .all(|value| value.trim().parse::<f64>().is_ok())
+    {
+        return Ok(col(column)
+            .str()
+            .strip_chars(lit(NULL))
+            .cast(DataType::Float64));
+    }
+    Ok(
+        crate::data::loader::parse_dates_expr(column, &values, date_order)
+            .unwrap_or_else(|| col(column)),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -349,6 +584,76 @@ mod tests {
         assert_eq!(df.height(), 4);
     }
 
+    #[test]
+    fn test_sort_numeric_strings_by_value() {
+        let lf = df! {
+            "rank" => &["10", "2", "33", "1"],
+        }
+        .unwrap()
+        .lazy();
+
+        let df = sort_by_column(lf, "rank", false, DateOrder::Us)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let ranks: Vec<&str> = df
+            .column("rank")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(ranks, vec!["1", "2", "10", "33"]);
+    }
+
+    #[test]
+    fn test_sort_us_dates_chronologically() {
+        let lf = df! {
+            "day" => &["02/01/2023", "12/31/2022", "01/15/2023"],
+        }
+        .unwrap()
+        .lazy();
+
+        let df = sort_by_column(lf, "day", false, DateOrder::Us)
+            .unwrap()
+            .collect()
+            .unwrap();
+        let days: Vec<&str> = df
+            .column("day")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(days, vec!["12/31/2022", "01/15/2023", "02/01/2023"]);
+    }
+
+    #[test]
+    fn test_sort_ambiguous_dates_in_the_configured_order() {
+        let sorted = |date_order: DateOrder| -> Vec<String> {
+            let lf = df! {
+                "day" => &["03/04/2023", "02/05/2023"],
+            }
+            .unwrap()
+            .lazy();
+            let df = sort_by_column(lf, "day", false, date_order)
+                .unwrap()
+                .collect()
+                .unwrap();
+            df.column("day")
+                .unwrap()
+                .utf8()
+                .unwrap()
+                .into_no_null_iter()
+                .map(str::to_string)
+                .collect()
+        };
+
+        // February 5th before March 4th, or 3 April before 2 May
+        assert_eq!(sorted(DateOrder::Us), vec!["02/05/2023", "03/04/2023"]);
+        assert_eq!(sorted(DateOrder::Eu), vec!["03/04/2023", "02/05/2023"]);
+    }
+
     #[test]
     fn test_apply_transforms_complete_workflow() {
         let lf = create_test_lazyframe();
@@ -410,4 +715,43 @@ mod tests {
         // Should handle expression filter gracefully (even though not implemented)
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_aggregate_series_count_and_sum() {
+        let lf = create_test_lazyframe();
+        let series = vec![
+            SeriesSpec {
+                column: "users".to_string(),
+                agg: AggregationType::Count,
+            },
+            SeriesSpec {
+                column: "value".to_string(),
+                agg: AggregationType::Sum,
+            },
+        ];
+
+        let df = aggregate_series(lf, "channel", &series)
+            .sort("channel", Default::default())
+            .collect()
+            .unwrap();
+
+        let counts: Vec<_> = df
+            .column("users_count")
+            .unwrap()
+            .cast(&DataType::Int64)
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        let sums: Vec<_> = df
+            .column("value_sum")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(counts, vec![2, 2]); // direct, organic
+        assert_eq!(sums, vec![45, 25]);
+    }
 }
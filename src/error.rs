@@ -0,0 +1,117 @@
+use std::fmt;
+
+/// Failure categories that map to distinct process exit codes, so scripts can
+/// tell a bad invocation from unreadable data or a failed render
+///
+/// | Code | Meaning |
+/// |------|---------|
+/// | 1    | Any other error |
+/// | 2    | Usage or validation (bad arguments, invalid spec, missing column) |
+/// | 3    | Data or IO (file missing or unreadable, CSV could not be parsed) |
+/// | 4    | Render (chart could not be drawn or written) |
+#[derive(Debug)]
+pub enum GraffError {
+    Usage(anyhow::Error),
+    Data(anyhow::Error),
+    Render(anyhow::Error),
+}
+
+impl GraffError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            GraffError::Usage(_) => 2,
+            GraffError::Data(_) => 3,
+            GraffError::Render(_) => 4,
+        }
+    }
+
+    fn inner(&self) -> &anyhow::Error {
+        match self {
+            GraffError::Usage(err) | GraffError::Data(err) | GraffError::Render(err) => err,
+        }
+    }
+}
+
+impl fmt::Display for GraffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.inner())
+    }
+}
+
+impl std::error::Error for GraffError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner().source()
+    }
+}
+
+/// Tag an error with the category that decides the exit code
+pub trait ErrorKindExt<T> {
+    fn usage_error(self) -> anyhow::Result<T>;
+    fn data_error(self) -> anyhow::Result<T>;
+    fn render_error(self) -> anyhow::Result<T>;
+}
+
+impl<T> ErrorKindExt<T> for anyhow::Result<T> {
+    fn usage_error(self) -> anyhow::Result<T> {
+        self.map_err(|err| GraffError::Usage(err).into())
+    }
+
+    fn data_error(self) -> anyhow::Result<T> {
+        self.map_err(|err| GraffError::Data(err).into())
+    }
+
+    fn render_error(self) -> anyhow::Result<T> {
+        self.map_err(|err| GraffError::Render(err).into())
+    }
+}
+
+/// Exit code for an error returned from `cli::run`
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.downcast_ref::<GraffError>()
+        .map(GraffError::exit_code)
+        .unwrap_or(1)
+}
+
+/// An error as JSON for `--error-format json`: the top-level message, then the rest of the
+/// context chain from outermost to root cause
+pub fn error_json(err: &anyhow::Error) -> serde_json::Value {
+    let mut chain = err.chain().map(|cause| cause.to_string());
+    serde_json::json!({
+        "error": chain.next().unwrap_or_default(),
+        "context": chain.collect::<Vec<_>>(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_code_survives_added_context() {
+        let result: anyhow::Result<()> = Err(anyhow::anyhow!("no such file"));
+        let err = result
+            .data_error()
+            .context("Failed to load data")
+            .unwrap_err();
+        assert_eq!(exit_code(&err), 3);
+        assert_eq!(exit_code(&anyhow::anyhow!("untagged")), 1);
+    }
+
+    #[test]
+    fn test_error_json_keeps_the_context_chain() {
+        let result: anyhow::Result<()> = Err(anyhow::anyhow!("no such file"));
+        let err = result
+            .data_error()
+            .context("Failed to load data")
+            .unwrap_err();
+
+        assert_eq!(
+            error_json(&err),
+            serde_json::json!({
+                "error": "Failed to load data",
+                "context": ["no such file"],
+            })
+        );
+    }
+}
@@ -1,13 +1,22 @@
 mod chart;
 mod cli;
 mod data;
+mod error;
 mod render;
+mod scaffold;
 mod spec;
+mod warnings;
 
-use anyhow::Result;
 use clap::Parser;
 
-fn main() -> Result<()> {
+fn main() {
     let args = cli::Cli::parse();
-    cli::run(args)
+    let error_format = args.error_format;
+    if let Err(err) = cli::run(args) {
+        match error_format {
+            cli::ErrorFormat::Text => eprintln!("Error: {:?}", err),
+            cli::ErrorFormat::Json => eprintln!("{}", error::error_json(&err)),
+        }
+        std::process::exit(error::exit_code(&err));
+    }
 }
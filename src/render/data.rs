@@ -0,0 +1,418 @@
+use crate::spec::{ChartConfig, ChartType};
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+/// The plotted series of a chart, written by `--emit-data` for web layers that redraw it
+#[derive(Debug, Serialize)]
+pub struct ChartData {
+    pub chart_type: ChartType,
+    pub title: Option<String>,
+    pub x_axis: AxisData,
+    pub y_axis: AxisData,
+    pub series: Vec<SeriesData>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AxisData {
+    /// Source column, when the axis is read from one
+    pub column: Option<String>,
+    pub label: String,
+}
+
+/// One series as parallel arrays; heatmaps also carry the cell values in `z`
+#[derive(Debug, Serialize)]
+pub struct SeriesData {
+    pub name: String,
+    pub x: Vec<Value>,
+    pub y: Vec<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub z: Option<Vec<Value>>,
+}
+
+/// Collect the series a chart draws from its final (filtered and aggregated) data
+pub fn chart_data(df: &DataFrame, config: &ChartConfig) -> Result<ChartData> {
+    let (x_axis, y_axis, series) = match config.chart_type {
+        ChartType::Funnel => funnel_series(df, config)?,
+        ChartType::Retention => retention_series(df, config)?,
+        ChartType::Heatmap if config.z.is_some() => heatmap_series(df, config)?,
+        ChartType::Box => box_series(df, config)?,
+        ChartType::Pie => pie_series(df, config)?,
+        _ => xy_series(df, config)?,
+    };
+
+    Ok(ChartData {
+        chart_type: config.chart_type.clone(),
+        title: config.title.clone(),
+        x_axis,
+        y_axis,
+        series,
+    })
+}
+
+/// Write the chart's series to `path` as pretty-printed JSON
+pub fn write_chart_data(df: &DataFrame, config: &ChartConfig, path: &Path) -> Result<()> {
+    let data = chart_data(df, config)?;
+    let json = serde_json::to_string_pretty(&data).context("Failed to serialize chart data")?;
+    std::fs::write(path, json)
+        .with_context(|| format!("Failed to write chart data: {}", path.display()))
+}
+
+type Axes = (AxisData, AxisData, Vec<SeriesData>);
+
+fn column_axis(config: &ChartConfig, column: Option<&String>) -> AxisData {
+    AxisData {
+        column: column.cloned(),
+        label: column.map(|c| config.axis_label(c)).unwrap_or_default(),
+    }
+}
+
+/// Line, area, bar, stacked bar and scatter: one series per combo or `ys` column, per group, or
+/// just `y`
+fn xy_series(df: &DataFrame, config: &ChartConfig) -> Result<Axes> {
+    let x_column = config.x.as_ref().context("Chart requires an 'x' field")?;
+    let x = column_values(df, x_column)?;
+
+    let series = if let Some(combo) = &config.series {
+        combo
+            .iter()
+            .map(|s| {
+                let name = s.output_column();
+                Ok(SeriesData {
+                    y: column_values(df, &name)?,
+                    x: x.clone(),
+                    name,
+                    z: None,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else if let Some(ys) = &config.ys {
+        ys.iter()
+            .map(|y| {
+                Ok(SeriesData {
+                    name: y.clone(),
+                    x: x.clone(),
+                    y: column_values(df, y)?,
+                    z: None,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        let y_column = config.y.as_ref().context("Chart requires a 'y' field")?;
+        let y = column_values(df, y_column)?;
+        match &config.group_by {
+            Some(group_by) => split_by_group(&column_values(df, group_by)?, &x, &y),
+            None => vec![SeriesData {
+                name: y_column.clone(),
+                x,
+                y,
+                z: None,
+            }],
+        }
+    };
+
+    Ok((
+        column_axis(config, Some(x_column)),
+        column_axis(config, config.y.as_ref()),
+        series,
+    ))
+}
+
+/// Partition rows into one series per group value, in first-seen order
+fn split_by_group(groups: &[Value], x: &[Value], y: &[Value]) -> Vec<SeriesData> {
+    let mut series: Vec<SeriesData> = Vec::new();
+    for ((group, x), y) in groups.iter().zip(x).zip(y) {
+        let name = match group {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let index = match series.iter().position(|s| s.name == name) {
+            Some(index) => index,
+            None => {
+                series.push(SeriesData {
+                    name,
+                    x: Vec::new(),
+                    y: Vec::new(),
+                    z: None,
+                });
+                series.len() - 1
+            }
+        };
+        series[index].x.push(x.clone());
+        series[index].y.push(y.clone());
+    }
+    series
+}
+
+/// Box plots: every `y` value against its `group_by` category, the distribution each box
+/// summarizes
+fn box_series(df: &DataFrame, config: &ChartConfig) -> Result<Axes> {
+    let group_column = config
+        .group_by
+        .as_ref()
+        .context("Box plot requires a 'group_by' field")?;
+    let y_column = config.y.as_ref().context("Box plot requires a 'y' field")?;
+
+    let series = SeriesData {
+        name: y_column.clone(),
+        x: column_values(df, group_column)?,
+        y: column_values(df, y_column)?,
+        z: None,
+    };
+
+    Ok((
+        column_axis(config, Some(group_column)),
+        column_axis(config, Some(y_column)),
+        vec![series],
+    ))
+}
+
+/// Heatmaps: one entry per drawn cell, with repeated (x, y) pairs summed as the renderer does;
+/// discrete z is exported as its category
+fn heatmap_series(df: &DataFrame, config: &ChartConfig) -> Result<Axes> {
+    let x_column = config.x.as_ref().context("Heatmap requires an 'x' field")?;
+    let y_column = config.y.as_ref().context("Heatmap requires a 'y' field")?;
+    let z_column = config.z.as_ref().context("Heatmap requires a 'z' field")?;
+
+    let mut series = SeriesData {
+        name: z_column.clone(),
+        x: Vec::new(),
+        y: Vec::new(),
+        z: Some(Vec::new()),
+    };
+    if let Some(crate::chart::heatmap::HeatmapGrid { grid, categories }) =
+        crate::chart::heatmap::heatmap_grid(df, config)?
+    {
+        let z = series.z.get_or_insert_default();
+        for &(column, row, value) in &grid.cells {
+            series.x.push(Value::from(grid.columns[column].clone()));
+            series.y.push(Value::from(grid.rows[row].clone()));
+            z.push(match &categories {
+                Some(categories) => Value::from(categories[value as usize].clone()),
+                None => number(value as f64),
+            });
+        }
+    }
+
+    Ok((
+        column_axis(config, Some(x_column)),
+        column_axis(config, Some(y_column)),
+        vec![series],
+    ))
+}
+
+/// Pies: one entry per drawn wedge, with repeated categories summed and empty ones left out
+fn pie_series(df: &DataFrame, config: &ChartConfig) -> Result<Axes> {
+    let x_column = config
+        .x
+        .as_ref()
+        .context("Pie chart requires an 'x' field")?;
+    let y_column = config
+        .y
+        .as_ref()
+        .context("Pie chart requires a 'y' field")?;
+    let pie = crate::chart::pie::pie_slices(df, config)?;
+
+    let series = SeriesData {
+        name: y_column.clone(),
+        x: pie
+            .slices
+            .iter()
+            .map(|(label, _)| Value::from(label.clone()))
+            .collect(),
+        y: pie.slices.iter().map(|(_, total)| number(*total)).collect(),
+        z: None,
+    };
+
+    Ok((
+        column_axis(config, Some(x_column)),
+        column_axis(config, Some(y_column)),
+        vec![series],
+    ))
+}
+
+fn funnel_series(df: &DataFrame, config: &ChartConfig) -> Result<Axes> {
+    let steps = crate::chart::funnel::ordered_step_values(df, config)?;
+    let name = config
+        .values
+        .clone()
+        .or_else(|| config.user_column.clone())
+        .unwrap_or_default();
+
+    let series = SeriesData {
+        name: name.clone(),
+        x: steps
+            .iter()
+            .map(|(step, _)| Value::from(step.clone()))
+            .collect(),
+        y: steps
+            .iter()
+            .map(|(_, value)| number(*value as f64))
+            .collect(),
+        z: None,
+    };
+
+    Ok((
+        AxisData {
+            column: config.step_column.clone(),
+            label: "Step".to_string(),
+        },
+        AxisData {
+            column: Some(name.clone()),
+            label: config.axis_label(&name),
+        },
+        vec![series],
+    ))
+}
+
+/// One series per cohort, plus the average row when enabled: retention % for each period
+fn retention_series(df: &DataFrame, config: &ChartConfig) -> Result<Axes> {
+    let series = match crate::chart::retention::retention_table(df, config)? {
+        Some(table) => {
+            let averages = config
+                .average_row
+                .unwrap_or(false)
+                .then(|| ("Average".to_string(), table.period_averages()));
+            table
+                .cohorts
+                .iter()
+                .cloned()
+                .zip(table.values.iter().cloned())
+                .chain(averages)
+                .map(|(name, row)| SeriesData {
+                    name,
+                    x: table.periods.iter().map(|&p| Value::from(p)).collect(),
+                    // Periods no cohort has reached are left empty
+                    y: row
+                        .iter()
+                        .map(|v| v.map_or(Value::Null, |v| number(v as f64)))
+                        .collect(),
+                    z: None,
+                })
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    Ok((
+        AxisData {
+            column: config.period_number.clone(),
+            label: "Period".to_string(),
+        },
+        AxisData {
+            column: None,
+            label: "Retention %".to_string(),
+        },
+        series,
+    ))
+}
+
+/// Column values as JSON: numbers stay numbers, everything else becomes its display string
+fn column_values(df: &DataFrame, column: &str) -> Result<Vec<Value>> {
+    let series = df
+        .column(column)
+        .with_context(|| format!("Column '{}' not found", column))?;
+    Ok((0..series.len())
+        .map(|i| series.get(i).map(json_value).unwrap_or(Value::Null))
+        .collect())
+}
+
+fn json_value(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(b) => Value::from(b),
+        AnyValue::Utf8(s) => Value::from(s),
+        AnyValue::Int32(i) => Value::from(i),
+        AnyValue::Int64(i) => Value::from(i),
+        AnyValue::UInt32(u) => Value::from(u),
+        AnyValue::UInt64(u) => Value::from(u),
+        AnyValue::Float32(f) => number(f as f64),
+        AnyValue::Float64(f) => number(f),
+        other => Value::from(other.to_string()),
+    }
+}
+
+/// JSON has no NaN or infinity, so those become null
+fn number(value: f64) -> Value {
+    serde_json::Number::from_f64(value)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grouped_chart_data_has_one_series_per_group() {
+        let df = df! {
+            "date" => &["2023-01-01", "2023-01-01", "2023-01-02", "2023-01-02"],
+            "channel" => &["organic", "paid", "organic", "paid"],
+            "users" => &[100i64, 40, 150, 60],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            group_by: Some("channel".to_string()),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_value(chart_data(&df, &config).unwrap()).unwrap();
+
+        assert_eq!(json["chart_type"], "line");
+        assert_eq!(json["x_axis"]["column"], "date");
+        assert_eq!(
+            json["series"],
+            serde_json::json!([
+                { "name": "organic", "x": ["2023-01-01", "2023-01-02"], "y": [100, 150] },
+                { "name": "paid", "x": ["2023-01-01", "2023-01-02"], "y": [40, 60] },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_heatmap_and_pie_data_match_what_is_drawn() {
+        let df = df! {
+            "day" => &["mon", "mon", "tue", "wed"],
+            "hour" => &["9", "9", "9", "10"],
+            "visits" => &[3.0, 4.0, 5.0, 0.0],
+        }
+        .unwrap();
+        let heatmap = ChartConfig {
+            chart_type: ChartType::Heatmap,
+            x: Some("day".to_string()),
+            y: Some("hour".to_string()),
+            z: Some("visits".to_string()),
+            ..Default::default()
+        };
+
+        // The repeated mon/9 cell is summed, as the heatmap colors it
+        let json = serde_json::to_value(chart_data(&df, &heatmap).unwrap()).unwrap();
+        assert_eq!(
+            json["series"][0],
+            serde_json::json!({
+                "name": "visits",
+                "x": ["mon", "tue", "wed"],
+                "y": ["9", "9", "10"],
+                "z": [7.0, 5.0, 0.0],
+            })
+        );
+
+        // The pie sums mon too, and draws no wedge for wed's zero
+        let pie = ChartConfig {
+            chart_type: ChartType::Pie,
+            y: Some("visits".to_string()),
+            z: None,
+            ..heatmap
+        };
+        let json = serde_json::to_value(chart_data(&df, &pie).unwrap()).unwrap();
+        assert_eq!(
+            json["series"][0],
+            serde_json::json!({ "name": "visits", "x": ["mon", "tue"], "y": [7.0, 5.0] })
+        );
+    }
+}
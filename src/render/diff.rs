@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use image::{Rgba, RgbaImage};
+use std::path::Path;
+
+/// Differing pixels are painted this color in the diff image
+const DIFF_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+/// Result of comparing two images pixel by pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDiff {
+    pub differing: u64,
+    pub total: u64,
+}
+
+impl ImageDiff {
+    /// Share of pixels that differ, from 0.0 to 1.0
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.differing as f64 / self.total as f64
+        }
+    }
+}
+
+/// Compare two images of the same size; with `diff_out`, also write an image that shows the
+/// differing pixels in red over a faded copy of `a`
+pub fn diff_images(a: &Path, b: &Path, diff_out: Option<&Path>) -> Result<ImageDiff> {
+    let a_image = load_rgba(a)?;
+    let b_image = load_rgba(b)?;
+
+    if a_image.dimensions() != b_image.dimensions() {
+        let (aw, ah) = a_image.dimensions();
+        let (bw, bh) = b_image.dimensions();
+        anyhow::bail!(
+            "Images have different dimensions: {} is {}x{}, {} is {}x{}",
+            a.display(),
+            aw,
+            ah,
+            b.display(),
+            bw,
+            bh
+        );
+    }
+
+    let (width, height) = a_image.dimensions();
+    let mut diff_image = diff_out.map(|_| RgbaImage::new(width, height));
+    let mut differing = 0;
+    for (x, y, a_pixel) in a_image.enumerate_pixels() {
+        let same = a_pixel == b_image.get_pixel(x, y);
+        if !same {
+            differing += 1;
+        }
+        if let Some(diff_image) = diff_image.as_mut() {
+            let pixel = if same { faded(a_pixel) } else { DIFF_COLOR };
+            diff_image.put_pixel(x, y, pixel);
+        }
+    }
+
+    if let (Some(diff_image), Some(diff_out)) = (diff_image, diff_out) {
+        diff_image
+            .save(diff_out)
+            .with_context(|| format!("Failed to write diff image: {}", diff_out.display()))?;
+    }
+
+    Ok(ImageDiff {
+        differing,
+        total: width as u64 * height as u64,
+    })
+}
+
+fn load_rgba(path: &Path) -> Result<RgbaImage> {
+    Ok(image::open(path)
+        .with_context(|| format!("Failed to open image: {}", path.display()))?
+        .to_rgba8())
+}
+
+/// Unchanged pixels are washed out towards white so the red differences stand out
+fn faded(pixel: &Rgba<u8>) -> Rgba<u8> {
+    let fade = |channel: u8| 255 - (255 - channel) / 4;
+    Rgba([fade(pixel[0]), fade(pixel[1]), fade(pixel[2]), 255])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_against_self_and_modified_copy() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let original = dir.path().join("original.png");
+        let modified = dir.path().join("modified.png");
+        let diff_out = dir.path().join("diff.png");
+
+        let mut chart = RgbaImage::from_pixel(20, 10, Rgba([255, 255, 255, 255]));
+        chart.save(&original).unwrap();
+        for x in 0..5 {
+            chart.put_pixel(x, 0, Rgba([0, 0, 0, 255]));
+        }
+        chart.save(&modified).unwrap();
+
+        let same = diff_images(&original, &original, None).unwrap();
+        assert_eq!(same.differing, 0);
+        assert_eq!(same.fraction(), 0.0);
+
+        let changed = diff_images(&original, &modified, Some(&diff_out)).unwrap();
+        assert_eq!(changed.differing, 5);
+        assert!(changed.fraction() > 0.0);
+        assert_eq!(
+            *image::open(&diff_out).unwrap().to_rgba8().get_pixel(0, 0),
+            DIFF_COLOR
+        );
+    }
+
+    #[test]
+    fn test_diff_rejects_mismatched_dimensions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let small = dir.path().join("small.png");
+        let large = dir.path().join("large.png");
+        RgbaImage::new(10, 10).save(&small).unwrap();
+        RgbaImage::new(20, 10).save(&large).unwrap();
+
+        let err = diff_images(&small, &large, None).unwrap_err();
+        assert!(err.to_string().contains("different dimensions"));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::spec::NumberFormat;
+use plotters::coord::ranged1d::ValueFormatter;
+use plotters::coord::types::RangedCoordf32;
+
+/// Column name fragments that mark a column as holding percentages
+const PERCENT_NAME_HINTS: &[&str] = &["pct", "rate", "%", "percent"];
+
+/// How numeric axis labels are rendered
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelFormat {
+    pub format: NumberFormat,
+    /// Values are fractions (0–1) and must be scaled by 100 when shown as percentages
+    pub fraction: bool,
+}
+
+impl LabelFormat {
+    pub fn plain() -> Self {
+        Self {
+            format: NumberFormat::Plain,
+            fraction: false,
+        }
+    }
+
+    pub fn format(&self, value: f32) -> String {
+        match self.format {
+            NumberFormat::Plain => RangedCoordf32::format(&value),
+            NumberFormat::Thousands => with_thousands(value),
+            NumberFormat::SiCompact => si_compact(value),
+            NumberFormat::Percent => {
+                let percent = if self.fraction { value * 100.0 } else { value };
+                format!("{}%", trim_decimals(percent, 1))
+            }
+        }
+    }
+
+    /// Format for values written on the chart: plain numbers gain thousands separators
+    pub fn with_separators(&self) -> Self {
+        match self.format {
+            NumberFormat::Plain => Self {
+                format: NumberFormat::Thousands,
+                fraction: self.fraction,
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+/// Choose the label format for a value column: an explicit format wins, otherwise
+/// percentage columns are detected by name or by values bounded to 0–1
+pub fn select_label_format(
+    explicit: Option<&NumberFormat>,
+    column: &str,
+    values: &[f32],
+) -> LabelFormat {
+    let fraction = is_fraction_column(values);
+
+    if let Some(format) = explicit {
+        return LabelFormat {
+            format: format.clone(),
+            fraction: *format == NumberFormat::Percent && fraction,
+        };
+    }
+
+    let name = column.to_lowercase();
+    let named_percent = PERCENT_NAME_HINTS.iter().any(|hint| name.contains(hint));
+    let within_percent_range = values.iter().all(|v| (0.0..=100.0).contains(v));
+
+    if fraction || (named_percent && within_percent_range && !values.is_empty()) {
+        LabelFormat {
+            format: NumberFormat::Percent,
+            fraction,
+        }
+    } else {
+        LabelFormat::plain()
+    }
+}
+
+/// Values all lie in 0–1 and at least one is not a whole number
+fn is_fraction_column(values: &[f32]) -> bool {
+    !values.is_empty()
+        && values.iter().all(|v| (0.0..=1.0).contains(v))
+        && values.iter().any(|v| v.fract() != 0.0)
+}
+
+/// Format with at most `decimals` decimal places, dropping trailing zeros
+fn trim_decimals(value: f32, decimals: usize) -> String {
+    let text = format!("{:.*}", decimals, value);
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    };
+    if text == "-0" { "0".to_string() } else { text }
+}
+
+fn with_thousands(value: f32) -> String {
+    let text = trim_decimals(value, 2);
+    let (sign, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text.as_str()),
+    };
+    let (integer, decimal) = match unsigned.split_once('.') {
+        Some((integer, decimal)) => (integer, Some(decimal)),
+        None => (unsigned, None),
+    };
+
+    let mut grouped = String::new();
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    match decimal {
+        Some(decimal) => format!("{}{}.{}", sign, grouped, decimal),
+        None => format!("{}{}", sign, grouped),
+    }
+}
+
+fn si_compact(value: f32) -> String {
+    const SUFFIXES: &[(f32, &str)] = &[(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "k")];
+
+    for &(scale, suffix) in SUFFIXES {
+        if value.abs() >= scale {
+            return format!("{}{}", trim_decimals(value / scale, 1), suffix);
+        }
+    }
+    trim_decimals(value, 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversion_rate_fraction_shows_percentages() {
+        let values = vec![0.12, 0.25, 0.4, 0.05];
+        let format = select_label_format(None, "conversion_rate", &values);
+        assert_eq!(format.format, NumberFormat::Percent);
+        assert_eq!(format.format(0.25), "25%");
+        assert_eq!(format.format(0.125), "12.5%");
+    }
+
+    #[test]
+    fn test_named_percent_column_in_hundreds() {
+        let values = vec![12.0, 48.0, 90.0];
+        let format = select_label_format(None, "retention_pct", &values);
+        assert_eq!(format.format(48.0), "48%");
+    }
+
+    #[test]
+    fn test_explicit_format_overrides_detection() {
+        let values = vec![0.12, 0.25];
+        let format = select_label_format(Some(&NumberFormat::Plain), "conversion_rate", &values);
+        assert_eq!(format, LabelFormat::plain());
+    }
+
+    #[test]
+    fn test_counts_stay_plain() {
+        let values = vec![120.0, 4500.0];
+        let format = select_label_format(None, "signups", &values);
+        assert_eq!(format, LabelFormat::plain());
+    }
+
+    #[test]
+    fn test_thousands_and_compact() {
+        let thousands = select_label_format(Some(&NumberFormat::Thousands), "revenue", &[]);
+        assert_eq!(thousands.format(1234567.0), "1,234,567");
+        let compact = select_label_format(Some(&NumberFormat::SiCompact), "revenue", &[]);
+        assert_eq!(compact.format(2500.0), "2.5k");
+        assert_eq!(compact.format(3_000_000.0), "3M");
+        assert_eq!(compact.format(1_500_000.0), "1.5M");
+        assert_eq!(compact.format(2300.0), "2.3k");
+    }
+}
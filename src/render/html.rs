@@ -0,0 +1,113 @@
+//! `format: html`: a chart and the data behind it as one self-contained page
+use super::data::ChartData;
+use serde_json::Value;
+
+const PAGE_STYLE: &str = "body { font-family: sans-serif; margin: 2em; }\n\
+    table { border-collapse: collapse; margin-top: 2em; }\n\
+    th, td { border: 1px solid #ccc; padding: 0.3em 0.8em; }\n\
+    td.number { text-align: right; }\n";
+
+/// An HTML page showing the `svg` drawing, then `data` as a table with a row per plotted point
+///
+/// Charts with several series get a leading column naming each row's series, and heatmaps a
+/// trailing column for the cell value.
+pub fn html_page(svg: &str, data: &ChartData) -> String {
+    let title = escape(data.title.as_deref().unwrap_or("Chart"));
+    let named_series = data.series.len() > 1;
+    let z_name = data
+        .series
+        .iter()
+        .find(|series| series.z.is_some())
+        .map(|series| series.name.as_str());
+
+    let mut header = Vec::new();
+    if named_series {
+        header.push("Series");
+    }
+    header.push(heading(&data.x_axis.label, "x"));
+    header.push(heading(&data.y_axis.label, "y"));
+    header.extend(z_name);
+    let header: String = header
+        .iter()
+        .map(|name| format!("<th>{}</th>", escape(name)))
+        .collect();
+
+    let mut rows = String::new();
+    for series in &data.series {
+        for (i, (x, y)) in series.x.iter().zip(&series.y).enumerate() {
+            let mut cells = Vec::new();
+            if named_series {
+                cells.push(Value::from(series.name.clone()));
+            }
+            cells.push(x.clone());
+            cells.push(y.clone());
+            if let Some(z) = &series.z {
+                cells.push(z.get(i).cloned().unwrap_or(Value::Null));
+            }
+            let cells: String = cells.iter().map(cell).collect();
+            rows.push_str(&format!("<tr>{}</tr>\n", cells));
+        }
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>\n{}</style>\n</head>\n<body>\n{}\n<table>\n\
+         <thead><tr>{}</tr></thead>\n<tbody>\n{}</tbody>\n</table>\n</body>\n</html>\n",
+        title, PAGE_STYLE, svg, header, rows
+    )
+}
+
+fn heading<'a>(label: &'a str, fallback: &'a str) -> &'a str {
+    if label.is_empty() { fallback } else { label }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "<td></td>".to_string(),
+        Value::Number(number) => format!("<td class=\"number\">{}</td>", number),
+        Value::String(text) => format!("<td>{}</td>", escape(text)),
+        other => format!("<td>{}</td>", escape(&other.to_string())),
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::spec::{ChartConfig, ChartType, OutputFormat};
+    use polars::prelude::*;
+
+    #[test]
+    fn test_html_output_holds_the_chart_and_its_data() {
+        let df = df! {
+            "channel" => &["organic", "direct", "R&D"],
+            "users" => &[300i64, 120, 60],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Bar,
+            title: Some("Users".to_string()),
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            format: Some(OutputFormat::Html),
+            ..Default::default()
+        };
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("users.html");
+
+        crate::render::render_chart(df.lazy(), &config, &path, None).unwrap();
+
+        let page = std::fs::read_to_string(&path).unwrap();
+        assert!(page.starts_with("<!DOCTYPE html>"));
+        assert!(page.contains("<svg"));
+        assert!(page.contains("<th>channel</th><th>users</th>"));
+        assert!(page.contains("<tr><td>organic</td><td class=\"number\">300</td></tr>"));
+        assert!(page.contains("<tr><td>R&amp;D</td><td class=\"number\">60</td></tr>"));
+        assert_eq!(page.matches("<tr><td").count(), 3);
+    }
+}
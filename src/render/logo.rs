@@ -0,0 +1,70 @@
+use crate::spec::LogoPosition;
+use anyhow::{Context, Result};
+use plotters::prelude::*;
+use std::path::Path;
+
+/// Gap between the logo and the canvas edges, in pixels
+const LOGO_MARGIN: i32 = 10;
+
+/// Top-left pixel of a logo of `logo_size` placed in a corner of a canvas of `canvas_size`
+pub fn logo_origin(
+    canvas_size: (u32, u32),
+    logo_size: (u32, u32),
+    position: &LogoPosition,
+) -> (i32, i32) {
+    let right = canvas_size.0 as i32 - logo_size.0 as i32 - LOGO_MARGIN;
+    let bottom = canvas_size.1 as i32 - logo_size.1 as i32 - LOGO_MARGIN;
+
+    match position {
+        LogoPosition::TopLeft => (LOGO_MARGIN, LOGO_MARGIN),
+        LogoPosition::TopRight => (right, LOGO_MARGIN),
+        LogoPosition::BottomLeft => (LOGO_MARGIN, bottom),
+        LogoPosition::BottomRight => (right, bottom),
+    }
+}
+
+/// Load a logo image, blending any transparency over the canvas background
+pub fn load_logo(path: &Path, background: &RGBColor) -> Result<image::RgbImage> {
+    let logo = image::open(path)
+        .with_context(|| format!("Failed to load logo '{}'", path.display()))?
+        .to_rgba8();
+
+    let mut flattened = image::RgbImage::new(logo.width(), logo.height());
+    for (x, y, pixel) in logo.enumerate_pixels() {
+        let [r, g, b, a] = pixel.0;
+        let alpha = a as f32 / 255.0;
+        let blend = |fg: u8, bg: u8| (fg as f32 * alpha + bg as f32 * (1.0 - alpha)).round() as u8;
+        flattened.put_pixel(
+            x,
+            y,
+            image::Rgb([
+                blend(r, background.0),
+                blend(g, background.1),
+                blend(b, background.2),
+            ]),
+        );
+    }
+
+    Ok(flattened)
+}
+
+/// Composite a logo into a corner of the canvas
+pub fn draw_logo<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    path: &Path,
+    position: &LogoPosition,
+    background: &RGBColor,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let logo = load_logo(path, background)?;
+    let size = logo.dimensions();
+    let origin = logo_origin(root.dim_in_pixel(), size, position);
+
+    let element = BitMapElement::with_owned_buffer(origin, size, logo.into_raw())
+        .ok_or_else(|| anyhow::anyhow!("Logo '{}' has an invalid pixel buffer", path.display()))?;
+    root.draw(&element).context("Failed to draw logo")?;
+
+    Ok(())
+}
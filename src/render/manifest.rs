@@ -0,0 +1,201 @@
+use crate::spec::{ChartConfig, ChartType};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// Every chart a `render --manifest` run produced, written as `manifest.json`
+#[derive(Debug, Default, Serialize)]
+pub struct Manifest {
+    pub charts: Vec<ManifestEntry>,
+}
+
+/// One chart of the batch; `output` is relative to the manifest's directory
+#[derive(Debug, Serialize)]
+pub struct ManifestEntry {
+    pub title: Option<String>,
+    #[serde(rename = "type")]
+    pub chart_type: ChartType,
+    pub source: String,
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    pub status: ChartStatus,
+    /// CRC-32 of the output file, as `crc32:` and 8 hex digits; only for charts that were written
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    /// Why the chart failed, with its causes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Whether a chart was rendered
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartStatus {
+    Success,
+    Failure,
+}
+
+impl ManifestEntry {
+    /// Describe a chart that has been written to `output_path`; fails when the output can't be
+    /// read back, and the chart should then be reported as failed
+    pub fn rendered(
+        config: &ChartConfig,
+        source: &crate::data::DataSource,
+        manifest_dir: &Path,
+        output_path: &Path,
+    ) -> Result<Self> {
+        let bytes = std::fs::read(output_path)
+            .with_context(|| format!("Failed to read chart: {}", output_path.display()))?;
+
+        Ok(Self {
+            checksum: Some(format!("crc32:{:08x}", crc32fast::hash(&bytes))),
+            ..Self::describe(
+                config,
+                source,
+                manifest_dir,
+                output_path,
+                ChartStatus::Success,
+            )
+        })
+    }
+
+    /// Describe a chart that failed to render to `output_path`
+    pub fn failed(
+        config: &ChartConfig,
+        source: &crate::data::DataSource,
+        manifest_dir: &Path,
+        output_path: &Path,
+        error: &anyhow::Error,
+    ) -> Self {
+        Self {
+            error: Some(format!("{:#}", error)),
+            ..Self::describe(
+                config,
+                source,
+                manifest_dir,
+                output_path,
+                ChartStatus::Failure,
+            )
+        }
+    }
+
+    fn describe(
+        config: &ChartConfig,
+        source: &crate::data::DataSource,
+        manifest_dir: &Path,
+        output_path: &Path,
+        status: ChartStatus,
+    ) -> Self {
+        let (width, height) = crate::render::chart_dimensions(config);
+        let output = output_path
+            .strip_prefix(manifest_dir)
+            .unwrap_or(output_path);
+
+        Self {
+            title: config.title.clone(),
+            chart_type: config.chart_type.clone(),
+            source: source.to_string(),
+            output: output.to_string_lossy().into_owned(),
+            width,
+            height,
+            status,
+            checksum: None,
+            error: None,
+        }
+    }
+}
+
+impl Manifest {
+    /// Write the manifest to `path` as pretty-printed JSON
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize manifest")?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write manifest: {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataSource;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_entry_describes_the_written_chart() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output_path = dir.path().join("weekly-users-Bar.png");
+        std::fs::write(&output_path, b"123456789").unwrap();
+
+        let config = ChartConfig {
+            chart_type: ChartType::Bar,
+            title: Some("Weekly Users".to_string()),
+            width: Some(640),
+            ..Default::default()
+        };
+        let source = DataSource::File(PathBuf::from("data/users.csv"));
+        let entry = ManifestEntry::rendered(&config, &source, dir.path(), &output_path).unwrap();
+
+        assert_eq!(entry.output, "weekly-users-Bar.png");
+        assert_eq!((entry.width, entry.height), (640, 800));
+        // The standard CRC-32 check value
+        assert_eq!(entry.checksum.as_deref(), Some("crc32:cbf43926"));
+
+        let json = serde_json::to_value(Manifest {
+            charts: vec![entry],
+        })
+        .unwrap();
+        assert_eq!(json["charts"][0]["type"], "bar");
+        assert_eq!(json["charts"][0]["source"], "data/users.csv");
+        assert_eq!(json["charts"][0]["status"], "success");
+        assert!(json["charts"][0].get("error").is_none());
+    }
+
+    #[test]
+    fn test_failed_entry_carries_the_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output_path = dir.path().join("broken-Line.png");
+        let config = ChartConfig {
+            chart_type: ChartType::Line,
+            title: Some("Broken".to_string()),
+            ..Default::default()
+        };
+        let source = DataSource::File(PathBuf::from("data/users.csv"));
+        let error = anyhow::anyhow!("Column 'missing' not found").context("Failed to render");
+        let entry = ManifestEntry::failed(&config, &source, dir.path(), &output_path, &error);
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["status"], "failure");
+        assert_eq!(json["output"], "broken-Line.png");
+        assert_eq!(
+            json["error"],
+            "Failed to render: Column 'missing' not found"
+        );
+        assert!(json.get("checksum").is_none());
+    }
+
+    #[test]
+    fn test_unreadable_output_is_an_error() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let missing = dir.path().join("gone-Line.png");
+        let config = ChartConfig {
+            chart_type: ChartType::Line,
+            ..Default::default()
+        };
+        let source = DataSource::File(PathBuf::from("data/users.csv"));
+
+        // The batch reports the chart as failed and records the error instead
+        let error = ManifestEntry::rendered(&config, &source, dir.path(), &missing).unwrap_err();
+        let entry = ManifestEntry::failed(&config, &source, dir.path(), &missing, &error);
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["status"], "failure");
+        assert!(
+            json["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Failed to read chart:")
+        );
+        assert!(json.get("checksum").is_none());
+    }
+}
@@ -1,33 +1,93 @@
 use crate::spec::{ChartConfig, ChartType, OutputFormat};
 use anyhow::{Context, Result};
 use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
 use polars::prelude::*;
-use std::path::Path;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
+pub mod data;
+pub mod diff;
+pub mod format;
+pub mod html;
+pub mod logo;
+pub mod manifest;
+pub mod pdf;
+pub mod quality;
+pub mod simulate;
 pub mod styling;
+pub mod svg;
 
-pub fn render_chart(data: LazyFrame, config: &ChartConfig, output_path: &Path) -> Result<()> {
+/// Shown in place of the chart when there are no rows left to plot
+pub const NO_DATA_MESSAGE: &str = "No data to display";
+
+pub fn render_chart(
+    data: LazyFrame,
+    config: &ChartConfig,
+    output_path: &Path,
+    legend_path: Option<&Path>,
+) -> Result<()> {
     // Collect the data for rendering
     let df = data
         .collect()
         .context("Failed to collect data for rendering")?;
 
-    // Calculate dimensions
-    let width = config.width.unwrap_or(800);
-    let height = config.height.unwrap_or(600);
-    let scaled_width = (width as f32 * 1.0) as u32;
-    let scaled_height = (height as f32 * 1.0) as u32;
+    // The legend goes beside the chart unless it is written to its own file
+    let with_legend = legend_path.is_none();
+    let size = canvas_size(config);
 
     // Render based on output format
     match config.format.as_ref().unwrap_or(&OutputFormat::Png) {
-        OutputFormat::Png => {
-            render_to_bitmap(&df, config, output_path, scaled_width, scaled_height)
-        }
-        OutputFormat::Svg => render_to_svg(&df, config, output_path, scaled_width, scaled_height),
-        OutputFormat::Pdf => {
-            // For now, render as PNG for PDF (could be enhanced later)
-            render_to_bitmap(&df, config, output_path, scaled_width, scaled_height)
+        OutputFormat::Png | OutputFormat::Webp => {
+            render_to_bitmap(&df, config, output_path, size, with_legend)
         }
+        OutputFormat::Svg => render_to_svg(&df, config, output_path, size, with_legend),
+        OutputFormat::Pdf => render_to_pdf(&df, config, output_path, size, with_legend),
+        OutputFormat::Html => render_to_html(&df, config, output_path, size, with_legend),
+    }?;
+
+    if let Some(legend_path) = legend_path {
+        render_legend_file(&df, config, legend_path)?;
+    }
+
+    Ok(())
+}
+
+/// Size of the written chart: its canvas enlarged by `scale`, text and lines included
+pub fn chart_dimensions(config: &ChartConfig) -> (u32, u32) {
+    let (width, height) = canvas_size(config);
+    let scale = config.scale.unwrap_or(1.0);
+    let scaled_width = (width as f64 * scale).round().max(1.0) as u32;
+    let scaled_height = (height as f64 * scale).round().max(1.0) as u32;
+    (scaled_width, scaled_height)
+}
+
+/// Size a chart is laid out at: its `format_sizes` entry for the output format, else its
+/// `width`/`height`, else the type's default
+fn canvas_size(config: &ChartConfig) -> (u32, u32) {
+    let format = config.format.as_ref().unwrap_or(&OutputFormat::Png);
+    if let Some(&[width, height]) = config
+        .format_sizes
+        .as_ref()
+        .and_then(|sizes| sizes.get(format))
+    {
+        return (width, height);
+    }
+
+    let (default_width, default_height) = default_dimensions(&config.chart_type);
+    (
+        config.width.unwrap_or(default_width),
+        config.height.unwrap_or(default_height),
+    )
+}
+
+/// Canvas size for charts that don't set their own: funnels are tall and narrow,
+/// retention matrices wide
+pub fn default_dimensions(chart_type: &ChartType) -> (u32, u32) {
+    match chart_type {
+        ChartType::Funnel => (1000, 1200),
+        ChartType::Retention => (1800, 900),
+        _ => (1400, 800),
     }
 }
 
@@ -35,28 +95,299 @@ fn render_to_bitmap(
     df: &DataFrame,
     config: &ChartConfig,
     output_path: &Path,
-    width: u32,
-    height: u32,
+    size: (u32, u32),
+    with_legend: bool,
 ) -> Result<()> {
-    let backend = BitMapBackend::new(output_path, (width, height)).into_drawing_area();
-    render_chart_impl(df, config, backend)
+    let output = chart_dimensions(config);
+    let factor = draw_factor(config, size);
+    let webp = config.format == Some(OutputFormat::Webp);
+    let image: image::DynamicImage = match canvas_fill(config)? {
+        Some(canvas) if factor == 1 && output == size && !webp => {
+            let backend = BitMapBackend::new(output_path, size).into_drawing_area();
+            return render_chart_impl(df, config, backend, with_legend, Some(canvas));
+        }
+        Some(canvas) => {
+            let (width, height) = size;
+            let mut buffer = vec![0u8; (width * factor) as usize * (height * factor) as usize * 3];
+            let backend =
+                BitMapBackend::with_buffer(&mut buffer, (width * factor, height * factor));
+            let area = quality::Supersampled::new(backend, factor).into_drawing_area();
+            render_chart_impl(df, config, area, with_legend, Some(canvas))?;
+            quality::downsample(buffer, size, factor, output)?.into()
+        }
+        None => render_transparent(size, factor, output, |area, fill| {
+            render_chart_impl(df, config, area, with_legend, Some(fill))
+        })?
+        .into(),
+    };
+
+    if webp {
+        return write_webp(&image, config.webp_quality.unwrap_or(80), output_path);
+    }
+    image
+        .save_with_format(output_path, image::ImageFormat::Png)
+        .with_context(|| format!("Failed to write image: {}", output_path.display()))
+}
+
+/// Longest side, in pixels, that a bitmap chart is drawn at before it is resampled
+const MAX_DRAW_SIDE: u32 = 10000;
+
+/// Whole multiple of the canvas a bitmap chart is drawn at: enough to cover its scaled output,
+/// times the quality's supersampling, but never past `MAX_DRAW_SIDE`
+fn draw_factor(config: &ChartConfig, size: (u32, u32)) -> u32 {
+    let enlarge = config.scale.unwrap_or(1.0).ceil().max(1.0) as u32;
+    let supersampling = config
+        .quality
+        .as_ref()
+        .map_or(1, crate::spec::Quality::supersampling);
+    let longest = size.0.max(size.1).max(1);
+    (enlarge * supersampling)
+        .min(MAX_DRAW_SIDE / longest)
+        .max(1)
+}
+
+/// Encode `image` as WebP: lossy at `quality` (0-100), or lossless at 100
+fn write_webp(image: &image::DynamicImage, quality: u8, output_path: &Path) -> Result<()> {
+    let (width, height) = (image.width(), image.height());
+    let rgb;
+    let encoder = match image {
+        image::DynamicImage::ImageRgba8(pixels) => {
+            ::webp::Encoder::from_rgba(pixels.as_raw(), width, height)
+        }
+        other => {
+            rgb = other.to_rgb8();
+            ::webp::Encoder::from_rgb(rgb.as_raw(), width, height)
+        }
+    };
+    let encoded = if quality == 100 {
+        encoder.encode_lossless()
+    } else {
+        encoder.encode(quality as f32)
+    };
+    std::fs::write(output_path, &*encoded)
+        .with_context(|| format!("Failed to write image: {}", output_path.display()))
 }
 
 fn render_to_svg(
     df: &DataFrame,
     config: &ChartConfig,
     output_path: &Path,
-    width: u32,
-    height: u32,
+    size: (u32, u32),
+    with_legend: bool,
 ) -> Result<()> {
-    let backend = SVGBackend::new(output_path, (width, height)).into_drawing_area();
-    render_chart_impl(df, config, backend)
+    {
+        let backend = SVGBackend::new(output_path, size).into_drawing_area();
+        render_chart_impl(df, config, backend, with_legend, canvas_fill(config)?)?;
+    }
+
+    // The drawing keeps its canvas-sized viewBox, so a larger displayed size scales all of it
+    let output = chart_dimensions(config);
+    if output != size {
+        svg::resize_svg_file(output_path, output)?;
+    }
+    Ok(())
+}
+
+/// The SVG drawing inlined in an HTML page, followed by a table of the plotted data
+fn render_to_html(
+    df: &DataFrame,
+    config: &ChartConfig,
+    output_path: &Path,
+    size: (u32, u32),
+    with_legend: bool,
+) -> Result<()> {
+    let mut drawing = String::new();
+    {
+        let backend = SVGBackend::with_string(&mut drawing, size).into_drawing_area();
+        render_chart_impl(df, config, backend, with_legend, canvas_fill(config)?)?;
+    }
+
+    let output = chart_dimensions(config);
+    if output != size {
+        drawing = svg::resize_svg(&drawing, output);
+    }
+    let page = html::html_page(&drawing, &data::chart_data(df, config)?);
+    std::fs::write(output_path, page)
+        .with_context(|| format!("Failed to write HTML file: {}", output_path.display()))
+}
+
+fn render_to_pdf(
+    df: &DataFrame,
+    config: &ChartConfig,
+    output_path: &Path,
+    size: (u32, u32),
+    with_legend: bool,
+) -> Result<()> {
+    let backend = pdf::PdfBackend::new(output_path, size)
+        .with_page_size(chart_dimensions(config))
+        .into_drawing_area();
+    render_chart_impl(
+        df,
+        config,
+        backend.clone(),
+        with_legend,
+        canvas_fill(config)?,
+    )?;
+    backend.present().context("Failed to write PDF")
+}
+
+/// Canvas color: the chart's `background`, else the theme's; None when it is transparent
+fn canvas_fill(config: &ChartConfig) -> Result<Option<RGBColor>> {
+    let theme = config.theme.as_ref().unwrap_or(&crate::spec::Theme::Light);
+    let style = crate::render::styling::get_chart_style_with_theme(theme);
+
+    let background = config
+        .background
+        .as_deref()
+        .map(crate::spec::Background::parse)
+        .transpose()?;
+    Ok(match background {
+        Some(crate::spec::Background::Color(r, g, b)) => Some(RGBColor(r, g, b)),
+        Some(crate::spec::Background::Transparent) => None,
+        None => Some(style.colors.background.canvas),
+    })
+}
+
+/// Legend color: a chart `background` covers the legend too, otherwise the theme's chart color
+fn legend_fill(config: &ChartConfig, canvas: Option<RGBColor>) -> Option<RGBColor> {
+    if config.background.is_some() {
+        return canvas;
+    }
+    let theme = config.theme.as_ref().unwrap_or(&crate::spec::Theme::Light);
+    Some(
+        crate::render::styling::get_chart_style_with_theme(theme)
+            .colors
+            .background
+            .chart,
+    )
+}
+
+/// Draw an RGBA image whose background is transparent
+///
+/// The bitmap backend has no alpha channel, so `draw` runs twice, over black and over white:
+/// a pixel's alpha follows from how much the two passes differ, and its color from the black pass.
+/// Each pass is drawn `factor` times larger and resampled to `output`, as for `quality: high`.
+fn render_transparent<F>(
+    size: (u32, u32),
+    factor: u32,
+    output: (u32, u32),
+    draw: F,
+) -> Result<image::RgbaImage>
+where
+    F: Fn(
+        DrawingArea<quality::Supersampled<BitMapBackend<'_>>, plotters::coord::Shift>,
+        RGBColor,
+    ) -> Result<()>,
+{
+    let (width, height) = size;
+    let large = (width * factor, height * factor);
+    let mut passes = Vec::new();
+    for fill in [BLACK, WHITE] {
+        let mut buffer = vec![0u8; large.0 as usize * large.1 as usize * 3];
+        let backend = BitMapBackend::with_buffer(&mut buffer, large);
+        draw(
+            quality::Supersampled::new(backend, factor).into_drawing_area(),
+            fill,
+        )?;
+        passes.push(quality::downsample(buffer, size, factor, output)?.into_raw());
+    }
+    let (over_black, over_white) = (&passes[0], &passes[1]);
+
+    let mut image = image::RgbaImage::new(output.0, output.1);
+    let passes = over_black.chunks_exact(3).zip(over_white.chunks_exact(3));
+    for (pixel, (black, white)) in image.pixels_mut().zip(passes) {
+        // Over black a pixel is color * alpha; over white it gains 255 * (1 - alpha)
+        let spread = (0..3)
+            .map(|c| white[c].saturating_sub(black[c]) as u32)
+            .sum::<u32>()
+            / 3;
+        let alpha = 255 - spread.min(255);
+        let unpremultiply = |channel: u8| match alpha {
+            0 => 0,
+            alpha => (channel as u32 * 255 / alpha).min(255) as u8,
+        };
+        *pixel = image::Rgba([
+            unpremultiply(black[0]),
+            unpremultiply(black[1]),
+            unpremultiply(black[2]),
+            alpha as u8,
+        ]);
+    }
+
+    Ok(image)
+}
+
+/// Render only the legend onto a canvas sized to fit its items; SVG when the
+/// path ends in `.svg`, otherwise PNG
+fn render_legend_file(df: &DataFrame, config: &ChartConfig, legend_path: &Path) -> Result<()> {
+    let items = get_legend_items(df, config)?;
+    let size = legend_size(&items, legend_column_count(config, items.len()));
+    let legend_position = config
+        .legend_position
+        .as_ref()
+        .unwrap_or(&crate::spec::LegendPosition::Right);
+
+    let is_svg = legend_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    let fill = legend_fill(config, canvas_fill(config)?);
+    if is_svg {
+        let area = SVGBackend::new(legend_path, size).into_drawing_area();
+        render_external_legend(df, config, area.clone(), legend_position, fill)?;
+        area.present().context("Failed to present legend")?;
+    } else if fill.is_none() {
+        render_transparent(size, 1, size, |area, fill| {
+            render_external_legend(df, config, area.clone(), legend_position, Some(fill))?;
+            area.present().context("Failed to present legend")
+        })?
+        .save_with_format(legend_path, image::ImageFormat::Png)
+        .with_context(|| format!("Failed to write image: {}", legend_path.display()))?;
+    } else {
+        let area = BitMapBackend::new(legend_path, size).into_drawing_area();
+        render_external_legend(df, config, area.clone(), legend_position, fill)?;
+        area.present().context("Failed to present legend")?;
+    }
+
+    Ok(())
+}
+
+/// Canvas size that fits every legend item without truncation, matching the
+/// layout used by `render_external_legend`
+fn legend_size(items: &[String], columns: usize) -> (u32, u32) {
+    let longest = items.iter().map(|item| item.len()).max().unwrap_or(0) as u32;
+    let columns = columns.clamp(1, items.len().max(1));
+    let rows = items.len().div_ceil(columns) as u32;
+    let width = (60 + longest * 8).max(120) * columns as u32;
+    let height = (30 + rows * 35).max(60);
+    (width, height)
+}
+
+/// Columns the legend is laid out in: `legend_columns`, but never more than there are items
+fn legend_column_count(config: &ChartConfig, items: usize) -> usize {
+    config.legend_columns.unwrap_or(1).clamp(1, items.max(1))
+}
+
+/// Top-left corner of each legend item's symbol, filling `columns` columns of equal
+/// width across the legend row by row
+fn legend_item_positions(count: usize, columns: usize, legend_width: u32) -> Vec<(i32, i32)> {
+    let columns = columns.max(1);
+    let column_width = legend_width / columns as u32;
+    (0..count)
+        .map(|index| {
+            let (row, column) = (index / columns, index % columns);
+            let x = 15 + (column as u32 * column_width) as i32;
+            let y = 30 + row as i32 * 35; // Start 30 pixels from top for better spacing
+            (x, y)
+        })
+        .collect()
 }
 
 fn render_chart_impl<DB: DrawingBackend>(
     df: &DataFrame,
     config: &ChartConfig,
     root: DrawingArea<DB, plotters::coord::Shift>,
+    with_legend: bool,
+    canvas: Option<RGBColor>,
 ) -> Result<()>
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
@@ -65,13 +396,21 @@ where
     let theme = config.theme.as_ref().unwrap_or(&crate::spec::Theme::Light);
     let style = crate::render::styling::get_chart_style_with_theme(theme);
 
-    // Fill with theme-appropriate background
-    root.fill(&style.colors.background.canvas)
-        .context("Failed to fill background")?;
+    // Fill with the background color; a transparent canvas is left unfilled
+    if let Some(canvas) = &canvas {
+        root.fill(canvas).context("Failed to fill background")?;
+    }
 
     // Get the title
     let title = config.title.as_deref().unwrap_or("Chart");
 
+    // Filters can remove every row; say so rather than leaving a blank canvas
+    if df.height() == 0 {
+        draw_no_data(&root, title, &style)?;
+        root.present().context("Failed to present chart")?;
+        return Ok(());
+    }
+
     // Get legend position (default to Right if not specified)
     let legend_position = config
         .legend_position
@@ -79,7 +418,13 @@ where
         .unwrap_or(&crate::spec::LegendPosition::Right);
 
     // Split the drawing area based on legend position
-    let (chart_area, legend_area) = split_drawing_area(&root, legend_position)?;
+    let (chart_area, legend_area) = if with_legend {
+        let (chart_area, legend_area) =
+            split_drawing_area(&root, legend_position, config.legend_ratio)?;
+        (chart_area, Some(legend_area))
+    } else {
+        (root.clone(), None)
+    };
 
     // Render the chart in the chart area
     match config.chart_type {
@@ -105,17 +450,70 @@ where
         ChartType::Retention => {
             crate::chart::retention::render(df, config, chart_area, title, legend_position)
         }
+        ChartType::Pie => crate::chart::pie::render(df, config, chart_area, title, legend_position),
+        ChartType::Box => {
+            crate::chart::boxplot::render(df, config, chart_area, title, legend_position)
+        }
     }?;
 
     // Render the legend in the legend area
-    render_external_legend(df, config, legend_area, legend_position)?;
+    if let Some(legend_area) = legend_area {
+        render_external_legend(
+            df,
+            config,
+            legend_area,
+            legend_position,
+            legend_fill(config, canvas),
+        )?;
+    }
+
+    // Composite the logo over the finished chart
+    if let Some(logo_path) = &config.logo {
+        let position = config.logo_position.clone().unwrap_or_default();
+        let background = canvas.unwrap_or(style.colors.background.canvas);
+        logo::draw_logo(&root, logo_path, &position, &background)?;
+        root.present().context("Failed to present chart")?;
+    }
 
     Ok(())
 }
 
+/// Title plus a centered "No data to display" message, shared by every chart type
+fn draw_no_data<DB: DrawingBackend>(
+    root: &DrawingArea<DB, plotters::coord::Shift>,
+    title: &str,
+    style: &crate::render::styling::ChartStyle,
+) -> Result<()>
+where
+    DB::ErrorType: 'static + std::error::Error + Send + Sync,
+{
+    let (width, height) = root.dim_in_pixel();
+    let center_x = (width / 2) as i32;
+
+    root.draw_text(
+        title,
+        &style.title_font().pos(Pos::new(HPos::Center, VPos::Top)),
+        (center_x, style.layout.margins.chart as i32),
+    )
+    .context("Failed to draw title")?;
+    root.draw_text(
+        NO_DATA_MESSAGE,
+        &style
+            .axis_desc_font()
+            .pos(Pos::new(HPos::Center, VPos::Center)),
+        (center_x, (height / 2) as i32),
+    )
+    .context("Failed to draw no data message")?;
+
+    Ok(())
+}
+
+/// Split off the legend's share of the canvas; `legend_ratio` overrides the default
+/// 25% of the width (left/right) or 15% of the height (top/bottom)
 fn split_drawing_area<DB: DrawingBackend>(
     root: &DrawingArea<DB, plotters::coord::Shift>,
     legend_position: &crate::spec::LegendPosition,
+    legend_ratio: Option<f64>,
 ) -> Result<(
     DrawingArea<DB, plotters::coord::Shift>,
     DrawingArea<DB, plotters::coord::Shift>,
@@ -128,11 +526,11 @@ where
     // Adjust legend space based on position - horizontal legends need more width
     let legend_width = match legend_position {
         crate::spec::LegendPosition::Right | crate::spec::LegendPosition::Left => {
-            (width as f32 * 0.25) as u32 // 25% for horizontal legends
+            (width as f32 * legend_ratio.unwrap_or(0.25) as f32) as u32 // 25% by default for horizontal legends
         }
         _ => (width as f32 * 0.15) as u32, // 15% for vertical legends
     };
-    let legend_height = (height as f32 * 0.15) as u32; // 15% of height for legend
+    let legend_height = (height as f32 * legend_ratio.unwrap_or(0.15) as f32) as u32; // 15% of height by default
 
     match legend_position {
         crate::spec::LegendPosition::Right => {
@@ -159,101 +557,142 @@ fn render_external_legend<DB: DrawingBackend>(
     config: &ChartConfig,
     legend_area: DrawingArea<DB, plotters::coord::Shift>,
     _legend_position: &crate::spec::LegendPosition,
+    fill: Option<RGBColor>,
 ) -> Result<()>
 where
     DB::ErrorType: 'static + std::error::Error + Send + Sync,
 {
-    // Get theme from config or default to light
-    let theme = config.theme.as_ref().unwrap_or(&crate::spec::Theme::Light);
-    let style = crate::render::styling::get_chart_style_with_theme(theme);
-
-    // Fill legend area with theme-appropriate background
-    legend_area
-        .fill(&style.colors.background.chart)
-        .context("Failed to fill legend background")?;
+    // Fill the legend area unless the background is transparent
+    if let Some(fill) = &fill {
+        legend_area
+            .fill(fill)
+            .context("Failed to fill legend background")?;
+    }
 
     // Get legend items based on chart type
-    let legend_items = get_legend_items(df, config)?;
+    let legend_items = legend_entries(df, config)?;
 
     // Get legend area dimensions for better text handling
     let (legend_width, _legend_height) = legend_area.dim_in_pixel();
 
-    // Render legend items
-    let style = crate::render::styling::get_chart_style();
-    let mut y_offset = 30; // Start 30 pixels from top for better spacing
+    // Render legend items, each column getting an equal share of the width
+    let style = crate::render::styling::get_chart_style_for(config);
+    let columns = legend_column_count(config, legend_items.len());
+    let column_width = legend_width / columns as u32;
+    let positions = legend_item_positions(legend_items.len(), columns, legend_width);
 
-    for (index, item) in legend_items.iter().enumerate() {
-        let color = style.get_primary_color(index);
+    let color_mode = config.color_mode.clone().unwrap_or_default();
+    for ((slot, item), &(x, y)) in legend_items.iter().zip(&positions) {
+        let color = style.get_group_color(*slot, item, &color_mode);
 
         // Draw legend symbol
         legend_area
-            .draw(&Rectangle::new(
-                [(15, y_offset), (35, y_offset + 15)],
-                color.filled(),
-            ))
+            .draw(&Rectangle::new([(x, y), (x + 20, y + 15)], color.filled()))
             .context("Failed to draw legend symbol")?;
 
-        // Calculate available text width (legend width minus symbol and padding)
-        let available_width = legend_width.saturating_sub(60); // 60px for symbol + padding
-        let max_chars = (available_width as f32 / 8.0) as usize; // Approximate chars per pixel
-
-        // Truncate text based on available space
-        let display_text = if item.len() > max_chars && max_chars > 10 {
-            format!("{}...", &item[..max_chars.saturating_sub(3)])
-        } else {
-            item.clone()
-        };
+        let display_text = legend_display_text(item, column_width);
 
         // Draw legend text with better positioning
         legend_area
             .draw(&Text::new(
                 display_text.as_str(),
-                (45, y_offset + 12),
+                (x + 30, y + 12),
                 style.axis_label_font(),
             ))
             .context("Failed to draw legend text")?;
-
-        y_offset += 35; // Increase spacing between legend items
     }
 
     Ok(())
 }
 
+/// A legend label cut to fit beside its symbol in a legend `legend_width` pixels wide
+fn legend_display_text(item: &str, legend_width: u32) -> String {
+    // Available text width is the legend width minus symbol and padding (60px)
+    let available_width = legend_width.saturating_sub(60);
+    let max_chars = (available_width as f32 / 8.0) as usize; // Approximate chars per pixel
+
+    // Very narrow legends show the whole label rather than a stub
+    if max_chars > 10 {
+        crate::chart::bar::truncate_label(item, max_chars)
+    } else {
+        item.to_string()
+    }
+}
+
 fn get_legend_items(df: &DataFrame, config: &ChartConfig) -> Result<Vec<String>> {
+    Ok(legend_entries(df, config)?
+        .into_iter()
+        .map(|(_, item)| item)
+        .collect())
+}
+
+/// Legend items with the palette slot of each; series in `hide_series` are left out, and the
+/// rest keep the slot they have when nothing is hidden
+fn legend_entries(df: &DataFrame, config: &ChartConfig) -> Result<Vec<(usize, String)>> {
+    let series_items = config.group_by.is_some() || config.ys.is_some();
+    Ok(all_legend_items(df, config)?
+        .into_iter()
+        .enumerate()
+        .filter(|(_, item)| !series_items || config.shows_series(item))
+        .collect())
+}
+
+fn all_legend_items(df: &DataFrame, config: &ChartConfig) -> Result<Vec<String>> {
     let mut items = Vec::new();
 
+    // Grouped charts list their groups sorted, which is also the order their colors follow;
+    // `rows` is how many rows the chart draws
+    let groups = |rows: usize| -> Result<Option<Vec<String>>> {
+        match &config.group_by {
+            Some(group_by) if df.column(group_by).is_ok() => Ok(Some(
+                crate::chart::line::group_names(&df.head(Some(rows)), group_by)?,
+            )),
+            _ => Ok(None),
+        }
+    };
+
     match config.chart_type {
         ChartType::Line => {
-            if let Some(y) = &config.y {
+            if let Some(ys) = &config.ys {
+                items.extend(ys.iter().cloned());
+            } else if let Some(groups) = groups(df.height())? {
+                items.extend(groups);
+            } else if let Some(y) = &config.y {
                 items.push(y.clone());
+                if let Some(band) = &config.rolling_band {
+                    items.push(format!(
+                        "{}-point mean ± {}σ",
+                        band.window,
+                        band.k.unwrap_or(2.0)
+                    ));
+                }
             }
         }
         ChartType::Area => {
-            if let Some(y) = &config.y {
+            if let Some(ys) = &config.ys {
+                items.extend(ys.iter().cloned());
+            } else if let Some(groups) = groups(100)? {
+                items.extend(groups);
+            } else if let Some(y) = &config.y {
                 items.push(y.clone());
             }
         }
         ChartType::Bar => {
-            if let Some(y) = &config.y {
+            if let Some(series) = &config.series {
+                items.extend(series.iter().map(|s| s.output_column()));
+            } else if let Some(groups) = groups(20)? {
+                items.extend(groups);
+            } else if let Some(y) = &config.y {
                 items.push(y.clone());
             }
         }
         ChartType::BarStacked => {
-            if let Some(group_by) = &config.group_by {
-                // Get unique values from group_by column
-                if let Ok(group_col) = df.column(group_by) {
-                    let mut unique_groups = std::collections::HashSet::new();
-                    for i in 0..df.height().min(50) {
-                        if let Ok(val) = group_col.get(i) {
-                            unique_groups.insert(format!("{:?}", val));
-                        }
-                    }
-                    items.extend(unique_groups.into_iter().collect::<Vec<_>>());
-                }
-            }
+            items.extend(groups(50)?.unwrap_or_default());
         }
         ChartType::Scatter => {
-            if let (Some(x), Some(y)) = (&config.x, &config.y) {
+            if let Some(groups) = groups(1000)? {
+                items.extend(groups);
+            } else if let (Some(x), Some(y)) = (&config.x, &config.y) {
                 items.push(format!("{} vs {}", y, x));
             }
         }
@@ -278,11 +717,33 @@ fn get_legend_items(df: &DataFrame, config: &ChartConfig) -> Result<Vec<String>>
             }
         }
         ChartType::Retention => {
-            items.push("Retention %".to_string());
+            // Survival curves are one line per cohort
+            if config.survival.unwrap_or(false) {
+                if let Some(table) = crate::chart::retention::retention_table(df, config)? {
+                    items.extend(table.cohorts);
+                }
+            } else {
+                items.push("Retention %".to_string());
+            }
         }
-        _ => {
-            if let Some(y) = &config.y {
-                items.push(y.clone());
+        ChartType::Pie => {
+            // One swatch per wedge, in drawing order
+            let pie = crate::chart::pie::pie_slices(df, config)?;
+            items.extend(pie.slices.into_iter().map(|(label, _)| label));
+        }
+        ChartType::Box => {
+            items.extend(groups(df.height())?.unwrap_or_default());
+        }
+        ChartType::Heatmap => {
+            // Discrete z gets a swatch per category; continuous z keeps the y label
+            let categories = config
+                .z
+                .as_ref()
+                .and_then(|z| df.column(z).ok())
+                .and_then(crate::chart::heatmap::z_categories);
+            match categories {
+                Some(categories) => items.extend(categories),
+                None => items.extend(config.y.clone()),
             }
         }
     }
@@ -290,7 +751,6 @@ fn get_legend_items(df: &DataFrame, config: &ChartConfig) -> Result<Vec<String>>
     Ok(items)
 }
 
-#[allow(dead_code)]
 pub fn generate_output_filename(
     config: &ChartConfig,
     output_dir: &Path,
@@ -305,12 +765,14 @@ pub fn generate_output_filename(
         ChartType::Scatter => "Scatter",
         ChartType::Funnel => "Funnel",
         ChartType::Retention => "Retention",
+        ChartType::Pie => "Pie",
+        ChartType::Box => "Box",
     };
-    let format = match config.format.as_ref().unwrap_or(&OutputFormat::Png) {
-        OutputFormat::Png => "png",
-        OutputFormat::Svg => "svg",
-        OutputFormat::Pdf => "pdf",
-    };
+    let format = config
+        .format
+        .as_ref()
+        .unwrap_or(&OutputFormat::Png)
+        .extension();
 
     // Sanitize the title for filename
     let safe_title = title
@@ -328,3 +790,737 @@ pub fn generate_output_filename(
     let filename = format!("{}-{}.{}", safe_title, chart_type, format);
     Ok(output_dir.join(filename))
 }
+
+/// Generate an output filename that doesn't collide with any path in `taken`,
+/// appending `-2`, `-3`, ... to the file stem when needed
+pub fn generate_unique_output_filename(
+    config: &ChartConfig,
+    output_dir: &Path,
+    taken: &HashSet<PathBuf>,
+) -> Result<PathBuf> {
+    let path = generate_output_filename(config, output_dir)?;
+    Ok(unique_output_path(&path, taken))
+}
+
+/// Return `path`, or the first `<stem>-N.<ext>` variant (N >= 2) not in `taken`
+///
+/// Only names claimed earlier in the same batch count; files left by an earlier run are
+/// overwritten, so rerunning a spec updates its outputs in place.
+pub fn unique_output_path(path: &Path, taken: &HashSet<PathBuf>) -> PathBuf {
+    if !taken.contains(path) {
+        return path.to_path_buf();
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().into_owned());
+
+    (2..)
+        .map(|n| {
+            let filename = match &extension {
+                Some(ext) => format!("{}-{}.{}", stem, n, ext),
+                None => format!("{}-{}", stem, n),
+            };
+            path.with_file_name(filename)
+        })
+        .find(|candidate| !taken.contains(candidate))
+        .expect("unbounded suffix search always finds a free name")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_output_filename() {
+        let config = ChartConfig {
+            chart_type: ChartType::Bar,
+            title: Some("Users by Channel".to_string()),
+            ..Default::default()
+        };
+
+        let path = generate_output_filename(&config, Path::new("out")).unwrap();
+        assert_eq!(path, Path::new("out/users-by-channel-Bar.png"));
+    }
+
+    #[test]
+    fn test_logo_drawn_in_corner() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let logo_path = dir.path().join("logo.png");
+        let logo_color = image::Rgba([200, 30, 60, 255]);
+        image::RgbaImage::from_pixel(12, 8, logo_color)
+            .save(&logo_path)
+            .unwrap();
+
+        let df = df! {
+            "channel" => &["organic", "direct", "email"],
+            "users" => &[120, 80, 45],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Bar,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            width: Some(400),
+            height: Some(300),
+            logo: Some(logo_path),
+            logo_position: Some(crate::spec::LogoPosition::BottomRight),
+            ..Default::default()
+        };
+
+        let output_path = dir.path().join("chart.png");
+        render_chart(df.lazy(), &config, &output_path, None).unwrap();
+
+        let output = image::open(&output_path).unwrap().to_rgb8();
+        let (x, y) = logo::logo_origin(
+            output.dimensions(),
+            (12, 8),
+            &crate::spec::LogoPosition::BottomRight,
+        );
+        for (dx, dy) in [(0, 0), (11, 0), (0, 7), (11, 7)] {
+            let pixel = output.get_pixel((x + dx) as u32, (y + dy) as u32);
+            assert_eq!(pixel.0, [200, 30, 60]);
+        }
+    }
+
+    #[test]
+    fn test_background_color_and_transparency() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "channel" => &["organic", "direct", "email"],
+            "users" => &[120, 80, 45],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Bar,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            width: Some(400),
+            height: Some(300),
+            background: Some("#1e3a5f".to_string()),
+            ..Default::default()
+        };
+
+        let colored_path = dir.path().join("colored.png");
+        render_chart(df.clone().lazy(), &config, &colored_path, None).unwrap();
+        let colored = image::open(&colored_path).unwrap().to_rgb8();
+        assert_eq!(colored.get_pixel(0, 0).0, [0x1e, 0x3a, 0x5f]);
+        // The legend shares the background
+        assert_eq!(colored.get_pixel(399, 299).0, [0x1e, 0x3a, 0x5f]);
+
+        let transparent_config = ChartConfig {
+            background: Some("transparent".to_string()),
+            ..config
+        };
+        let transparent_path = dir.path().join("transparent.png");
+        render_chart(df.lazy(), &transparent_config, &transparent_path, None).unwrap();
+        let transparent = image::open(&transparent_path).unwrap();
+        assert!(transparent.color().has_alpha());
+        let transparent = transparent.to_rgba8();
+        assert_eq!(transparent.get_pixel(0, 0)[3], 0);
+        // Drawn content stays opaque
+        assert!(transparent.pixels().any(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn test_high_quality_smooths_edges() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "day" => &[1, 2, 3, 4],
+            "users" => &[120, 80, 160, 45],
+        }
+        .unwrap();
+        let render = |quality: crate::spec::Quality| {
+            let config = ChartConfig {
+                chart_type: ChartType::Line,
+                x: Some("day".to_string()),
+                y: Some("users".to_string()),
+                width: Some(400),
+                height: Some(300),
+                quality: Some(quality),
+                ..Default::default()
+            };
+            let path = dir.path().join(format!("{:?}.png", config.quality));
+            render_chart(df.clone().lazy(), &config, &path, None).unwrap();
+            image::open(&path).unwrap().to_rgb8()
+        };
+
+        let fast = render(crate::spec::Quality::Fast);
+        let high = render(crate::spec::Quality::High);
+        assert_eq!(fast.dimensions(), high.dimensions());
+        assert_ne!(fast.as_raw(), high.as_raw());
+
+        // Blended edges give the supersampled chart more distinct colors
+        let distinct = |image: &image::RgbImage| {
+            image
+                .pixels()
+                .map(|pixel| pixel.0)
+                .collect::<HashSet<_>>()
+                .len()
+        };
+        assert!(distinct(&high) > distinct(&fast));
+    }
+
+    #[test]
+    fn test_legend_truncates_multibyte_labels_by_character() {
+        let label = "日本語チャンネル".repeat(5);
+        // 12 characters fit in a 160px legend
+        let shown = legend_display_text(&label, 160);
+        assert_eq!(shown.chars().count(), 12);
+        assert!(shown.starts_with("日本語チャンネル日本語"));
+        assert!(shown.ends_with('…'));
+        assert_eq!(legend_display_text("café", 160), "café");
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "day" => &["mon", "mon", "tue", "tue"],
+            "channel" => &[label.as_str(), "café", label.as_str(), "café"],
+            "users" => &[120, 80, 90, 60],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::BarStacked,
+            x: Some("day".to_string()),
+            y: Some("users".to_string()),
+            group_by: Some("channel".to_string()),
+            ..Default::default()
+        };
+        let path = dir.path().join("legend.png");
+        render_chart(df.lazy(), &config, &path, None).unwrap();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_legend_columns_wrap_items_into_rows() {
+        let positions = legend_item_positions(12, 3, 600);
+
+        let mut xs: Vec<i32> = positions.iter().map(|&(x, _)| x).collect();
+        xs.sort();
+        xs.dedup();
+        assert_eq!(xs, vec![15, 215, 415]);
+        // Items fill each row left to right before wrapping, giving four rows
+        assert_eq!(positions[0].1, positions[2].1);
+        assert_eq!(positions[3], (15, positions[0].1 + 35));
+        assert_eq!(positions.iter().filter(|&&(x, _)| x == 215).count(), 4);
+
+        let items: Vec<String> = (1..=12).map(|i| format!("Series {}", i)).collect();
+        let (width, height) = legend_size(&items, 3);
+        assert_eq!(height, 30 + 4 * 35);
+        assert_eq!(width, 3 * legend_size(&items, 1).0);
+    }
+
+    #[test]
+    fn test_legend_columns_are_capped_at_the_item_count() {
+        let config = ChartConfig {
+            legend_columns: Some(5),
+            ..Default::default()
+        };
+        // Two items share the width between them rather than a fifth each
+        assert_eq!(legend_column_count(&config, 2), 2);
+        assert_eq!(legend_item_positions(2, 2, 600), vec![(15, 30), (315, 30)]);
+        assert_eq!(legend_column_count(&config, 12), 5);
+        assert_eq!(legend_column_count(&ChartConfig::default(), 0), 1);
+    }
+
+    #[test]
+    fn test_area_ys_stacks_a_band_per_column() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "date" => &["2023-01-01", "2023-01-02", "2023-01-03"],
+            "organic" => &[100, 120, 90],
+            "paid" => &[40, 60, 55],
+            "referral" => &[10, 15, 20],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Area,
+            x: Some("date".to_string()),
+            ys: Some(vec![
+                "organic".to_string(),
+                "paid".to_string(),
+                "referral".to_string(),
+            ]),
+            format: Some(OutputFormat::Svg),
+            ..Default::default()
+        };
+        config.validate().unwrap();
+
+        let output_path = dir.path().join("area.svg");
+        render_chart(df.lazy(), &config, &output_path, None).unwrap();
+        let svg = std::fs::read_to_string(&output_path).unwrap();
+
+        assert_eq!(svg.matches("<polygon").count(), 3);
+        for column in ["organic", "paid", "referral"] {
+            assert!(
+                svg.contains(&format!(">{}<", column)),
+                "missing legend entry {}",
+                column
+            );
+        }
+    }
+
+    #[test]
+    fn test_groups_keep_their_sorted_color_in_every_chart_type() {
+        let dir = tempfile::TempDir::new().unwrap();
+        // "organic" is seen first, but sorts after "direct"
+        let df = df! {
+            "day" => &[1i64, 1, 2, 2, 3, 3],
+            "channel" => &["organic", "direct", "organic", "direct", "organic", "direct"],
+            "users" => &[100, 40, 120, 60, 90, 80],
+        }
+        .unwrap();
+        let grouped = |chart_type: ChartType| ChartConfig {
+            chart_type,
+            x: Some("day".to_string()),
+            y: Some("users".to_string()),
+            group_by: Some("channel".to_string()),
+            format: Some(OutputFormat::Svg),
+            ..Default::default()
+        };
+        // Stacks draw "organic" first here, so draw order and sorted order disagree
+        let stacked = |chart_type: ChartType| ChartConfig {
+            stack_order: Some(vec!["organic".to_string()]),
+            ..grouped(chart_type)
+        };
+        let charts = [
+            ("line", "<polyline", grouped(ChartType::Line), 0),
+            (
+                "area",
+                "<polygon",
+                ChartConfig {
+                    stacked: Some(false),
+                    ..grouped(ChartType::Area)
+                },
+                0,
+            ),
+            ("stacked-area", "<polygon", stacked(ChartType::Area), 1),
+            ("bar-stacked", "<rect", stacked(ChartType::BarStacked), 1),
+            ("bar", "<rect", grouped(ChartType::Bar), 1),
+            ("scatter", "<circle", grouped(ChartType::Scatter), 0),
+        ];
+
+        let style = crate::render::styling::get_chart_style();
+        let hex = |index: usize| {
+            let color = style.get_primary_color(index);
+            format!("#{:02X}{:02X}{:02X}", color.0, color.1, color.2)
+        };
+        for (name, tag, config, first_drawn) in charts {
+            let output_path = dir.path().join(format!("{}.svg", name));
+            render_chart(df.clone().lazy(), &config, &output_path, None).unwrap();
+            let svg = std::fs::read_to_string(&output_path).unwrap();
+
+            // The first group drawn has the color of its place in sorted order
+            let first = svg
+                .split(tag)
+                .skip(1)
+                .map(|element| element.split("/>").next().unwrap())
+                .find_map(|element| (0..2).find(|&index| element.contains(&hex(index))));
+            assert_eq!(first, Some(first_drawn), "{} colors", name);
+            assert_eq!(
+                get_legend_items(&df, &config).unwrap(),
+                vec!["direct", "organic"],
+                "{} legend",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_hidden_series_leave_the_plot_and_legend() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "day" => &[1i64, 1, 2, 2, 3, 3],
+            "channel" => &["organic", "direct", "organic", "direct", "organic", "direct"],
+            "users" => &[100, 40, 120, 60, 90, 80],
+        }
+        .unwrap();
+        let style = crate::render::styling::get_chart_style();
+        let hex = |index: usize| {
+            let color = style.get_primary_color(index);
+            format!("#{:02X}{:02X}{:02X}", color.0, color.1, color.2)
+        };
+
+        for (name, chart_type, tag) in [
+            ("line", ChartType::Line, "<polyline"),
+            ("scatter", ChartType::Scatter, "<circle"),
+        ] {
+            let config = ChartConfig {
+                chart_type,
+                x: Some("day".to_string()),
+                y: Some("users".to_string()),
+                group_by: Some("channel".to_string()),
+                hide_series: Some(vec!["direct".to_string()]),
+                format: Some(OutputFormat::Svg),
+                ..Default::default()
+            };
+            let output_path = dir.path().join(format!("{}.svg", name));
+            render_chart(df.clone().lazy(), &config, &output_path, None).unwrap();
+            let svg = std::fs::read_to_string(&output_path).unwrap();
+
+            // "direct" sorts first and has the first color; "organic" keeps the second
+            let marks: Vec<&str> = svg
+                .split(tag)
+                .skip(1)
+                .map(|element| element.split("/>").next().unwrap())
+                .collect();
+            assert!(!marks.iter().any(|mark| mark.contains(&hex(0))), "{}", name);
+            assert!(marks.iter().any(|mark| mark.contains(&hex(1))), "{}", name);
+            assert!(
+                !svg.contains(">direct<") && svg.contains(">organic<"),
+                "{}",
+                name
+            );
+            assert_eq!(get_legend_items(&df, &config).unwrap(), vec!["organic"]);
+        }
+    }
+
+    #[test]
+    fn test_line_ys_draw_a_series_per_column() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "day" => &[1i64, 2, 3],
+            "sessions" => &[180, 210, 160],
+            "users" => &[100, 120, 90],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Line,
+            x: Some("day".to_string()),
+            ys: Some(vec!["sessions".to_string(), "users".to_string()]),
+            format: Some(OutputFormat::Svg),
+            ..Default::default()
+        };
+        config.validate().unwrap();
+        let output_path = dir.path().join("line.svg");
+        render_chart(df.clone().lazy(), &config, &output_path, None).unwrap();
+        let svg = std::fs::read_to_string(&output_path).unwrap();
+
+        // One line per column, colored in listed order
+        let style = crate::render::styling::get_chart_style();
+        let lines: Vec<&str> = svg
+            .split("<polyline")
+            .skip(1)
+            .map(|element| element.split("/>").next().unwrap())
+            .collect();
+        for index in 0..2 {
+            let color = style.get_primary_color(index);
+            let hex = format!("#{:02X}{:02X}{:02X}", color.0, color.1, color.2);
+            assert_eq!(
+                lines.iter().filter(|line| line.contains(&hex)).count(),
+                1,
+                "series {}",
+                index
+            );
+        }
+        assert_eq!(
+            get_legend_items(&df, &config).unwrap(),
+            vec!["sessions", "users"]
+        );
+    }
+
+    #[test]
+    fn test_smaller_legend_ratio_widens_plot() {
+        let mut svg = String::new();
+        let root = SVGBackend::with_string(&mut svg, (1000, 600)).into_drawing_area();
+        let right = crate::spec::LegendPosition::Right;
+
+        let (default_chart, _) = split_drawing_area(&root, &right, None).unwrap();
+        let (narrow_chart, narrow_legend) = split_drawing_area(&root, &right, Some(0.1)).unwrap();
+        let (wide_chart, _) = split_drawing_area(&root, &right, Some(0.4)).unwrap();
+
+        assert_eq!(default_chart.dim_in_pixel().0, 750);
+        assert_eq!(narrow_chart.dim_in_pixel().0, 900);
+        assert_eq!(narrow_legend.dim_in_pixel().0, 100);
+        assert_eq!(wide_chart.dim_in_pixel().0, 600);
+
+        // Top and bottom legends take the ratio of the height instead
+        let top = crate::spec::LegendPosition::Top;
+        let (chart, _) = split_drawing_area(&root, &top, Some(0.1)).unwrap();
+        assert_eq!(chart.dim_in_pixel(), (1000, 540));
+    }
+
+    #[test]
+    fn test_funnel_without_dimensions_uses_funnel_default() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "value" => &[1000, 400, 100],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Funnel,
+            steps: Some(vec![
+                "Visit".to_string(),
+                "Signup".to_string(),
+                "Purchase".to_string(),
+            ]),
+            values: Some("value".to_string()),
+            ..Default::default()
+        };
+
+        let output_path = dir.path().join("funnel.png");
+        render_chart(df.clone().lazy(), &config, &output_path, None).unwrap();
+
+        let dimensions = image::image_dimensions(&output_path).unwrap();
+        assert_eq!(dimensions, default_dimensions(&ChartType::Funnel));
+        assert_ne!(dimensions, (1400, 800));
+
+        // Explicit dimensions still win
+        let config = ChartConfig {
+            width: Some(500),
+            height: Some(400),
+            ..config
+        };
+        render_chart(df.lazy(), &config, &output_path, None).unwrap();
+        assert_eq!(image::image_dimensions(&output_path).unwrap(), (500, 400));
+    }
+
+    #[test]
+    fn test_draw_factor_is_capped() {
+        let config = ChartConfig {
+            scale: Some(1.1),
+            quality: Some(crate::spec::Quality::High),
+            ..Default::default()
+        };
+        assert_eq!(draw_factor(&config, (400, 300)), 4);
+
+        // A large canvas is drawn no bigger than MAX_DRAW_SIDE, however high the quality
+        let config = ChartConfig {
+            scale: Some(10.0),
+            ..config
+        };
+        assert_eq!(draw_factor(&config, (4000, 300)), 2);
+        assert_eq!(draw_factor(&config, (10000, 300)), 1);
+    }
+
+    #[test]
+    fn test_spec_scale_doubles_the_output() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "channel" => &["organic", "direct"],
+            "users" => &[120, 80],
+        }
+        .unwrap();
+        let spec = crate::spec::ChartSpec::from_yaml(
+            r#"
+charts:
+  - type: bar
+    x: channel
+    y: users
+    width: 400
+    height: 300
+    scale: 2.0
+"#,
+        )
+        .unwrap();
+        let mut config = spec.charts[0].clone();
+        assert_eq!(chart_dimensions(&config), (800, 600));
+
+        let png_path = dir.path().join("chart.png");
+        render_chart(df.clone().lazy(), &config, &png_path, None).unwrap();
+        assert_eq!(image::image_dimensions(&png_path).unwrap(), (800, 600));
+
+        // Vector output keeps the 400x300 drawing and is displayed at twice its size
+        config.format = Some(OutputFormat::Svg);
+        let svg_path = dir.path().join("chart.svg");
+        render_chart(df.lazy(), &config, &svg_path, None).unwrap();
+        let svg = std::fs::read_to_string(&svg_path).unwrap();
+        assert!(svg.starts_with(r#"<svg width="800" height="600" viewBox="0 0 400 300""#));
+    }
+
+    #[test]
+    fn test_format_sizes_pick_the_size_for_each_format() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "channel" => &["organic", "direct"],
+            "users" => &[120, 80],
+        }
+        .unwrap();
+        let spec = crate::spec::ChartSpec::from_yaml(
+            r#"
+charts:
+  - type: bar
+    x: channel
+    y: users
+    width: 500
+    height: 400
+    format_sizes:
+      png: [1200, 900]
+      svg: [600, 300]
+"#,
+        )
+        .unwrap();
+        let mut config = spec.charts[0].clone();
+
+        let png_path = dir.path().join("chart.png");
+        render_chart(df.clone().lazy(), &config, &png_path, None).unwrap();
+        assert_eq!(image::image_dimensions(&png_path).unwrap(), (1200, 900));
+
+        config.format = Some(OutputFormat::Svg);
+        let svg_path = dir.path().join("chart.svg");
+        render_chart(df.clone().lazy(), &config, &svg_path, None).unwrap();
+        let svg = std::fs::read_to_string(&svg_path).unwrap();
+        assert!(svg.starts_with(r#"<svg width="600" height="300" viewBox="0 0 600 300""#));
+
+        // Formats without an entry fall back to width/height
+        config.format = Some(OutputFormat::Webp);
+        assert_eq!(chart_dimensions(&config), (500, 400));
+    }
+
+    #[test]
+    fn test_webp_output_is_a_riff_webp_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "channel" => &["organic", "direct"],
+            "users" => &[120, 80],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Bar,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            width: Some(400),
+            height: Some(300),
+            format: Some(OutputFormat::Webp),
+            ..Default::default()
+        };
+
+        let path = generate_output_filename(&config, dir.path()).unwrap();
+        assert_eq!(path.extension().unwrap(), "webp");
+        for (name, webp_quality) in [("lossy.webp", None), ("lossless.webp", Some(100))] {
+            let config = ChartConfig {
+                webp_quality,
+                ..config.clone()
+            };
+            let path = dir.path().join(name);
+            render_chart(df.clone().lazy(), &config, &path, None).unwrap();
+            let bytes = std::fs::read(&path).unwrap();
+            assert_eq!(&bytes[0..4], b"RIFF");
+            assert_eq!(&bytes[8..12], b"WEBP");
+        }
+    }
+
+    #[test]
+    fn test_unique_output_path_appends_suffix() {
+        let mut taken = HashSet::new();
+        let path = PathBuf::from("out/users-Line.png");
+
+        let first = unique_output_path(&path, &taken);
+        assert_eq!(first, path);
+        taken.insert(first);
+
+        let second = unique_output_path(&path, &taken);
+        assert_eq!(second, PathBuf::from("out/users-Line-2.png"));
+        taken.insert(second);
+
+        let third = unique_output_path(&path, &taken);
+        assert_eq!(third, PathBuf::from("out/users-Line-3.png"));
+    }
+
+    #[test]
+    fn test_unique_output_path_reuses_files_from_earlier_runs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("users-Line.png");
+        std::fs::write(&path, b"earlier run").unwrap();
+
+        assert_eq!(unique_output_path(&path, &HashSet::new()), path);
+    }
+
+    #[test]
+    fn test_empty_data_renders_placeholder() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "channel" => &["organic", "direct"],
+            "users" => &[120, 80],
+        }
+        .unwrap();
+        let filtered = df.lazy().filter(col("users").gt(lit(1000)));
+
+        let mut config = ChartConfig {
+            chart_type: ChartType::Bar,
+            title: Some("Users by Channel".to_string()),
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            width: Some(400),
+            height: Some(300),
+            format: Some(OutputFormat::Svg),
+            ..Default::default()
+        };
+
+        let svg_path = dir.path().join("chart.svg");
+        render_chart(filtered.clone(), &config, &svg_path, None).unwrap();
+        let svg = std::fs::read_to_string(&svg_path).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert!(svg.contains(NO_DATA_MESSAGE));
+
+        config.format = Some(OutputFormat::Png);
+        let png_path = dir.path().join("chart.png");
+        render_chart(filtered, &config, &png_path, None).unwrap();
+        let image = image::open(&png_path).unwrap();
+        assert_eq!((image.width(), image.height()), (400, 300));
+    }
+
+    #[test]
+    fn test_categorical_heatmap_legend_lists_categories() {
+        let df = df! {
+            "hour" => &["9", "10", "11"],
+            "day" => &["mon", "mon", "tue"],
+            "status" => &["ok", "warn", "error"],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Heatmap,
+            x: Some("hour".to_string()),
+            y: Some("day".to_string()),
+            z: Some("status".to_string()),
+            ..Default::default()
+        };
+
+        let items = get_legend_items(&df, &config).unwrap();
+        assert_eq!(items, vec!["ok", "warn", "error"]);
+    }
+
+    #[test]
+    fn test_pie_wedges_match_their_swatches_under_hash_colors() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let df = df! {
+            "channel" => &["organic", "direct", "paid"],
+            "users" => &[500i64, 300, 200],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Pie,
+            x: Some("channel".to_string()),
+            y: Some("users".to_string()),
+            color_mode: Some(crate::spec::ColorMode::Hash),
+            format: Some(OutputFormat::Svg),
+            ..Default::default()
+        };
+
+        let output_path = dir.path().join("pie.svg");
+        render_chart(df.lazy(), &config, &output_path, None).unwrap();
+        let svg = std::fs::read_to_string(&output_path).unwrap();
+
+        // Fill of every element of one kind, in drawing order
+        let fills = |element: &str| -> Vec<String> {
+            svg.split('<')
+                .filter(|tag| tag.starts_with(element))
+                .filter_map(|tag| tag.split("fill=\"").nth(1))
+                .filter_map(|rest| rest.split('"').next())
+                .map(str::to_string)
+                .collect()
+        };
+        let wedges = fills("polygon ");
+        let rects = fills("rect ");
+        let style = crate::render::styling::get_chart_style();
+        for (slot, channel) in ["organic", "direct", "paid"].iter().enumerate() {
+            let RGBColor(r, g, b) =
+                *style.get_group_color(slot, channel, &crate::spec::ColorMode::Hash);
+            let fill = format!("#{:02X}{:02X}{:02X}", r, g, b);
+            assert_eq!(wedges[slot], fill, "wedge for {}", channel);
+            assert!(rects.contains(&fill), "no swatch for {}", channel);
+        }
+    }
+}
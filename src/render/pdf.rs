@@ -0,0 +1,424 @@
+//! Single-page vector PDF drawing backend for plotters
+//!
+//! Shapes become PDF path operators and text is set in the standard Helvetica fonts, so the
+//! output needs no embedded fonts and stays sharp at any zoom.
+use plotters_backend::text_anchor::{HPos, VPos};
+use plotters_backend::{
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind,
+    FontStyle, FontTransform,
+};
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+
+/// Bezier control distance for a quarter circle, as a fraction of the radius
+const CIRCLE_KAPPA: f64 = 0.552_284_75;
+
+/// Draws into a PDF content stream that is written to `path` on `present`
+pub struct PdfBackend<'a> {
+    path: &'a Path,
+    size: (u32, u32),
+    /// MediaBox size in points; the drawing is stretched from `size` to fill it
+    page: (u32, u32),
+    content: String,
+    /// Opacities in use; each gets an `/GA<index>` graphics state
+    alphas: Vec<String>,
+    saved: bool,
+}
+
+impl<'a> PdfBackend<'a> {
+    pub fn new(path: &'a Path, size: (u32, u32)) -> Self {
+        // Flip the y axis once so every operator below works in plotters' top-left pixel space
+        let content = format!("1 0 0 -1 0 {} cm\n", size.1);
+        Self {
+            path,
+            size,
+            page: size,
+            content,
+            alphas: Vec::new(),
+            saved: false,
+        }
+    }
+
+    /// Print the `size` drawing on a page of `page` points, scaling everything on it
+    pub fn with_page_size(mut self, page: (u32, u32)) -> Self {
+        if page != self.size {
+            let scale_x = page.0 as f64 / self.size.0 as f64;
+            let scale_y = page.1 as f64 / self.size.1 as f64;
+            let scale = format!("{:.6} 0 0 {:.6} 0 0 cm\n", scale_x, scale_y);
+            self.content.insert_str(0, &scale);
+        }
+        self.page = page;
+        self
+    }
+
+    /// Open a graphics state for one shape; returns false when the color is fully transparent
+    fn begin(&mut self, color: BackendColor, fill: bool) -> bool {
+        if color.alpha <= 0.0 {
+            return false;
+        }
+        self.saved = false;
+        self.content.push_str("q\n");
+        if color.alpha < 1.0 {
+            let alpha = format!("{:.3}", color.alpha);
+            let index = match self.alphas.iter().position(|a| *a == alpha) {
+                Some(index) => index,
+                None => {
+                    self.alphas.push(alpha);
+                    self.alphas.len() - 1
+                }
+            };
+            let _ = writeln!(self.content, "/GA{} gs", index);
+        }
+        let (r, g, b) = color.rgb;
+        let _ = writeln!(
+            self.content,
+            "{:.3} {:.3} {:.3} {}",
+            r as f64 / 255.0,
+            g as f64 / 255.0,
+            b as f64 / 255.0,
+            if fill { "rg" } else { "RG" }
+        );
+        true
+    }
+
+    fn path_to(&mut self, points: &[BackendCoord]) {
+        for (index, (x, y)) in points.iter().enumerate() {
+            let op = if index == 0 { "m" } else { "l" };
+            let _ = writeln!(self.content, "{} {} {}", x, y, op);
+        }
+    }
+
+    /// The finished document: catalog, page tree, one page, fonts, opacities and content
+    fn document(&self) -> Vec<u8> {
+        let (width, height) = self.page;
+        let font_ids = (4, 5);
+        let first_alpha_id = 6;
+        let content_id = first_alpha_id + self.alphas.len();
+
+        let alpha_resources: String = self
+            .alphas
+            .iter()
+            .enumerate()
+            .map(|(index, _)| format!(" /GA{} {} 0 R", index, first_alpha_id + index))
+            .collect();
+
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] \
+                 /Resources << /Font << /F1 {} 0 R /F2 {} 0 R >> /ExtGState <<{} >> >> \
+                 /Contents {} 0 R >>",
+                width, height, font_ids.0, font_ids.1, alpha_resources, content_id
+            ),
+            font_object("Helvetica"),
+            font_object("Helvetica-Bold"),
+        ];
+        for alpha in &self.alphas {
+            objects.push(format!("<< /Type /ExtGState /ca {0} /CA {0} >>", alpha));
+        }
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            self.content.len(),
+            self.content
+        ));
+
+        let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(objects.len());
+        for (index, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", index + 1, object);
+        }
+        let xref = pdf.len();
+        let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            let _ = write!(pdf, "{:010} 00000 n \n", offset);
+        }
+        let _ = write!(
+            pdf,
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        );
+        pdf
+    }
+}
+
+fn font_object(name: &str) -> String {
+    format!(
+        "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+        name
+    )
+}
+
+/// A PDF string literal in WinAnsi encoding; characters it lacks become `?`
+fn pdf_string(text: &str) -> String {
+    let mut out = String::from("(");
+    for c in text.chars() {
+        let byte = match c {
+            '€' => 0x80,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '•' => 0x95,
+            '–' => 0x96,
+            '—' => 0x97,
+            c if (c as u32) < 0x20 => b' ',
+            c if (c as u32) < 0x80 || (0xa0..=0xff).contains(&(c as u32)) => c as u32 as u8,
+            _ => b'?',
+        };
+        match byte {
+            b'(' | b')' | b'\\' => {
+                out.push('\\');
+                out.push(byte as char);
+            }
+            0x20..=0x7e => out.push(byte as char),
+            _ => {
+                let _ = write!(out, "\\{:03o}", byte);
+            }
+        }
+    }
+    out.push(')');
+    out
+}
+
+impl DrawingBackend for PdfBackend<'_> {
+    type ErrorType = std::io::Error;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        std::fs::write(self.path, self.document()).map_err(DrawingErrorKind::DrawingError)?;
+        self.saved = true;
+        Ok(())
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.draw_rect(point, point, &color, true)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.draw_path([from, to], style)
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if !self.begin(style.color(), fill) {
+            return Ok(());
+        }
+        let (x0, y0) = upper_left;
+        let (x1, y1) = bottom_right;
+        if fill {
+            // Pixel coordinates are inclusive, so a filled rect covers one extra column and row
+            let _ = writeln!(
+                self.content,
+                "{} {} {} {} re f\nQ",
+                x0,
+                y0,
+                x1 - x0 + 1,
+                y1 - y0 + 1
+            );
+        } else {
+            let _ = writeln!(
+                self.content,
+                "{} w {} {} {} {} re S\nQ",
+                style.stroke_width(),
+                x0,
+                y0,
+                x1 - x0,
+                y1 - y0
+            );
+        }
+        Ok(())
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let points: Vec<_> = path.into_iter().collect();
+        if points.len() < 2 || !self.begin(style.color(), false) {
+            return Ok(());
+        }
+        let _ = writeln!(self.content, "{} w 1 J 1 j", style.stroke_width());
+        self.path_to(&points);
+        self.content.push_str("S\nQ\n");
+        Ok(())
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if !self.begin(style.color(), fill) {
+            return Ok(());
+        }
+        let (cx, cy) = (center.0 as f64, center.1 as f64);
+        let r = radius as f64;
+        let k = r * CIRCLE_KAPPA;
+        if !fill {
+            let _ = writeln!(self.content, "{} w", style.stroke_width());
+        }
+        let _ = writeln!(self.content, "{:.2} {:.2} m", cx + r, cy);
+        for (c1, c2, end) in [
+            ((cx + r, cy + k), (cx + k, cy + r), (cx, cy + r)),
+            ((cx - k, cy + r), (cx - r, cy + k), (cx - r, cy)),
+            ((cx - r, cy - k), (cx - k, cy - r), (cx, cy - r)),
+            ((cx + k, cy - r), (cx + r, cy - k), (cx + r, cy)),
+        ] {
+            let _ = writeln!(
+                self.content,
+                "{:.2} {:.2} {:.2} {:.2} {:.2} {:.2} c",
+                c1.0, c1.1, c2.0, c2.1, end.0, end.1
+            );
+        }
+        self.content
+            .push_str(if fill { "f\nQ\n" } else { "S\nQ\n" });
+        Ok(())
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let points: Vec<_> = vert.into_iter().collect();
+        if points.len() < 3 || !self.begin(style.color(), true) {
+            return Ok(());
+        }
+        self.path_to(&points);
+        self.content.push_str("h f\nQ\n");
+        Ok(())
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        if text.is_empty() || !self.begin(style.color(), true) {
+            return Ok(());
+        }
+
+        // Same sizing as the SVG backend, so layouts match across formats
+        let font_size = style.size() / 1.24;
+        let width = match style.layout_box(text) {
+            Ok(((min_x, _), (max_x, _))) => (max_x - min_x) as f64,
+            Err(_) => text.chars().count() as f64 * font_size * 0.5,
+        };
+        // Offsets along the text direction and down across it, in unrotated text space
+        let along = match style.anchor().h_pos {
+            HPos::Left => 0.0,
+            HPos::Center => -width / 2.0,
+            HPos::Right => -width,
+        };
+        let across = match style.anchor().v_pos {
+            VPos::Top => font_size * 0.76,
+            VPos::Center => font_size * 0.25,
+            VPos::Bottom => -font_size * 0.24,
+        };
+
+        // Clockwise rotation in the y-down page space; text space is y-up, hence the sign flips
+        let (cos, sin) = match style.transform() {
+            FontTransform::None => (1.0, 0.0),
+            FontTransform::Rotate90 => (0.0, 1.0),
+            FontTransform::Rotate180 => (-1.0, 0.0),
+            FontTransform::Rotate270 => (0.0, -1.0),
+        };
+        let x = pos.0 as f64 + along * cos - across * sin;
+        let y = pos.1 as f64 + along * sin + across * cos;
+        let font = match style.style() {
+            FontStyle::Bold => "F2",
+            _ => "F1",
+        };
+
+        let _ = writeln!(
+            self.content,
+            "BT /{} {:.2} Tf {} {} {} {} {:.2} {:.2} Tm {} Tj ET\nQ",
+            font,
+            font_size,
+            cos,
+            sin,
+            sin,
+            -cos,
+            x,
+            y,
+            pdf_string(text)
+        );
+        Ok(())
+    }
+}
+
+impl Drop for PdfBackend<'_> {
+    fn drop(&mut self) {
+        if !self.saved {
+            // drop should not panic, so a failed write is ignored
+            let _ = self.present();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::prelude::*;
+
+    #[test]
+    fn test_pdf_is_a_vector_document() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("chart.pdf");
+
+        {
+            let root = PdfBackend::new(&path, (200, 100)).into_drawing_area();
+            root.fill(&WHITE).unwrap();
+            root.draw(&PathElement::new(vec![(10, 10), (190, 90)], BLUE))
+                .unwrap();
+            root.draw(&Text::new("Sales (€)", (20, 20), ("sans-serif", 12)))
+                .unwrap();
+            root.present().unwrap();
+        }
+
+        let pdf = std::fs::read(&path).unwrap();
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("/MediaBox [0 0 200 100]"));
+        assert!(text.contains("10 10 m\n190 90 l\nS"));
+        assert!(text.contains("(Sales \\(\\200\\)) Tj"));
+    }
+
+    #[test]
+    fn test_pdf_string_escapes_and_encodes() {
+        assert_eq!(pdf_string("a(b)\\"), "(a\\(b\\)\\\\)");
+        assert_eq!(pdf_string("Région…"), "(R\\351gion\\205)");
+        assert_eq!(pdf_string("日本"), "(??)");
+    }
+}
@@ -0,0 +1,242 @@
+//! Supersampled bitmap rendering for `quality: high`
+//!
+//! Plotters' bitmap backend draws without antialiasing, so a chart is drawn at a multiple of its
+//! size with every coordinate, stroke and font scaled up, then shrunk back down; edges come out
+//! blended with their surroundings.
+use anyhow::{Context, Result};
+use plotters::style::{FontDesc, TextStyle};
+use plotters_backend::{
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind,
+};
+
+/// Forwards drawing to `inner`, `factor` times larger in each direction
+///
+/// Charts see the logical size, so layout is unchanged; only the pixels behind it multiply.
+pub struct Supersampled<DB> {
+    inner: DB,
+    factor: u32,
+}
+
+impl<DB: DrawingBackend> Supersampled<DB> {
+    /// Wrap `inner`, whose size must be `factor` times the logical canvas
+    pub fn new(inner: DB, factor: u32) -> Self {
+        Self {
+            inner,
+            factor: factor.max(1),
+        }
+    }
+
+    fn scale(&self, (x, y): BackendCoord) -> BackendCoord {
+        (x * self.factor as i32, y * self.factor as i32)
+    }
+
+    /// Bottom-right corner of the block a logical pixel covers
+    fn scale_end(&self, point: BackendCoord) -> BackendCoord {
+        let (x, y) = self.scale(point);
+        let extra = self.factor as i32 - 1;
+        (x + extra, y + extra)
+    }
+}
+
+/// A style with its stroke widened by the supersampling factor
+struct ScaledStyle<'a, S> {
+    style: &'a S,
+    factor: u32,
+}
+
+impl<S: BackendStyle> BackendStyle for ScaledStyle<'_, S> {
+    fn color(&self) -> BackendColor {
+        self.style.color()
+    }
+
+    fn stroke_width(&self) -> u32 {
+        self.style.stroke_width() * self.factor
+    }
+}
+
+impl<DB: DrawingBackend> DrawingBackend for Supersampled<DB> {
+    type ErrorType = DB::ErrorType;
+
+    fn get_size(&self) -> (u32, u32) {
+        let (width, height) = self.inner.get_size();
+        (width / self.factor, height / self.factor)
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.ensure_prepared()
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        self.inner.present()
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let (start, end) = (self.scale(point), self.scale_end(point));
+        self.inner.draw_rect(start, end, &color, true)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let scaled = ScaledStyle {
+            style,
+            factor: self.factor,
+        };
+        let (from, to) = (self.scale(from), self.scale(to));
+        self.inner.draw_path([from, to], &scaled)
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let scaled = ScaledStyle {
+            style,
+            factor: self.factor,
+        };
+        let (start, end) = (self.scale(upper_left), self.scale_end(bottom_right));
+        self.inner.draw_rect(start, end, &scaled, fill)
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let scaled = ScaledStyle {
+            style,
+            factor: self.factor,
+        };
+        let path: Vec<BackendCoord> = path.into_iter().map(|point| self.scale(point)).collect();
+        self.inner.draw_path(path, &scaled)
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let scaled = ScaledStyle {
+            style,
+            factor: self.factor,
+        };
+        let center = self.scale(center);
+        self.inner
+            .draw_circle(center, radius * self.factor, &scaled, fill)
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let vert: Vec<BackendCoord> = vert.into_iter().map(|point| self.scale(point)).collect();
+        self.inner.fill_polygon(vert, style)
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        let font = FontDesc::new(
+            style.family(),
+            style.size() * self.factor as f64,
+            style.style(),
+        )
+        .transform(style.transform());
+        let scaled = TextStyle {
+            font,
+            color: style.color(),
+            pos: style.anchor(),
+        };
+        let pos = self.scale(pos);
+        self.inner.draw_text(text, &scaled, pos)
+    }
+
+    fn blit_bitmap(
+        &mut self,
+        pos: BackendCoord,
+        (width, height): (u32, u32),
+        src: &[u8],
+    ) -> Result<(), DrawingErrorKind<Self::ErrorType>> {
+        // Each source pixel becomes a factor x factor block
+        let factor = self.factor as usize;
+        let row_len = width as usize * factor * 3;
+        let mut scaled = Vec::with_capacity(row_len * height as usize * factor);
+        for row in src.chunks_exact(width as usize * 3) {
+            let start = scaled.len();
+            for pixel in row.chunks_exact(3) {
+                for _ in 0..factor {
+                    scaled.extend_from_slice(pixel);
+                }
+            }
+            for _ in 1..factor {
+                scaled.extend_from_within(start..start + row_len);
+            }
+        }
+        let pos = self.scale(pos);
+        self.inner
+            .blit_bitmap(pos, (width * self.factor, height * self.factor), &scaled)
+    }
+}
+
+/// Shrink an RGB buffer drawn at `factor` times `size` to `output`, usually `size` itself
+pub fn downsample(
+    buffer: Vec<u8>,
+    size: (u32, u32),
+    factor: u32,
+    output: (u32, u32),
+) -> Result<image::RgbImage> {
+    let (width, height) = size;
+    let large = image::RgbImage::from_raw(width * factor, height * factor, buffer)
+        .context("Supersampled buffer does not match the canvas size")?;
+    if large.dimensions() == output {
+        return Ok(large);
+    }
+    Ok(image::imageops::resize(
+        &large,
+        output.0,
+        output.1,
+        image::imageops::FilterType::Triangle,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use plotters::prelude::*;
+
+    #[test]
+    fn test_supersampled_canvas_reports_logical_size() {
+        let mut buffer = vec![0u8; 40 * 20 * 3];
+        let backend = Supersampled::new(BitMapBackend::with_buffer(&mut buffer, (40, 20)), 2);
+        assert_eq!(backend.get_size(), (20, 10));
+
+        let area = backend.into_drawing_area();
+        area.fill(&WHITE).unwrap();
+        area.draw_pixel((19, 9), &RED).unwrap();
+        area.present().unwrap();
+        drop(area);
+
+        let image = downsample(buffer, (20, 10), 2, (20, 10)).unwrap();
+        assert_eq!(image.dimensions(), (20, 10));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 255, 255]);
+        // The red block lands on the logical pixel it was drawn at
+        let corner = image.get_pixel(19, 9).0;
+        assert!(corner[0] > 200 && corner[1] < 128);
+    }
+}
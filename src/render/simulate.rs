@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use image::DynamicImage;
+use std::path::Path;
+
+/// Color-vision deficiency to preview a rendered chart with
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum ColorVision {
+    /// No red cones
+    Protanopia,
+    /// No green cones
+    Deuteranopia,
+    /// No blue cones
+    Tritanopia,
+}
+
+impl ColorVision {
+    /// Full-severity simulation matrix from Machado, Oliveira & Fernandes (2009), applied to
+    /// linear RGB
+    fn matrix(&self) -> [[f32; 3]; 3] {
+        match self {
+            ColorVision::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorVision::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorVision::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+/// Rewrite a PNG in place as it would look with `vision`; alpha is left untouched
+pub fn simulate_file(path: &Path, vision: ColorVision) -> Result<()> {
+    let image =
+        image::open(path).with_context(|| format!("Failed to open image: {}", path.display()))?;
+
+    let simulated = if image.color().has_alpha() {
+        let mut rgba = image.to_rgba8();
+        for pixel in rgba.pixels_mut() {
+            let [r, g, b] = simulate_rgb([pixel[0], pixel[1], pixel[2]], vision);
+            pixel.0 = [r, g, b, pixel[3]];
+        }
+        DynamicImage::ImageRgba8(rgba)
+    } else {
+        let mut rgb = image.to_rgb8();
+        for pixel in rgb.pixels_mut() {
+            pixel.0 = simulate_rgb(pixel.0, vision);
+        }
+        DynamicImage::ImageRgb8(rgb)
+    };
+
+    simulated
+        .save_with_format(path, image::ImageFormat::Png)
+        .with_context(|| format!("Failed to write image: {}", path.display()))
+}
+
+/// One sRGB color as seen with `vision`
+pub fn simulate_rgb(rgb: [u8; 3], vision: ColorVision) -> [u8; 3] {
+    let linear = rgb.map(to_linear);
+    let matrix = vision.matrix();
+    [0, 1, 2].map(|row| {
+        let value: f32 = (0..3).map(|col| matrix[row][col] * linear[col]).sum();
+        from_linear(value)
+    })
+}
+
+fn to_linear(channel: u8) -> f32 {
+    let c = channel as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(value: f32) -> u8 {
+    let v = value.clamp(0.0, 1.0);
+    let c = if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn test_simulated_chart_differs_and_stays_valid() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("chart.png");
+
+        // Red and green bars on white: the pair protanopes confuse
+        let mut chart = RgbImage::from_pixel(20, 10, Rgb([255, 255, 255]));
+        for y in 0..10 {
+            chart.put_pixel(2, y, Rgb([214, 39, 40]));
+            chart.put_pixel(10, y, Rgb([44, 160, 44]));
+        }
+        chart.save(&path).unwrap();
+
+        simulate_file(&path, ColorVision::Protanopia).unwrap();
+
+        let simulated = image::open(&path).unwrap().to_rgb8();
+        assert_eq!(simulated.dimensions(), (20, 10));
+        assert_ne!(simulated.as_raw(), chart.as_raw());
+        assert_ne!(simulated.get_pixel(2, 0), &Rgb([214, 39, 40]));
+    }
+
+    #[test]
+    fn test_grays_are_unchanged() {
+        for vision in [
+            ColorVision::Protanopia,
+            ColorVision::Deuteranopia,
+            ColorVision::Tritanopia,
+        ] {
+            for gray in [0u8, 128, 255] {
+                let [r, g, b] = simulate_rgb([gray; 3], vision);
+                for channel in [r, g, b] {
+                    assert!(channel.abs_diff(gray) <= 1, "{:?} shifted {}", vision, gray);
+                }
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::spec::Theme;
+use crate::spec::{ChartConfig, ColorMap, ColorMode, ColorScale, Theme};
 use plotters::prelude::*;
 
 /// Centralized styling configuration for all chart types
@@ -316,6 +316,96 @@ impl Default for HeatmapStyle {
     }
 }
 
+impl HeatmapStyle {
+    /// Base shade for a color position in 0.0..=1.0
+    pub fn shade(&self, position: f32) -> f32 {
+        self.intensity_range.0 + position * (self.intensity_range.1 - self.intensity_range.0)
+    }
+}
+
+/// Color at position `t` (0.0..=1.0) along a colormap's gradient, from low to high values
+///
+/// Viridis and plasma interpolate matplotlib's stops at 0, 0.25, 0.5, 0.75 and 1; the
+/// single-hue maps run from ColorBrewer's lightest to darkest shade through its middle one.
+pub fn colormap_color(map: &ColorMap, t: f32) -> RGBColor {
+    let stops: &[(u8, u8, u8)] = match map {
+        ColorMap::Viridis => &[
+            (68, 1, 84),
+            (59, 82, 139),
+            (33, 145, 140),
+            (94, 201, 98),
+            (253, 231, 37),
+        ],
+        ColorMap::Plasma => &[
+            (13, 8, 135),
+            (126, 3, 168),
+            (204, 71, 120),
+            (248, 149, 64),
+            (240, 249, 33),
+        ],
+        ColorMap::Blues => &[(247, 251, 255), (107, 174, 214), (8, 48, 107)],
+        ColorMap::Reds => &[(255, 245, 240), (251, 106, 74), (103, 0, 13)],
+        ColorMap::Greens => &[(247, 252, 245), (116, 196, 118), (0, 68, 27)],
+    };
+
+    // NaN falls to the low end rather than poisoning the interpolation
+    let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+    let scaled = t * (stops.len() - 1) as f32;
+    let index = (scaled.floor() as usize).min(stops.len() - 2);
+    let fraction = scaled - index as f32;
+    let (low, high) = (stops[index], stops[index + 1]);
+    let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * fraction).round() as u8;
+    RGBColor(mix(low.0, high.0), mix(low.1, high.1), mix(low.2, high.2))
+}
+
+/// Map values to color positions in 0.0..=1.0 according to the color scale
+pub fn color_positions(values: &[f32], scale: &ColorScale) -> Vec<f32> {
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    if values.is_empty() || max <= min {
+        return vec![0.5; values.len()];
+    }
+
+    match scale {
+        ColorScale::Linear => values.iter().map(|v| (v - min) / (max - min)).collect(),
+        ColorScale::Log => {
+            let span = (max - min).ln_1p();
+            values.iter().map(|v| (v - min).ln_1p() / span).collect()
+        }
+        ColorScale::Quantile => {
+            let mut distinct = values.to_vec();
+            distinct.sort_by(|a, b| a.total_cmp(b));
+            distinct.dedup();
+            let last = (distinct.len() - 1) as f32;
+            values
+                .iter()
+                .map(|v| {
+                    let rank = distinct.partition_point(|d| d < v);
+                    rank as f32 / last
+                })
+                .collect()
+        }
+    }
+}
+
+/// FNV-1a hash of a group name; unlike `DefaultHasher` it is fixed across runs and Rust versions
+fn stable_hash(name: &str) -> u64 {
+    name.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Palette slot of each group: its position among the groups sorted by name, so a group gets
+/// the same color whatever order the rows or `stack_order` put it in
+pub fn group_color_slots(groups: &[String]) -> Vec<usize> {
+    let mut sorted: Vec<&String> = groups.iter().collect();
+    sorted.sort();
+    groups
+        .iter()
+        .map(|group| sorted.partition_point(|name| *name < group))
+        .collect()
+}
+
 /// Helper functions for creating styled fonts and colors
 impl ChartStyle {
     /// Get a primary color by index (cycles through available colors)
@@ -323,6 +413,18 @@ impl ChartStyle {
         &self.colors.primary[index % self.colors.primary.len()]
     }
 
+    /// Get the color for a named group: by its palette slot (see [`group_color_slots`]), or by
+    /// hashing the name in `ColorMode::Hash` so the same group gets the same color in every chart
+    pub fn get_group_color(&self, index: usize, group: &str, mode: &ColorMode) -> &RGBColor {
+        match mode {
+            ColorMode::Index => self.get_primary_color(index),
+            ColorMode::Hash => {
+                let palette_len = self.colors.primary.len() as u64;
+                self.get_primary_color((stable_hash(group) % palette_len) as usize)
+            }
+        }
+    }
+
     /// Create a title font style
     pub fn title_font(&self) -> TextStyle<'_> {
         (self.typography.font_family, self.typography.sizes.title)
@@ -364,6 +466,52 @@ pub fn get_chart_style() -> ChartStyle {
     ChartStyle::default()
 }
 
+/// Chart style with the series palette replaced by the chart's `colors`, when it sets any
+///
+/// Specs are validated before rendering, so colors that don't parse are skipped here.
+pub fn get_chart_style_for(config: &ChartConfig) -> ChartStyle {
+    let mut style = get_chart_style();
+    let colors: Vec<RGBColor> = config
+        .colors
+        .iter()
+        .flatten()
+        .filter_map(|color| parse_hex_color(color).ok())
+        .collect();
+    if !colors.is_empty() {
+        style.colors.primary = colors;
+    }
+    style
+}
+
+/// Parse a `#rrggbb` or `#rgb` hex color
+pub fn parse_hex_color(value: &str) -> anyhow::Result<RGBColor> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid color '{}': expected a hex color like #1f77b4",
+            value
+        )
+    };
+    let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+    let channel = |digits: &str| u8::from_str_radix(digits, 16).map_err(|_| invalid());
+    match hex.len() {
+        6 => Ok(RGBColor(
+            channel(&hex[0..2])?,
+            channel(&hex[2..4])?,
+            channel(&hex[4..6])?,
+        )),
+        // #rgb is shorthand for #rrggbb
+        3 => Ok(RGBColor(
+            channel(&hex[0..1].repeat(2))?,
+            channel(&hex[1..2].repeat(2))?,
+            channel(&hex[2..3].repeat(2))?,
+        )),
+        _ => Err(invalid()),
+    }
+}
+
 /// Get chart style with specific theme
 pub fn get_chart_style_with_theme(theme: &Theme) -> ChartStyle {
     ChartStyle::new(theme.clone())
@@ -373,3 +521,95 @@ pub fn get_chart_style_with_theme(theme: &Theme) -> ChartStyle {
 pub fn get_heatmap_style() -> HeatmapStyle {
     HeatmapStyle::default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn distinct_shades(values: &[f32], scale: &ColorScale) -> usize {
+        let style = get_heatmap_style();
+        color_positions(values, scale)
+            .iter()
+            .map(|&p| style.shade(p) as u8)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    #[test]
+    fn test_quantile_scale_separates_skewed_values() {
+        // Most cells cluster low with a single large outlier
+        let values = vec![1.0, 1.2, 1.4, 1.6, 1.8, 1000.0];
+
+        assert!(distinct_shades(&values, &ColorScale::Linear) < values.len());
+        assert_eq!(
+            distinct_shades(&values, &ColorScale::Quantile),
+            values.len()
+        );
+    }
+
+    #[test]
+    fn test_hex_colors_parse() {
+        assert_eq!(
+            parse_hex_color("#1f77b4").unwrap(),
+            RGBColor(0x1f, 0x77, 0xb4)
+        );
+        assert_eq!(parse_hex_color("#F0A").unwrap(), RGBColor(0xff, 0x00, 0xaa));
+        for malformed in ["1f77b4", "#1f77b", "#1f77bz", "#", "blue"] {
+            let error = parse_hex_color(malformed).unwrap_err().to_string();
+            assert!(error.contains("Invalid color"), "{}: {}", malformed, error);
+        }
+    }
+
+    #[test]
+    fn test_color_positions_bounds() {
+        let values = vec![0.0, 10.0, 100.0];
+        for scale in [ColorScale::Linear, ColorScale::Quantile, ColorScale::Log] {
+            let positions = color_positions(&values, &scale);
+            assert_eq!(positions.first(), Some(&0.0));
+            assert_eq!(positions.last(), Some(&1.0));
+        }
+        assert_eq!(
+            color_positions(&[5.0, 5.0], &ColorScale::Linear),
+            vec![0.5, 0.5]
+        );
+    }
+
+    #[test]
+    fn test_colormap_gradients() {
+        // How far red stands out above the other channels
+        let redness = |color: RGBColor| color.0 as i32 - (color.1 as i32 + color.2 as i32) / 2;
+        assert!(
+            redness(colormap_color(&ColorMap::Reds, 1.0))
+                > redness(colormap_color(&ColorMap::Reds, 0.0))
+        );
+
+        assert_eq!(colormap_color(&ColorMap::Viridis, 0.0), RGBColor(68, 1, 84));
+        assert_eq!(
+            colormap_color(&ColorMap::Viridis, 1.0),
+            RGBColor(253, 231, 37)
+        );
+        assert_eq!(
+            colormap_color(&ColorMap::Plasma, 0.5),
+            RGBColor(204, 71, 120)
+        );
+        // Out-of-range positions are clamped to the ends
+        assert_eq!(
+            colormap_color(&ColorMap::Blues, 2.0),
+            colormap_color(&ColorMap::Blues, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_hash_color_mode_is_stable_per_group() {
+        let style = get_chart_style();
+        let first_run = style.get_group_color(0, "organic", &ColorMode::Hash);
+        let second_run = style.get_group_color(3, "organic", &ColorMode::Hash);
+        assert_eq!(first_run, second_run);
+        assert_eq!(stable_hash("organic"), stable_hash("organic"));
+        assert_eq!(
+            style.get_group_color(3, "organic", &ColorMode::Index),
+            style.get_primary_color(3)
+        );
+    }
+}
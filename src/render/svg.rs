@@ -0,0 +1,133 @@
+use anyhow::{Context, Result};
+use regex::{Captures, Regex};
+use std::path::Path;
+
+/// Opacity keeps at least this many decimals so translucent fills don't snap to 0 or 1
+const MIN_OPACITY_DECIMALS: usize = 2;
+
+/// Round the decimal numbers in every attribute of an SVG document to `decimals` places
+///
+/// Text content is left alone, so data labels keep their formatting.
+pub fn round_svg_numbers(svg: &str, decimals: usize) -> String {
+    let tag = Regex::new(r"<[^>]*>").unwrap();
+    let attribute = Regex::new(r#"([\w:-]+)="([^"]*)""#).unwrap();
+    let number = Regex::new(r"-?\d+\.\d+").unwrap();
+
+    tag.replace_all(svg, |tag: &Captures| {
+        attribute
+            .replace_all(&tag[0], |attr: &Captures| {
+                let places = if attr[1].ends_with("opacity") {
+                    decimals.max(MIN_OPACITY_DECIMALS)
+                } else {
+                    decimals
+                };
+                let value =
+                    number.replace_all(&attr[2], |n: &Captures| round_number(&n[0], places));
+                format!("{}=\"{}\"", &attr[1], value)
+            })
+            .into_owned()
+    })
+    .into_owned()
+}
+
+/// Rewrite an SVG file in place with its attribute numbers rounded
+pub fn round_svg_file(path: &Path, decimals: usize) -> Result<()> {
+    let svg = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read SVG file: {}", path.display()))?;
+    std::fs::write(path, round_svg_numbers(&svg, decimals))
+        .with_context(|| format!("Failed to write SVG file: {}", path.display()))
+}
+
+/// Set the displayed size of an SVG file, keeping its `viewBox` so the drawing scales to fit
+pub fn resize_svg_file(path: &Path, (width, height): (u32, u32)) -> Result<()> {
+    let svg = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read SVG file: {}", path.display()))?;
+    std::fs::write(path, resize_svg(&svg, (width, height)))
+        .with_context(|| format!("Failed to write SVG file: {}", path.display()))
+}
+
+/// Set the displayed size of an SVG document, keeping its `viewBox`
+pub fn resize_svg(svg: &str, (width, height): (u32, u32)) -> String {
+    let root = Regex::new(r#"<svg width="\d+" height="\d+""#).unwrap();
+    root.replace(
+        svg,
+        format!(r#"<svg width="{}" height="{}""#, width, height),
+    )
+    .into_owned()
+}
+
+/// `12.903225806451612` -> `12.9` at one decimal; trailing zeros and a bare `-0` are dropped
+fn round_number(text: &str, decimals: usize) -> String {
+    let Ok(value) = text.parse::<f64>() else {
+        return text.to_string();
+    };
+    let rounded = format!("{:.*}", decimals, value);
+    let rounded = if rounded.contains('.') {
+        rounded.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        rounded.as_str()
+    };
+    if rounded == "-0" {
+        "0".to_string()
+    } else {
+        rounded.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::{ChartConfig, ChartType, OutputFormat};
+    use polars::prelude::*;
+
+    /// Every opening tag is closed in order, i.e. the document is well-formed XML
+    fn assert_well_formed(svg: &str) {
+        let tag = Regex::new(r"<(/?)([\w:-]+)[^>]*?(/?)>").unwrap();
+        let mut open = Vec::new();
+        for caps in tag.captures_iter(svg) {
+            let name = caps[2].to_string();
+            if &caps[1] == "/" {
+                assert_eq!(open.pop(), Some(name), "mismatched closing tag");
+            } else if &caps[3] != "/" {
+                open.push(name);
+            }
+        }
+        assert!(open.is_empty(), "unclosed tags: {:?}", open);
+    }
+
+    #[test]
+    fn test_round_svg_numbers_only_touches_attributes() {
+        let svg = r#"<svg><text x="10" font-size="12.903225806451612" opacity="0.30000001192092896">1.23456</text></svg>"#;
+        assert_eq!(
+            round_svg_numbers(svg, 0),
+            r#"<svg><text x="10" font-size="13" opacity="0.3">1.23456</text></svg>"#
+        );
+    }
+
+    #[test]
+    fn test_rounded_chart_is_smaller_and_well_formed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("chart.svg");
+        let df = df! {
+            "date" => &["2023-01-01", "2023-01-02", "2023-01-03"],
+            "users" => &[100.5, 150.25, 120.75],
+        }
+        .unwrap();
+        let config = ChartConfig {
+            chart_type: ChartType::Area,
+            title: Some("Users".to_string()),
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            format: Some(OutputFormat::Svg),
+            ..Default::default()
+        };
+        crate::render::render_chart(df.lazy(), &config, &path, None).unwrap();
+        let original = std::fs::read_to_string(&path).unwrap();
+
+        round_svg_file(&path, 1).unwrap();
+        let rounded = std::fs::read_to_string(&path).unwrap();
+
+        assert!(rounded.len() < original.len());
+        assert_well_formed(&rounded);
+    }
+}
@@ -0,0 +1,173 @@
+//! Starter spec written by `graff init`, built from the columns of a CSV
+use polars::prelude::*;
+use std::path::Path;
+
+/// Columns picked out of a schema for the example charts
+#[derive(Debug, Default)]
+struct Columns<'a> {
+    dates: Vec<&'a str>,
+    numbers: Vec<&'a str>,
+    categories: Vec<&'a str>,
+}
+
+impl<'a> Columns<'a> {
+    fn from_schema(schema: &'a Schema) -> Self {
+        let mut columns = Self::default();
+        for (name, dtype) in schema.iter() {
+            match dtype {
+                DataType::Date | DataType::Datetime(_, _) => columns.dates.push(name.as_str()),
+                DataType::Utf8 => columns.categories.push(name.as_str()),
+                dtype if dtype.is_numeric() => columns.numbers.push(name.as_str()),
+                _ => {}
+            }
+        }
+        columns
+    }
+}
+
+/// A YAML spec with one example chart for `data_path`, plus commented alternatives
+///
+/// The example is a line of the first numeric column over the first date column, falling back
+/// to a bar per category, or a row count when there is no numeric column at all.
+pub fn starter_spec(data_path: &Path, schema: &Schema) -> String {
+    let columns = Columns::from_schema(schema);
+    let first_column = schema.iter_names().next().map(|name| name.as_str());
+    let first_number = columns.numbers.first().copied();
+    let first_category = columns.categories.first().copied();
+
+    let mut spec = String::from("# Starter spec generated by `graff init`; render it with\n");
+    spec.push_str("#   graff render --spec charts.yaml --out charts/\n");
+    spec.push_str("# See docs/api.md for every chart type and field.\n\n");
+    spec.push_str("data:\n");
+    spec.push_str(&format!(
+        "  default: {}\n\n",
+        quoted(&data_path.to_string_lossy())
+    ));
+    spec.push_str("charts:\n");
+
+    match (
+        columns.dates.first(),
+        first_number,
+        first_category,
+        first_column,
+    ) {
+        (Some(date), Some(number), _, _) => {
+            spec.push_str(&chart(
+                "line",
+                &format!("{} over time", number),
+                date,
+                number,
+            ));
+            spec.push_str("    agg: sum\n");
+        }
+        (None, Some(number), Some(category), _) => {
+            spec.push_str(&chart(
+                "bar",
+                &format!("{} by {}", number, category),
+                category,
+                number,
+            ));
+            spec.push_str("    agg: sum\n");
+        }
+        (_, _, _, Some(column)) => {
+            spec.push_str(&chart(
+                "bar",
+                &format!("Rows by {}", column),
+                column,
+                column,
+            ));
+            spec.push_str("    agg: count\n");
+        }
+        (_, _, _, None) => spec.push_str("  []\n"),
+    }
+
+    // Alternatives stay commented out so the spec renders a single chart until edited
+    let mut alternatives = Vec::new();
+    if let (Some(category), Some(number)) = (first_category, first_number) {
+        if let Some(date) = columns.dates.first() {
+            alternatives.push(format!(
+                "{}    group_by: {}\n    agg: sum\n",
+                chart("line", &format!("{} by {}", number, category), date, number),
+                quoted(category)
+            ));
+        }
+        alternatives.push(format!(
+            "{}    agg: sum\n",
+            chart(
+                "bar",
+                &format!("Total {} by {}", number, category),
+                category,
+                number
+            )
+        ));
+    }
+    if let [x, y, ..] = columns.numbers[..] {
+        alternatives.push(chart("scatter", &format!("{} vs {}", y, x), x, y));
+    }
+
+    if !alternatives.is_empty() {
+        spec.push_str("\n  # Other charts to try:\n");
+        for alternative in alternatives {
+            spec.push_str("  #\n");
+            for line in alternative.lines() {
+                spec.push_str(&format!("  # {}\n", line));
+            }
+        }
+    }
+
+    spec
+}
+
+fn chart(chart_type: &str, title: &str, x: &str, y: &str) -> String {
+    format!(
+        "  - type: {}\n    title: {}\n    x: {}\n    y: {}\n",
+        chart_type,
+        quoted(title),
+        quoted(x),
+        quoted(y)
+    )
+}
+
+/// Double-quoted YAML scalar (JSON strings are valid YAML)
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spec::{ChartSpec, ChartType};
+
+    #[test]
+    fn test_starter_spec_parses_and_uses_real_columns() {
+        let schema = Schema::from_iter([
+            Field::new("channel", DataType::Utf8),
+            Field::new("users", DataType::Int64),
+            Field::new("date", DataType::Date),
+            Field::new("sessions", DataType::Float64),
+        ]);
+
+        let yaml = starter_spec(Path::new("data.csv"), &schema);
+        let spec = ChartSpec::from_yaml(&yaml).unwrap();
+
+        assert_eq!(spec.charts.len(), 1);
+        let chart = &spec.charts[0];
+        assert_eq!(chart.chart_type, ChartType::Line);
+        assert_eq!(chart.x.as_deref(), Some("date"));
+        assert_eq!(chart.y.as_deref(), Some("users"));
+        for column in [&chart.x, &chart.y].into_iter().flatten() {
+            assert!(schema.contains(column), "unknown column {}", column);
+        }
+        // The alternatives are present, but commented out
+        assert!(yaml.contains("#   - type: scatter"));
+    }
+
+    #[test]
+    fn test_starter_spec_counts_rows_without_numbers() {
+        let schema = Schema::from_iter([Field::new("country", DataType::Utf8)]);
+        let spec = ChartSpec::from_yaml(&starter_spec(Path::new("d.csv"), &schema)).unwrap();
+
+        assert_eq!(spec.charts[0].chart_type, ChartType::Bar);
+        assert_eq!(spec.charts[0].x.as_deref(), Some("country"));
+    }
+}
@@ -13,14 +13,20 @@ pub struct ChartSpec {
 pub struct DataConfig {
     pub default: Option<PathBuf>,
     pub sources: Option<HashMap<String, PathBuf>>,
+    /// CSV text used when no `default` file is given
+    pub inline: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Column holding the row counts of a chart that sets `agg: count` without a `y`
+pub const COUNT_COLUMN: &str = "count";
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ChartConfig {
     #[serde(rename = "type")]
     pub chart_type: ChartType,
     pub title: Option<String>,
     pub data: Option<PathBuf>,
+    pub inline: Option<String>, // CSV text used in place of a 'data' file
     pub x: Option<String>,
     pub y: Option<String>,
     pub z: Option<String>, // For heatmaps
@@ -52,23 +58,73 @@ pub struct ChartConfig {
     pub users: Option<String>,
     pub percentage: Option<bool>,
     pub legend_position: Option<LegendPosition>,
+    pub mark_extremes: Option<bool>, // For line/bar/scatter charts - annotate max/min points
+    pub baseline: Option<RetentionBaseline>, // For retention charts - denominator for each cohort
+    pub color_scale: Option<ColorScale>, // For heatmap/retention charts - how values map to colors
+    pub mean_line: Option<bool>,     // For bar charts - dashed reference line at the mean value
+    pub step_column: Option<String>, // For funnel charts - raw event column holding step names
+    pub user_column: Option<String>, // For funnel charts - user id column counted per step
+    pub stack_order: Option<Vec<String>>, // For stacked area/bar charts - bottom-to-top group order
+    pub log_x: Option<bool>,         // For scatter charts - logarithmic x-axis
+    pub logo: Option<PathBuf>,       // PNG composited into a corner of the canvas
+    pub logo_position: Option<LogoPosition>, // Corner for the logo [default: bottom-right]
+    #[serde(alias = "number_format")]
+    pub y_format: Option<NumberFormat>, // For line/area/bar/scatter charts - y-axis label format
+    pub y_lower: Option<String>,     // For line charts - lower bound of a shaded band
+    pub y_upper: Option<String>,     // For line charts - upper bound of a shaded band
+    pub series: Option<Vec<SeriesSpec>>, // For bar charts - aggregated series drawn as bars + lines
+    pub color_mode: Option<ColorMode>, // How group colors are picked from the palette [default: index]
+    pub labels: Option<HashMap<String, String>>, // Display labels for columns, used as axis titles
+    pub margins: Option<bool>,         // For heatmap charts - row/column total bars along the edges
+    pub trend_type: Option<TrendType>, // For scatter charts - fitted trend curve: linear, poly2, loess
+    pub x2: Option<String>, // For bar and line charts - outer x category drawn as brackets under the x labels
+    pub y_axis_side: Option<YAxisSide>, // Side of the plot for y-axis labels: left, right
+    pub join: Option<JoinConfig>, // Lookup source from data.sources joined onto the chart data
+    pub nan_policy: Option<NanPolicy>, // NaN and infinite values in plotted columns: drop, zero, clamp
+    pub legend_ratio: Option<f64>,     // Fraction of the canvas given to the legend (0.05-0.5)
+    pub growth: Option<bool>, // For line, area and bar charts - plot period-over-period % change of y
+    pub label_max_len: Option<usize>, // For bar charts - truncate x category labels longer than this, ending in an ellipsis
+    pub background: Option<String>,   // Canvas color as #rrggbb, or "transparent" [default: theme]
+    pub ys: Option<Vec<String>>, // For line and area charts - wide columns drawn as one series each (instead of y + group_by)
+    pub x_ticks: Option<usize>,  // Maximum number of x-axis tick labels [default: plotters picks]
+    pub y_ticks: Option<usize>,  // Maximum number of y-axis tick labels [default: plotters picks]
+    pub average_row: Option<bool>, // For retention charts - add a row below the cohorts with the mean retention per period
+    pub quality: Option<Quality>, // PNG rendering quality: fast, normal, high (2x supersampled) [default: normal]
+    pub date_from: Option<String>, // Keep rows whose x date is on or after this ISO date
+    pub date_to: Option<String>,  // Keep rows whose x date is on or before this ISO date
+    pub highlight_dropoff: Option<bool>, // For funnel charts - color steps by their loss from the previous step instead of the palette
+    pub legend_columns: Option<usize>, // Lay legend items out in this many columns, filling rows left to right [default: 1]
+    pub log_scale: Option<bool>, // For line, area, bar and scatter charts - logarithmic y-axis
+    pub survival: Option<bool>, // For retention charts - draw a survival curve per cohort instead of the matrix
+    pub endpoint_labels: Option<bool>, // For line charts - label each series' first and last value
+    pub webp_quality: Option<u8>, // WebP encoding quality, 0-100, where 100 is lossless [default: 80]
+    pub data_labels: Option<bool>, // For bar and stacked bar charts - write each bar's value on it, plus stack totals
+    pub hide_series: Option<Vec<String>>, // For grouped charts - group values (or area `ys` columns) left out of the plot and legend
+    pub null_label: Option<String>, // Shown for missing x, group_by and x2 category values [default: "(none)"]
+    pub rolling_band: Option<RollingBandConfig>, // For line charts - rolling mean line inside a band of ±k rolling standard deviations
+    pub trendline: Option<bool>, // For scatter charts - least squares line with its slope, intercept and R² printed
+    pub format_sizes: Option<HashMap<OutputFormat, [u32; 2]>>, // [width, height] per output format, in place of width/height
+    pub colors: Option<Vec<String>>, // Hex colors (#1f77b4) used for series in place of the theme palette
+    pub y_min: Option<f64>, // For line, area, bar and scatter charts - fixed bottom of the y-axis
+    pub y_max: Option<f64>, // For line, area, bar and scatter charts - fixed top of the y-axis
+    pub donut: Option<bool>, // For pie charts - leave the center hollow
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct FilterConfig {
     pub include: Option<HashMap<String, FilterValue>>,
     pub exclude: Option<HashMap<String, FilterValue>>,
     pub expression: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(untagged)]
 pub enum FilterValue {
     Single(String),
     Multiple(Vec<String>),
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SortConfig {
     pub column: String,
     pub ascending: Option<bool>,
@@ -85,6 +141,8 @@ pub enum ChartType {
     Scatter,
     Funnel,
     Retention,
+    Pie,
+    Box,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -98,6 +156,66 @@ pub enum AggregationType {
     Max,
 }
 
+impl AggregationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AggregationType::Sum => "sum",
+            AggregationType::Count => "count",
+            AggregationType::Mean => "mean",
+            AggregationType::Median => "median",
+            AggregationType::Min => "min",
+            AggregationType::Max => "max",
+        }
+    }
+}
+
+/// Join of a named lookup source onto a chart's data, e.g. channel -> category
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct JoinConfig {
+    /// Name of a source in `data.sources`
+    pub source: String,
+    /// Key column in the chart's data
+    pub left_on: String,
+    /// Key column in the lookup source [default: left_on]
+    pub right_on: Option<String>,
+    pub how: Option<JoinHow>,
+}
+
+/// A rolling mean drawn over a line, shaded `k` rolling standard deviations either side
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RollingBandConfig {
+    /// Points in each window, ending at the point itself
+    pub window: usize,
+    /// Half-width of the band in standard deviations [default: 2]
+    pub k: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum JoinHow {
+    /// Keep every chart row, with empty lookup columns where no key matches
+    #[default]
+    Left,
+    /// Keep only rows whose key appears in both
+    Inner,
+    /// Keep rows from both sides
+    Outer,
+}
+
+/// One aggregated value column of a combo chart
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct SeriesSpec {
+    pub column: String,
+    pub agg: AggregationType,
+}
+
+impl SeriesSpec {
+    /// Name of the aggregated column, e.g. "revenue_sum"
+    pub fn output_column(&self) -> String {
+        format!("{}_{}", self.column, self.agg.as_str())
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Theme {
@@ -105,12 +223,27 @@ pub enum Theme {
     Dark,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Png,
     Svg,
     Pdf,
+    Webp,
+    Html,
+}
+
+impl OutputFormat {
+    /// File extension for rendered charts, without the dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Svg => "svg",
+            OutputFormat::Pdf => "pdf",
+            OutputFormat::Webp => "webp",
+            OutputFormat::Html => "html",
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
@@ -139,6 +272,157 @@ pub enum ColorMap {
     Greens,
 }
 
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum YAxisSide {
+    #[default]
+    Left,
+    Right,
+}
+
+/// What happens to NaN and infinite values in the plotted float columns
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NanPolicy {
+    /// Drop the rows holding them
+    #[default]
+    Drop,
+    /// Replace them with 0
+    Zero,
+    /// Clamp infinities to the column's finite min/max; NaN rows are dropped
+    Clamp,
+}
+
+/// Parse a `YYYY-MM-DD` date from the spec field `field`
+pub fn parse_iso_date(field: &str, value: &str) -> anyhow::Result<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+        anyhow::anyhow!(
+            "Invalid {} '{}': expected an ISO date like 2023-01-31",
+            field,
+            value
+        )
+    })
+}
+
+/// Canvas background parsed from a chart's `background` field
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Background {
+    /// Solid color as red, green, blue
+    Color(u8, u8, u8),
+    /// No fill; PNG output gains an alpha channel
+    Transparent,
+}
+
+impl Background {
+    /// Parse `#rrggbb`, `#rgb` or `transparent`
+    pub fn parse(value: &str) -> anyhow::Result<Self> {
+        if value.eq_ignore_ascii_case("transparent") {
+            return Ok(Background::Transparent);
+        }
+
+        let color = crate::render::styling::parse_hex_color(value).map_err(|_| {
+            anyhow::anyhow!(
+                "Invalid background '{}': expected a hex color like #1a2b3c or 'transparent'",
+                value
+            )
+        })?;
+        Ok(Background::Color(color.0, color.1, color.2))
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TrendType {
+    /// Straight least-squares line
+    Linear,
+    /// Least-squares quadratic
+    Poly2,
+    /// Local regression that follows the shape of the data
+    Loess,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum NumberFormat {
+    /// Plain numbers, e.g. 1234.5
+    Plain,
+    /// Thousands separators, e.g. 1,234.5
+    Thousands,
+    /// SI suffixes, e.g. 1.2k, 3.4M
+    #[serde(alias = "compact")]
+    #[value(alias = "compact")]
+    SiCompact,
+    /// Percentages, e.g. 25%
+    Percent,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Colors follow each group's position among the sorted group values
+    #[default]
+    Index,
+    /// Colors derive from a stable hash of the group name, so a group keeps its color across charts
+    Hash,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogoPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScale {
+    /// Position proportional to the value
+    #[default]
+    Linear,
+    /// Position by rank, spreading colors evenly across the data
+    Quantile,
+    /// Position proportional to the log of the value
+    Log,
+}
+
+/// How carefully PNG output is rasterized
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Quality {
+    /// Drawn straight onto the canvas, with hard pixel edges
+    Fast,
+    /// Same as `fast` today; the default
+    #[default]
+    Normal,
+    /// Drawn at twice the size and scaled down, smoothing edges and text
+    High,
+}
+
+impl Quality {
+    /// How many times larger than the output the chart is drawn before downscaling
+    pub fn supersampling(&self) -> u32 {
+        match self {
+            Quality::Fast | Quality::Normal => 1,
+            Quality::High => 2,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RetentionBaseline {
+    /// Users in period 0 of the cohort
+    #[default]
+    PeriodZero,
+    /// Users in the earliest period present for the cohort
+    FirstPresent,
+    /// Largest user count seen in the cohort
+    CohortSize,
+}
+
 impl ChartSpec {
     pub fn from_yaml(content: &str) -> anyhow::Result<Self> {
         let spec: Self = serde_yaml::from_str(content)?;
@@ -165,9 +449,204 @@ impl ChartSpec {
 
         Ok(())
     }
+
+    /// Collect lint warnings for fields that have no effect or repeat a default
+    pub fn lint(&self) -> Vec<String> {
+        let default_data = self.data.as_ref().and_then(|d| d.default.as_ref());
+        let mut warnings = Vec::new();
+
+        for (index, chart) in self.charts.iter().enumerate() {
+            let label = match &chart.title {
+                Some(title) => format!("Chart {} ('{}')", index + 1, title),
+                None => format!("Chart {}", index + 1),
+            };
+            for message in chart.lint(default_data) {
+                warnings.push(format!("{}: {}", label, message));
+            }
+        }
+
+        warnings
+    }
+}
+
+impl ChartType {
+    /// Type-specific fields that have an effect on this chart type
+    fn specific_fields(&self) -> &'static [&'static str] {
+        match self {
+            ChartType::Line => &[
+                "x",
+                "y",
+                "ys",
+                "group_by",
+                "hide_series",
+                "null_label",
+                "mark_extremes",
+                "y_format",
+                "y_lower",
+                "y_upper",
+                "x2",
+                "growth",
+                "log_scale",
+                "y_min",
+                "y_max",
+                "endpoint_labels",
+                "rolling_band",
+                "x_ticks",
+                "y_ticks",
+            ],
+            ChartType::Area => &[
+                "x",
+                "y",
+                "group_by",
+                "hide_series",
+                "null_label",
+                "stacked",
+                "normalize",
+                "stack_order",
+                "y_format",
+                "growth",
+                "ys",
+                "log_scale",
+                "y_min",
+                "y_max",
+                "x_ticks",
+                "y_ticks",
+            ],
+            ChartType::Bar => &[
+                "x",
+                "y",
+                "group_by",
+                "hide_series",
+                "null_label",
+                "stacked",
+                "horizontal",
+                "mark_extremes",
+                "mean_line",
+                "y_format",
+                "series",
+                "x2",
+                "growth",
+                "label_max_len",
+                "log_scale",
+                "y_min",
+                "y_max",
+                "x_ticks",
+                "y_ticks",
+                "data_labels",
+            ],
+            ChartType::BarStacked => &[
+                "x",
+                "y",
+                "group_by",
+                "hide_series",
+                "null_label",
+                "horizontal",
+                "stack_order",
+                "data_labels",
+                "x_ticks",
+                "y_ticks",
+            ],
+            ChartType::Heatmap => &[
+                "x",
+                "y",
+                "z",
+                "bins",
+                "colormap",
+                "color_scale",
+                "margins",
+                "null_label",
+            ],
+            ChartType::Scatter => &[
+                "x",
+                "y",
+                "group_by",
+                "hide_series",
+                "null_label",
+                "mark_extremes",
+                "log_x",
+                "log_scale",
+                "y_min",
+                "y_max",
+                "y_format",
+                "trend_type",
+                "trendline",
+                "x_ticks",
+                "y_ticks",
+            ],
+            ChartType::Funnel => &[
+                "steps",
+                "step_order",
+                "value_labels",
+                "highlight_dropoff",
+                "values",
+                "conversion_rates",
+                "step_column",
+                "user_column",
+                "y_format",
+            ],
+            ChartType::Retention => &[
+                "cohort_date",
+                "period_number",
+                "users",
+                "percentage",
+                "baseline",
+                "color_scale",
+                "average_row",
+                "survival",
+            ],
+            ChartType::Pie => &["x", "y", "null_label", "donut"],
+            ChartType::Box => &[
+                "y",
+                "group_by",
+                "hide_series",
+                "null_label",
+                "y_format",
+                "log_scale",
+                "y_min",
+                "y_max",
+                "y_ticks",
+            ],
+        }
+    }
 }
 
 impl ChartConfig {
+    /// Axis title for a column: its display label if one is set, otherwise the column name
+    pub fn axis_label(&self, column: &str) -> String {
+        self.labels
+            .as_ref()
+            .and_then(|labels| labels.get(column))
+            .cloned()
+            .unwrap_or_else(|| column.to_string())
+    }
+
+    /// Whether a series - a `group_by` value or `ys` column - is drawn: not when it is listed in
+    /// `hide_series`
+    pub fn shows_series(&self, name: &str) -> bool {
+        !self
+            .hide_series
+            .as_ref()
+            .is_some_and(|hidden| hidden.iter().any(|h| h == name))
+    }
+
+    /// Whether the chart plots row counts per x: `agg: count` with no `y` or `ys` to count
+    pub fn counts_rows(&self) -> bool {
+        self.y.is_none()
+            && self.ys.is_none()
+            && self.series.is_none()
+            && self.agg == Some(AggregationType::Count)
+    }
+
+    /// Copy of this config with label hints filled in for columns that have no label yet
+    pub fn with_label_hints(&self, hints: &HashMap<String, String>) -> Self {
+        let mut labels = hints.clone();
+        labels.extend(self.labels.clone().unwrap_or_default());
+        Self {
+            labels: Some(labels),
+            ..self.clone()
+        }
+    }
+
     pub fn validate(&self) -> anyhow::Result<()> {
         // Validate required fields based on chart type
         match self.chart_type {
@@ -180,8 +659,19 @@ impl ChartConfig {
                 if self.steps.is_none() {
                     anyhow::bail!("Funnel charts require a 'steps' field with step names");
                 }
-                if self.values.is_none() {
-                    anyhow::bail!("Funnel charts require a 'values' field for step values");
+                match (&self.step_column, &self.user_column) {
+                    (Some(_), Some(_)) => {}
+                    (Some(_), None) if self.values.is_some() => {}
+                    (Some(_), None) => anyhow::bail!(
+                        "Funnel charts with a 'step_column' require 'user_column' (raw events) or 'values' (one row per step)"
+                    ),
+                    (None, Some(_)) => anyhow::bail!(
+                        "Funnel charts computed from events require both 'step_column' and 'user_column'"
+                    ),
+                    (None, None) if self.values.is_none() => anyhow::bail!(
+                        "Funnel charts require a 'values' field for step values, or 'step_column' and 'user_column' for raw events"
+                    ),
+                    (None, None) => {}
                 }
             }
             ChartType::Retention => {
@@ -195,17 +685,41 @@ impl ChartConfig {
                     anyhow::bail!("Retention charts require a 'users' field");
                 }
             }
+            ChartType::Box => {
+                if self.group_by.is_none() {
+                    anyhow::bail!("Box charts require a 'group_by' field for the categories");
+                }
+                if self.y.is_none() {
+                    anyhow::bail!("Box charts require a 'y' field for the values");
+                }
+                if self.agg.is_some() {
+                    anyhow::bail!(
+                        "Box charts summarize every row of 'y'; remove 'agg' so the rows are not aggregated first"
+                    );
+                }
+            }
             _ => {
                 // Line, Area, Bar, BarStacked, Scatter charts all require x and y
                 if self.x.is_none() {
                     anyhow::bail!("{:?} charts require an 'x' field", self.chart_type);
                 }
-                if self.y.is_none() {
-                    anyhow::bail!("{:?} charts require a 'y' field", self.chart_type);
+                if self.y.is_none() && self.ys.is_none() && !self.counts_rows() {
+                    anyhow::bail!(
+                        "{:?} charts require a 'y' field, unless 'agg: count' counts rows per x",
+                        self.chart_type
+                    );
                 }
             }
         }
 
+        if self.data.is_some() && self.inline.is_some() {
+            anyhow::bail!("Use either 'data' or 'inline', not both");
+        }
+
+        if self.y_lower.is_some() != self.y_upper.is_some() {
+            anyhow::bail!("Bands require both 'y_lower' and 'y_upper' fields");
+        }
+
         // Validate dimensions
         if let Some(width) = self.width
             && !(100..=10000).contains(&width)
@@ -225,11 +739,122 @@ impl ChartConfig {
             );
         }
 
-        // Validate scale
-        if let Some(scale) = self.scale
-            && (scale <= 0.0 || scale > 10.0)
+        if let Some(sizes) = &self.format_sizes {
+            for (format, [width, height]) in sizes {
+                if !(100..=10000).contains(width) || !(100..=10000).contains(height) {
+                    anyhow::bail!(
+                        "format_sizes.{} must be between 100 and 10000 pixels each way, got {}x{}",
+                        format.extension(),
+                        width,
+                        height
+                    );
+                }
+            }
+        }
+
+        // Validate scale, and the output size it leads to
+        if let Some(scale) = self.scale {
+            if !(0.1..=10.0).contains(&scale) {
+                anyhow::bail!("Chart scale must be between 0.1 and 10.0, got {}", scale);
+            }
+            let (width, height) = crate::render::chart_dimensions(self);
+            if !(100..=10000).contains(&width) || !(100..=10000).contains(&height) {
+                anyhow::bail!(
+                    "Scaled chart size must be between 100 and 10000 pixels each way, got {}x{} \
+                     (scale {})",
+                    width,
+                    height,
+                    scale
+                );
+            }
+        }
+
+        // Validate WebP quality
+        if let Some(quality) = self.webp_quality
+            && quality > 100
+        {
+            anyhow::bail!("WebP quality must be between 0 and 100, got {}", quality);
+        }
+
+        // A rolling standard deviation needs at least two points
+        if let Some(band) = &self.rolling_band {
+            if band.window < 2 {
+                anyhow::bail!(
+                    "rolling_band window must be at least 2, got {}",
+                    band.window
+                );
+            }
+            if let Some(k) = band.k
+                && k <= 0.0
+            {
+                anyhow::bail!("rolling_band k must be greater than 0, got {}", k);
+            }
+        }
+
+        // Validate the legend share of the canvas
+        if let Some(ratio) = self.legend_ratio
+            && !(0.05..=0.5).contains(&ratio)
+        {
+            anyhow::bail!("Legend ratio must be between 0.05 and 0.5, got {}", ratio);
+        }
+
+        if let (Some(y_min), Some(y_max)) = (self.y_min, self.y_max)
+            && y_min >= y_max
+        {
+            anyhow::bail!("y_min ({}) must be less than y_max ({})", y_min, y_max);
+        }
+        if self.log_scale == Some(true) {
+            for (field, value) in [("y_min", self.y_min), ("y_max", self.y_max)] {
+                if let Some(value) = value
+                    && value <= 0.0
+                {
+                    anyhow::bail!(
+                        "{} must be positive on a log_scale axis, got {}",
+                        field,
+                        value
+                    );
+                }
+            }
+        }
+
+        if self.log_scale == Some(true) && self.normalize == Some(true) {
+            anyhow::bail!(
+                "log_scale can't be combined with normalize: percentage shares are read on a linear axis"
+            );
+        }
+
+        if self.legend_columns == Some(0) {
+            anyhow::bail!("legend_columns must be at least 1");
+        }
+
+        if let Some(colors) = &self.colors {
+            if colors.is_empty() {
+                anyhow::bail!("colors must list at least one color");
+            }
+            for color in colors {
+                crate::render::styling::parse_hex_color(color)?;
+            }
+        }
+
+        if let Some(background) = &self.background {
+            Background::parse(background)?;
+        }
+
+        // Date bounds must be real dates and leave a non-empty range
+        let date_from = self
+            .date_from
+            .as_deref()
+            .map(|date| parse_iso_date("date_from", date))
+            .transpose()?;
+        let date_to = self
+            .date_to
+            .as_deref()
+            .map(|date| parse_iso_date("date_to", date))
+            .transpose()?;
+        if let (Some(from), Some(to)) = (date_from, date_to)
+            && from > to
         {
-            anyhow::bail!("Chart scale must be between 0.1 and 10.0, got {}", scale);
+            anyhow::bail!("date_from ({}) is after date_to ({})", from, to);
         }
 
         // Validate bins for heatmaps
@@ -239,6 +864,67 @@ impl ChartConfig {
             anyhow::bail!("Heatmap bins must be between 2 and 100, got {}", bins);
         }
 
+        if let Some(ys) = &self.ys {
+            if !matches!(self.chart_type, ChartType::Line | ChartType::Area) {
+                anyhow::bail!("'ys' is only supported for line and area charts");
+            }
+            if ys.is_empty() {
+                anyhow::bail!("'ys' must list at least one column");
+            }
+            if self.group_by.is_some() {
+                anyhow::bail!(
+                    "'ys' cannot be combined with 'group_by'; each column is already a series"
+                );
+            }
+            // Wide series are drawn on a plain x axis, each from its own column alone
+            let unsupported = [
+                ("x2", self.x2.is_some()),
+                (
+                    "y_lower/y_upper",
+                    self.y_lower.is_some() || self.y_upper.is_some(),
+                ),
+                ("growth", self.growth == Some(true)),
+            ];
+            if let Some((field, _)) = unsupported.iter().find(|(_, set)| *set) {
+                anyhow::bail!("'ys' cannot be combined with '{}'", field);
+            }
+        }
+
+        if let Some(series) = &self.series {
+            if self.chart_type != ChartType::Bar {
+                anyhow::bail!("'series' is only supported for bar charts");
+            }
+            if series.is_empty() {
+                anyhow::bail!("'series' must list at least one column");
+            }
+        }
+
+        if self.growth == Some(true)
+            && !matches!(
+                self.chart_type,
+                ChartType::Line | ChartType::Area | ChartType::Bar
+            )
+        {
+            anyhow::bail!("'growth' is only supported for line, area and bar charts");
+        }
+
+        if self.x_ticks == Some(0) || self.y_ticks == Some(0) {
+            anyhow::bail!("'x_ticks' and 'y_ticks' must be at least 1");
+        }
+
+        if self.label_max_len == Some(0) {
+            anyhow::bail!("'label_max_len' must be at least 1");
+        }
+
+        if self.x2.is_some() {
+            if !matches!(self.chart_type, ChartType::Bar | ChartType::Line) {
+                anyhow::bail!("'x2' is only supported for bar and line charts");
+            }
+            if self.group_by.is_some() {
+                anyhow::bail!("'x2' cannot be combined with 'group_by'");
+            }
+        }
+
         // Validate filter expressions
         if let Some(filter) = &self.filter {
             self.validate_filter(filter)?;
@@ -247,6 +933,97 @@ impl ChartConfig {
         Ok(())
     }
 
+    /// Report fields that have no effect for this chart type or repeat a default
+    pub fn lint(&self, default_data: Option<&PathBuf>) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        let allowed = self.chart_type.specific_fields();
+        for (field, is_set) in self.type_specific_fields() {
+            if is_set && !allowed.contains(&field) {
+                warnings.push(format!(
+                    "'{}' has no effect on {:?} charts",
+                    field, self.chart_type
+                ));
+            }
+        }
+
+        if let (Some(data), Some(default)) = (&self.data, default_data)
+            && data == default
+        {
+            warnings.push("'data' duplicates the spec's default data source".to_string());
+        }
+        if self.theme == Some(Theme::Light) {
+            warnings.push("'theme: light' is already the default".to_string());
+        }
+        if self.format == Some(OutputFormat::Png) {
+            warnings.push("'format: png' is already the default".to_string());
+        }
+        if self.scale == Some(1.0) {
+            warnings.push("'scale: 1.0' is already the default".to_string());
+        }
+        if self.legend_position == Some(LegendPosition::Right) {
+            warnings.push("'legend_position: right' is already the default".to_string());
+        }
+
+        warnings
+    }
+
+    fn type_specific_fields(&self) -> Vec<(&'static str, bool)> {
+        vec![
+            ("x", self.x.is_some()),
+            ("y", self.y.is_some()),
+            ("z", self.z.is_some()),
+            ("group_by", self.group_by.is_some()),
+            ("stacked", self.stacked.is_some()),
+            ("horizontal", self.horizontal.is_some()),
+            ("normalize", self.normalize.is_some()),
+            ("bins", self.bins.is_some()),
+            ("colormap", self.colormap.is_some()),
+            ("steps", self.steps.is_some()),
+            ("step_order", self.step_order.is_some()),
+            ("value_labels", self.value_labels.is_some()),
+            ("highlight_dropoff", self.highlight_dropoff.is_some()),
+            ("values", self.values.is_some()),
+            ("conversion_rates", self.conversion_rates.is_some()),
+            ("cohort_date", self.cohort_date.is_some()),
+            ("period_number", self.period_number.is_some()),
+            ("users", self.users.is_some()),
+            ("percentage", self.percentage.is_some()),
+            ("mark_extremes", self.mark_extremes.is_some()),
+            ("baseline", self.baseline.is_some()),
+            ("color_scale", self.color_scale.is_some()),
+            ("mean_line", self.mean_line.is_some()),
+            ("step_column", self.step_column.is_some()),
+            ("user_column", self.user_column.is_some()),
+            ("stack_order", self.stack_order.is_some()),
+            ("log_x", self.log_x.is_some()),
+            ("log_scale", self.log_scale.is_some()),
+            ("y_format", self.y_format.is_some()),
+            ("y_lower", self.y_lower.is_some()),
+            ("y_upper", self.y_upper.is_some()),
+            ("series", self.series.is_some()),
+            ("margins", self.margins.is_some()),
+            ("trend_type", self.trend_type.is_some()),
+            ("x2", self.x2.is_some()),
+            ("growth", self.growth.is_some()),
+            ("label_max_len", self.label_max_len.is_some()),
+            ("ys", self.ys.is_some()),
+            ("x_ticks", self.x_ticks.is_some()),
+            ("y_ticks", self.y_ticks.is_some()),
+            ("average_row", self.average_row.is_some()),
+            ("survival", self.survival.is_some()),
+            ("endpoint_labels", self.endpoint_labels.is_some()),
+            ("data_labels", self.data_labels.is_some()),
+            ("hide_series", self.hide_series.is_some()),
+            ("null_label", self.null_label.is_some()),
+            ("rolling_band", self.rolling_band.is_some()),
+            ("trendline", self.trendline.is_some()),
+            ("y_min", self.y_min.is_some()),
+            ("y_max", self.y_max.is_some()),
+            ("donut", self.donut.is_some()),
+        ]
+    }
+
     fn validate_filter(&self, filter: &FilterConfig) -> anyhow::Result<()> {
         // Validate that we have at least one filter condition
         let has_include = filter.include.as_ref().is_some_and(|f| !f.is_empty());
@@ -629,6 +1406,30 @@ charts: []
         assert!(error_msg.contains("scale must be between 0.1 and 10.0"));
     }
 
+    #[test]
+    fn test_scale_validation_checks_the_scaled_size() {
+        let chart = ChartConfig {
+            chart_type: ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            width: Some(10000),
+            scale: Some(10.0),
+            ..Default::default()
+        };
+
+        let error_msg = chart.validate().unwrap_err().to_string();
+        assert!(error_msg.contains("Scaled chart size must be between 100 and 10000"));
+
+        // Shrinking a canvas below 100 pixels is rejected the same way
+        let chart = ChartConfig {
+            width: Some(400),
+            height: Some(300),
+            scale: Some(0.2),
+            ..chart
+        };
+        assert!(chart.validate().is_err());
+    }
+
     #[test]
     fn test_bins_validation_too_small() {
         let chart = ChartConfig {
@@ -769,6 +1570,163 @@ charts: []
         assert!(error_msg.contains("expression cannot be empty"));
     }
 
+    #[test]
+    fn test_lint_bins_on_line_chart() {
+        let yaml_content = r#"
+charts:
+  - type: line
+    title: "Users"
+    x: date
+    y: users
+    bins: 10
+"#;
+
+        let spec = ChartSpec::from_yaml(yaml_content).unwrap();
+        let warnings = spec.lint();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Chart 1 ('Users')"));
+        assert!(warnings[0].contains("'bins' has no effect on Line charts"));
+    }
+
+    #[test]
+    fn test_lint_redundant_defaults() {
+        let yaml_content = r#"
+data:
+  default: users.csv
+charts:
+  - type: bar
+    data: users.csv
+    x: channel
+    y: users
+    theme: light
+"#;
+
+        let spec = ChartSpec::from_yaml(yaml_content).unwrap();
+        let warnings = spec.lint();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().any(|w| w.contains("default data source")));
+        assert!(warnings.iter().any(|w| w.contains("'theme: light'")));
+    }
+
+    #[test]
+    fn test_lint_clean_spec() {
+        let chart = ChartConfig {
+            chart_type: ChartType::Heatmap,
+            x: Some("hour".to_string()),
+            y: Some("day".to_string()),
+            z: Some("value".to_string()),
+            bins: Some(10),
+            ..Default::default()
+        };
+
+        assert!(chart.lint(None).is_empty());
+    }
+
+    #[test]
+    fn test_date_bounds_validation() {
+        let config = ChartConfig {
+            chart_type: ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            date_from: Some("2023-02-30".to_string()),
+            ..Default::default()
+        };
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("Invalid date_from '2023-02-30'"));
+
+        let reversed = ChartConfig {
+            date_from: Some("2023-03-01".to_string()),
+            date_to: Some("2023-02-01".to_string()),
+            ..config
+        };
+        let error = reversed.validate().unwrap_err().to_string();
+        assert!(error.contains("date_from (2023-03-01) is after date_to (2023-02-01)"));
+    }
+
+    #[test]
+    fn test_y_min_must_be_below_y_max() {
+        let config = ChartConfig {
+            chart_type: ChartType::Line,
+            x: Some("date".to_string()),
+            y: Some("users".to_string()),
+            y_min: Some(100.0),
+            y_max: Some(100.0),
+            ..Default::default()
+        };
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("y_min (100) must be less than y_max (100)"));
+    }
+
+    #[test]
+    fn test_log_scale_rejects_normalize() {
+        let config = ChartConfig {
+            chart_type: ChartType::Area,
+            x: Some("date".to_string()),
+            y: Some("revenue".to_string()),
+            log_scale: Some(true),
+            normalize: Some(true),
+            ..Default::default()
+        };
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("log_scale can't be combined with normalize"));
+
+        let linear = ChartConfig {
+            normalize: Some(false),
+            ..config
+        };
+        assert!(linear.validate().is_ok());
+    }
+
+    #[test]
+    fn test_ys_rejects_fields_wide_series_ignore() {
+        let config = ChartConfig {
+            chart_type: ChartType::Line,
+            x: Some("date".to_string()),
+            ys: Some(vec!["sessions".to_string(), "users".to_string()]),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let with_growth = ChartConfig {
+            growth: Some(true),
+            ..config.clone()
+        };
+        let error = with_growth.validate().unwrap_err().to_string();
+        assert!(error.contains("'ys' cannot be combined with 'growth'"));
+
+        let with_band = ChartConfig {
+            y_lower: Some("low".to_string()),
+            y_upper: Some("high".to_string()),
+            ..config.clone()
+        };
+        assert!(with_band.validate().is_err());
+
+        let with_x2 = ChartConfig {
+            x2: Some("year".to_string()),
+            ..config
+        };
+        assert!(with_x2.validate().is_err());
+    }
+
+    #[test]
+    fn test_background_parsing() {
+        assert_eq!(
+            Background::parse("#1A2b3c").unwrap(),
+            Background::Color(0x1a, 0x2b, 0x3c)
+        );
+        assert_eq!(
+            Background::parse("#f80").unwrap(),
+            Background::Color(0xff, 0x88, 0x00)
+        );
+        assert_eq!(
+            Background::parse("Transparent").unwrap(),
+            Background::Transparent
+        );
+        for invalid in ["1a2b3c", "#12345", "#ggg", "blue"] {
+            assert!(Background::parse(invalid).is_err(), "accepted {}", invalid);
+        }
+    }
+
     #[test]
     fn test_enum_serialization() {
         // Test ChartType serialization
@@ -844,6 +1802,56 @@ impl Default for ChartConfig {
             users: None,
             percentage: None,
             legend_position: None,
+            mark_extremes: None,
+            baseline: None,
+            color_scale: None,
+            mean_line: None,
+            step_column: None,
+            user_column: None,
+            stack_order: None,
+            log_x: None,
+            logo: None,
+            logo_position: None,
+            y_format: None,
+            y_lower: None,
+            y_upper: None,
+            series: None,
+            color_mode: None,
+            labels: None,
+            margins: None,
+            trend_type: None,
+            x2: None,
+            y_axis_side: None,
+            join: None,
+            nan_policy: None,
+            legend_ratio: None,
+            growth: None,
+            label_max_len: None,
+            background: None,
+            ys: None,
+            x_ticks: None,
+            y_ticks: None,
+            average_row: None,
+            inline: None,
+            quality: None,
+            date_from: None,
+            date_to: None,
+            highlight_dropoff: None,
+            legend_columns: None,
+            log_scale: None,
+            survival: None,
+            endpoint_labels: None,
+            webp_quality: None,
+            data_labels: None,
+            hide_series: None,
+            null_label: None,
+            rolling_band: None,
+            trendline: None,
+            format_sizes: None,
+            colors: None,
+            y_min: None,
+            y_max: None,
+            donut: None,
         }
     }
 }
@@ -0,0 +1,40 @@
+//! Central warning emission so `--strict` can promote warnings to errors
+use anyhow::Result;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable strict mode for the rest of the process
+pub fn set_strict(strict: bool) {
+    STRICT.store(strict, Ordering::Relaxed);
+}
+
+pub fn is_strict() -> bool {
+    STRICT.load(Ordering::Relaxed)
+}
+
+/// Print a warning, or return it as an error in strict mode
+pub fn warn(message: impl Display) -> Result<()> {
+    if is_strict() {
+        anyhow::bail!("{} (warning treated as error in --strict mode)", message);
+    }
+    eprintln!("⚠️  Warning: {}", message);
+    Ok(())
+}
+
+/// Print a suggestion; unlike a warning it never fails a `--strict` run
+pub fn advise(message: impl Display) {
+    eprintln!("💡 Hint: {}", message);
+}
+
+/// Warn when a chart only draws the first `cap` of `rows` rows
+pub fn warn_row_cap(chart: &str, rows: usize, cap: usize) -> Result<()> {
+    if rows > cap {
+        warn(format!(
+            "{} chart only draws the first {} of {} rows",
+            chart, cap, rows
+        ))?;
+    }
+    Ok(())
+}
@@ -100,6 +100,457 @@ fn test_cli_bar_chart_basic() {
     assert!(output_path.exists(), "Output file was not created");
 }
 
+#[test]
+fn test_cli_strict_mode_fails_on_warning() {
+    let test_dir = create_test_dir();
+
+    // 25 categories exceeds the bar chart's 20-bar cap, which emits a warning
+    let mut csv_content = String::from("category,value");
+    for i in 0..25 {
+        csv_content.push_str(&format!("\ncat{:02},{}", i, 100 + i));
+    }
+    create_test_csv(test_dir.path(), "test.csv", &csv_content);
+
+    let run_bar = |strict: bool, output_name: &str| {
+        let output_path = test_dir.path().join(output_name);
+        let mut args = vec![
+            "run".to_string(),
+            "--".to_string(),
+            "bar".to_string(),
+            "--input".to_string(),
+            test_dir
+                .path()
+                .join("test.csv")
+                .to_str()
+                .unwrap()
+                .to_string(),
+            "--x".to_string(),
+            "category".to_string(),
+            "--y".to_string(),
+            "value".to_string(),
+            "--out".to_string(),
+            output_path.to_str().unwrap().to_string(),
+        ];
+        if strict {
+            args.push("--strict".to_string());
+        }
+        std::process::Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    let lenient = run_bar(false, "lenient.png");
+    assert!(
+        lenient.status.success(),
+        "Warnings should not fail by default"
+    );
+    let stderr = String::from_utf8_lossy(&lenient.stderr);
+    assert!(stderr.contains("Warning"), "Row cap should emit a warning");
+
+    let strict = run_bar(true, "strict.png");
+    assert!(!strict.status.success(), "--strict should fail on warnings");
+    let stderr = String::from_utf8_lossy(&strict.stderr);
+    assert!(stderr.contains("--strict"), "Error should mention --strict");
+}
+
+#[test]
+fn test_cli_warns_on_count_of_aggregated_data() {
+    let test_dir = create_test_dir();
+
+    // One row per date: the totals have already been summed upstream
+    let csv_content = "date,users\n2023-01-01,100\n2023-01-02,150\n2023-01-03,120";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+    let output_path = test_dir.path().join("count.png");
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "bar",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "date",
+            "--y",
+            "users",
+            "--agg",
+            "count",
+            "--out",
+            output_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "Warnings should not fail by default"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("already aggregated"),
+        "Counting pre-aggregated data should warn, got: {}",
+        stderr
+    );
+}
+
+#[test]
+fn test_cli_number_format_compacts_y_labels() {
+    let test_dir = create_test_dir();
+    let csv_content = "channel,revenue\norganic,1500000\ndirect,500000";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+    let output_path = test_dir.path().join("revenue.svg");
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "--format",
+            "svg",
+            "bar",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "channel",
+            "--y",
+            "revenue",
+            "--number-format",
+            "si-compact",
+            "--out",
+            output_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let svg = fs::read_to_string(&output_path).unwrap();
+    assert!(svg.contains(">1M<"), "Labels should use SI suffixes");
+    assert!(!svg.contains(">1000000<"));
+}
+
+#[test]
+fn test_cli_line_draws_a_line_per_y_column() {
+    let test_dir = create_test_dir();
+    let csv_content =
+        "date,sessions,users\n2023-01-01,180,100\n2023-01-02,210,120\n2023-01-03,160,90";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+    let output_path = test_dir.path().join("traffic.svg");
+
+    let run = |y: &str| {
+        std::process::Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "--format",
+                "svg",
+                "line",
+                "--input",
+                test_dir.path().join("test.csv").to_str().unwrap(),
+                "--x",
+                "date",
+                "--y",
+                y,
+                "--out",
+                output_path.to_str().unwrap(),
+            ])
+            .output()
+            .expect("Failed to run CLI")
+    };
+
+    let output = run("sessions,users");
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let svg = fs::read_to_string(&output_path).unwrap();
+    assert!(svg.contains(">sessions<") && svg.contains(">users<"));
+
+    // Every listed column must exist
+    let output = run("sessions,visits");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Column 'visits' not found"));
+}
+
+#[test]
+fn test_cli_warns_when_grouping_by_the_x_column() {
+    let test_dir = create_test_dir();
+    let csv_content = "date,users\n2023-01-01,100\n2023-01-01,50\n2023-01-02,150";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+    let output_path = test_dir.path().join("grouped.png");
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "line",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "date",
+            "--y",
+            "users",
+            "--group",
+            "date",
+            "--agg",
+            "sum",
+            "--out",
+            output_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "Warnings should not fail by default"
+    );
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("group_by 'date' is the x column"),
+        "Grouping by x should warn, got: {}",
+        stderr
+    );
+    assert!(output_path.exists(), "Chart should still be drawn");
+}
+
+#[test]
+fn test_cli_emit_data_writes_series_json() {
+    let test_dir = create_test_dir();
+    let csv_content = "date,users\n2023-01-01,100\n2023-01-02,150\n2023-01-03,120";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+    let output_path = test_dir.path().join("chart.png");
+    let data_path = test_dir.path().join("chart.json");
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "line",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "date",
+            "--y",
+            "users",
+            "--out",
+            output_path.to_str().unwrap(),
+            "--emit-data",
+            data_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output_path.exists(), "Chart should still be rendered");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&data_path).unwrap()).unwrap();
+    let series = &json["series"][0];
+    assert_eq!(series["name"], "users");
+
+    // Aggregation may reorder rows, so compare (x, y) pairs
+    let xs = series["x"].as_array().unwrap();
+    let ys = series["y"].as_array().unwrap();
+    let mut points: Vec<(String, i64)> = xs
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| (x.as_str().unwrap().to_string(), y.as_i64().unwrap()))
+        .collect();
+    points.sort();
+    assert_eq!(
+        points,
+        vec![
+            ("2023-01-01".to_string(), 100),
+            ("2023-01-02".to_string(), 150),
+            ("2023-01-03".to_string(), 120),
+        ]
+    );
+    assert_eq!(json["y_axis"]["column"], "users");
+}
+
+#[test]
+fn test_cli_validate_only() {
+    let test_dir = create_test_dir();
+    let csv_content = "date,users,channel\n2023-01-01,100,organic\n2023-01-02,150,direct";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    let validate = |y_column: &str, output_name: &str| {
+        let output_path = test_dir.path().join(output_name);
+        let output = std::process::Command::new("cargo")
+            .args([
+                "run",
+                "--",
+                "line",
+                "--input",
+                test_dir.path().join("test.csv").to_str().unwrap(),
+                "--x",
+                "date",
+                "--y",
+                y_column,
+                "--out",
+                output_path.to_str().unwrap(),
+                "--validate-only",
+            ])
+            .output()
+            .expect("Failed to run CLI");
+        (output, output_path)
+    };
+
+    // A valid config succeeds without rendering anything
+    let (output, output_path) = validate("users", "valid.png");
+    assert!(output.status.success(), "Valid config should pass");
+    assert!(!output_path.exists(), "--validate-only should not render");
+
+    // A missing column fails, still without writing a file
+    let (output, output_path) = validate("missing_column", "invalid.png");
+    assert!(!output.status.success(), "Missing column should fail");
+    assert!(!output_path.exists(), "--validate-only should not render");
+}
+
+#[test]
+fn test_cli_legend_out_writes_separate_file() {
+    let test_dir = create_test_dir();
+    let csv_content = "channel,users\norganic,100\ndirect,150\nemail,80";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    let chart_path = test_dir.path().join("chart.png");
+    let legend_path = test_dir.path().join("legend.png");
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "bar",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "channel",
+            "--y",
+            "users",
+            "--out",
+            chart_path.to_str().unwrap(),
+            "--legend-out",
+            legend_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(chart_path.exists(), "Chart file should be created");
+    let legend_size = fs::metadata(&legend_path)
+        .expect("Legend file should be created")
+        .len();
+    assert!(legend_size > 0, "Legend file should not be empty");
+}
+
+#[test]
+fn test_cli_bar_overlay_draws_a_second_measure() {
+    let test_dir = create_test_dir();
+    let csv_content = "date,users,revenue\n2023-01-01,10,100\n2023-01-01,15,300\n2023-01-02,20,150";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+    let output_path = test_dir.path().join("overlay.png");
+    let data_path = test_dir.path().join("overlay.json");
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "bar",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "date",
+            "--y",
+            "users",
+            "--overlay",
+            "revenue:mean",
+            "--out",
+            output_path.to_str().unwrap(),
+            "--emit-data",
+            data_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output_path.exists(), "Overlay chart should be created");
+
+    // The bars keep `--agg`, and the overlay becomes a second series
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&data_path).unwrap()).unwrap();
+    let names: Vec<&str> = json["series"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|series| series["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["users_sum", "revenue_mean"]);
+}
+
+#[test]
+fn test_cli_bar_combo_series() {
+    let test_dir = create_test_dir();
+    let csv_content = "date,sessions,revenue\n2023-01-01,s1,10\n2023-01-01,s2,15\n2023-01-02,s3,20";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    let output_path = test_dir.path().join("combo.png");
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "bar",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "date",
+            "--y",
+            "sessions:count,revenue:sum",
+            "--out",
+            output_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(output_path.exists(), "Combo chart should be created");
+
+    // An unknown aggregation is rejected before rendering
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "bar",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "date",
+            "--y",
+            "sessions:total",
+        ])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(!output.status.success());
+}
+
 #[test]
 fn test_cli_render_spec_file() {
     let test_dir = create_test_dir();
@@ -123,13 +574,189 @@ charts:
     x: "channel"
     y: "users"
 "#,
-        test_dir.path().join("test.csv").to_str().unwrap(),
-        test_dir.path().join("test.csv").to_str().unwrap()
+        test_dir.path().join("test.csv").to_str().unwrap(),
+        test_dir.path().join("test.csv").to_str().unwrap()
+    );
+    create_test_spec(test_dir.path(), "test_spec.yaml", &spec_content);
+
+    // Test the render command - run from the project root, not the temp dir
+    let result = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "render",
+            "--spec",
+            test_dir.path().join("test_spec.yaml").to_str().unwrap(),
+            "--out",
+            test_dir.path().to_str().unwrap(),
+        ])
+        .output();
+
+    assert!(result.is_ok());
+    let output = result.unwrap();
+
+    if !output.status.success() {
+        println!("STDOUT: {}", String::from_utf8_lossy(&output.stdout));
+        println!("STDERR: {}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    assert!(output.status.success(), "CLI command failed");
+
+    // Check if output files were created
+    let output_files: Vec<_> = fs::read_dir(test_dir.path())
+        .unwrap()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext == "png" || ext == "svg")
+                .unwrap_or(false)
+        })
+        .collect();
+
+    assert!(!output_files.is_empty(), "No output files were created");
+}
+
+#[test]
+fn test_cli_render_duplicate_titles_get_distinct_files() {
+    let test_dir = create_test_dir();
+
+    let csv_content = "date,users,channel\n2023-01-01,100,organic\n2023-01-02,150,direct";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    // Two charts with the same title and type would otherwise share a file name
+    let spec_content = format!(
+        r#"
+data:
+  default: "{}"
+charts:
+  - type: line
+    title: "Weekly Users"
+    x: "date"
+    y: "users"
+  - type: line
+    title: "Weekly Users"
+    x: "date"
+    y: "users"
+    theme: dark
+"#,
+        test_dir.path().join("test.csv").to_str().unwrap()
+    );
+    create_test_spec(test_dir.path(), "test_spec.yaml", &spec_content);
+
+    let result = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "render",
+            "--spec",
+            test_dir.path().join("test_spec.yaml").to_str().unwrap(),
+            "--out",
+            test_dir.path().to_str().unwrap(),
+        ])
+        .output();
+
+    assert!(result.is_ok());
+    let output = result.unwrap();
+    assert!(output.status.success(), "CLI command failed");
+
+    let first = test_dir.path().join("weekly-users-Line.png");
+    let second = test_dir.path().join("weekly-users-Line-2.png");
+    assert!(first.exists(), "First chart was not created");
+    assert!(
+        second.exists(),
+        "Second chart was overwritten instead of suffixed"
+    );
+}
+
+#[test]
+fn test_cli_render_only_selected_chart() {
+    let test_dir = create_test_dir();
+
+    let csv_content = "date,users,channel\n2023-01-01,100,organic\n2023-01-02,150,direct";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    let spec_content = format!(
+        r#"
+data:
+  default: "{}"
+charts:
+  - type: line
+    title: "First Chart"
+    x: "date"
+    y: "users"
+  - type: line
+    title: "Second Chart"
+    x: "date"
+    y: "users"
+  - type: line
+    title: "Third Chart"
+    x: "date"
+    y: "users"
+"#,
+        test_dir.path().join("test.csv").to_str().unwrap()
+    );
+    create_test_spec(test_dir.path(), "test_spec.yaml", &spec_content);
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "render",
+            "--spec",
+            test_dir.path().join("test_spec.yaml").to_str().unwrap(),
+            "--out",
+            test_dir.path().to_str().unwrap(),
+            "--only",
+            "2",
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(!test_dir.path().join("first-chart-Line.png").exists());
+    assert!(test_dir.path().join("second-chart-Line.png").exists());
+    assert!(!test_dir.path().join("third-chart-Line.png").exists());
+}
+
+#[test]
+fn test_cli_render_join_lookup_source() {
+    let test_dir = create_test_dir();
+
+    let metrics =
+        "date,users,channel\n2023-01-01,100,organic\n2023-01-01,40,paid\n2023-01-02,150,direct";
+    create_test_csv(test_dir.path(), "metrics.csv", metrics);
+    let lookup = "channel,category\norganic,unpaid\ndirect,unpaid\npaid,paid";
+    create_test_csv(test_dir.path(), "channels.csv", lookup);
+
+    let spec_content = format!(
+        r#"
+data:
+  default: "{}"
+  sources:
+    channels: "{}"
+charts:
+  - type: bar
+    title: "Users by Category"
+    x: "date"
+    y: "users"
+    group_by: "category"
+    join:
+      source: channels
+      left_on: channel
+"#,
+        test_dir.path().join("metrics.csv").to_str().unwrap(),
+        test_dir.path().join("channels.csv").to_str().unwrap()
     );
     create_test_spec(test_dir.path(), "test_spec.yaml", &spec_content);
 
-    // Test the render command - run from the project root, not the temp dir
-    let result = std::process::Command::new("cargo")
+    let output = std::process::Command::new("cargo")
         .args([
             "run",
             "--",
@@ -139,33 +766,68 @@ charts:
             "--out",
             test_dir.path().to_str().unwrap(),
         ])
-        .output();
+        .output()
+        .expect("Failed to run CLI");
 
-    assert!(result.is_ok());
-    let output = result.unwrap();
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(test_dir.path().join("users-by-category-Bar.png").exists());
+}
 
-    if !output.status.success() {
-        println!("STDOUT: {}", String::from_utf8_lossy(&output.stdout));
-        println!("STDERR: {}", String::from_utf8_lossy(&output.stderr));
-    }
+#[test]
+fn test_cli_render_inline_data() {
+    let test_dir = create_test_dir();
 
-    assert!(output.status.success(), "CLI command failed");
+    // The rows live in the spec itself; no CSV is written
+    let spec_content = r#"
+data:
+  inline: |
+    channel,users
+    organic,120
+    direct,80
+    email,45
+charts:
+  - type: bar
+    title: "Inline Users"
+    x: "channel"
+    y: "users"
+"#;
+    create_test_spec(test_dir.path(), "test_spec.yaml", spec_content);
 
-    // Check if output files were created
-    let output_files: Vec<_> = fs::read_dir(test_dir.path())
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "render",
+            "--spec",
+            test_dir.path().join("test_spec.yaml").to_str().unwrap(),
+            "--out",
+            test_dir.path().to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(test_dir.path().join("inline-users-Bar.png").exists());
+    let csv_files = std::fs::read_dir(test_dir.path())
         .unwrap()
-        .filter_map(|entry| entry.ok())
         .filter(|entry| {
             entry
+                .as_ref()
+                .unwrap()
                 .path()
                 .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext == "png" || ext == "svg")
-                .unwrap_or(false)
+                .is_some_and(|ext| ext == "csv")
         })
-        .collect();
-
-    assert!(!output_files.is_empty(), "No output files were created");
+        .count();
+    assert_eq!(csv_files, 0);
 }
 
 #[test]
@@ -202,6 +864,11 @@ fn test_cli_error_handling_missing_file() {
         "Error message should mention file issue: {}",
         stderr
     );
+    assert_eq!(
+        output.status.code(),
+        Some(3),
+        "Missing file is a data/IO error"
+    );
 }
 
 #[test]
@@ -242,6 +909,11 @@ fn test_cli_error_handling_missing_columns() {
         "Error message should mention missing column: {}",
         stderr
     );
+    assert_eq!(
+        output.status.code(),
+        Some(2),
+        "Missing column is a validation error"
+    );
 }
 
 #[test]
@@ -355,6 +1027,10 @@ fn test_cli_with_scale_option() {
             "users",
             "--out",
             output_path.to_str().unwrap(),
+            "--width",
+            "600",
+            "--height",
+            "400",
             "--scale",
             "2.0",
         ])
@@ -373,6 +1049,16 @@ fn test_cli_with_scale_option() {
         "CLI command with scale should succeed"
     );
     assert!(output_path.exists(), "Output file was not created");
+
+    // Width and height sit in the PNG header's IHDR chunk
+    let png = fs::read(&output_path).unwrap();
+    let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+    assert_eq!(
+        (width, height),
+        (1200, 800),
+        "--scale 2.0 should double the image"
+    );
 }
 
 #[test]
@@ -426,3 +1112,409 @@ fn test_cli_with_format_option() {
         "File should be a valid PNG"
     );
 }
+
+#[test]
+fn test_cli_line_chart_pdf_format() {
+    let test_dir = create_test_dir();
+    let csv_content = "date,users\n2023-01-01,100\n2023-01-02,150\n2023-01-03,120";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    let output_path = test_dir.path().join("output.pdf");
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "line",
+            "--input",
+            test_dir.path().join("test.csv").to_str().unwrap(),
+            "--x",
+            "date",
+            "--y",
+            "users",
+            "--out",
+            output_path.to_str().unwrap(),
+            "--format",
+            "pdf",
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let bytes = std::fs::read(&output_path).unwrap();
+    assert!(bytes.starts_with(b"%PDF"), "not a PDF document");
+}
+
+#[test]
+fn test_cli_render_manifest_lists_rendered_charts() {
+    let test_dir = create_test_dir();
+    let csv_content = "channel,users\norganic,120\ndirect,80\nemail,45";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    // The last chart names a missing column, so it fails and is listed as a failure
+    let spec_content = format!(
+        r#"
+data:
+  default: "{}"
+charts:
+  - type: bar
+    title: "Users"
+    x: "channel"
+    y: "users"
+  - type: line
+    title: "Users Trend"
+    x: "channel"
+    y: "users"
+    width: 800
+    height: 400
+  - type: bar
+    title: "Broken"
+    x: "channel"
+    y: "missing"
+"#,
+        test_dir.path().join("test.csv").display()
+    );
+    create_test_spec(test_dir.path(), "test_spec.yaml", &spec_content);
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "render",
+            "--spec",
+            test_dir.path().join("test_spec.yaml").to_str().unwrap(),
+            "--out",
+            test_dir.path().to_str().unwrap(),
+            "--manifest",
+        ])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(!output.status.success(), "the broken chart should fail");
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(test_dir.path().join("manifest.json")).unwrap())
+            .unwrap();
+    let charts = manifest["charts"].as_array().unwrap();
+    assert_eq!(charts.len(), 3);
+
+    assert_eq!(charts[0]["title"], "Users");
+    assert_eq!(charts[0]["status"], "success");
+    assert_eq!(charts[0]["type"], "bar");
+    assert_eq!(charts[0]["output"], "users-Bar.png");
+    assert_eq!(charts[1]["output"], "users-trend-Line.png");
+    assert_eq!(
+        (charts[1]["width"].as_u64(), charts[1]["height"].as_u64()),
+        (Some(800), Some(400))
+    );
+    for chart in &charts[..2] {
+        assert!(
+            test_dir
+                .path()
+                .join(chart["output"].as_str().unwrap())
+                .exists()
+        );
+        assert!(chart["checksum"].as_str().unwrap().starts_with("crc32:"));
+    }
+
+    let broken = &charts[2];
+    assert_eq!(broken["title"], "Broken");
+    assert_eq!(broken["status"], "failure");
+    assert!(broken["error"].as_str().unwrap().contains("missing"));
+    assert!(broken.get("checksum").is_none());
+}
+
+#[test]
+fn test_cli_render_parallel_writes_every_chart() {
+    let test_dir = create_test_dir();
+    let csv_content = "channel,users,sessions\norganic,120,300\ndirect,80,150\nemail,45,90";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    let charts: String = ["bar", "line", "area", "scatter", "bar", "line"]
+        .iter()
+        .enumerate()
+        .map(|(index, chart_type)| {
+            format!(
+                "  - type: {}\n    title: \"Chart {}\"\n    x: \"users\"\n    y: \"sessions\"\n",
+                chart_type, index
+            )
+        })
+        .collect();
+    let spec_content = format!(
+        "data:\n  default: \"{}\"\ncharts:\n{}",
+        test_dir.path().join("test.csv").display(),
+        charts
+    );
+    create_test_spec(test_dir.path(), "test_spec.yaml", &spec_content);
+    let output_dir = test_dir.path().join("charts");
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "render",
+            "--spec",
+            test_dir.path().join("test_spec.yaml").to_str().unwrap(),
+            "--out",
+            output_dir.to_str().unwrap(),
+            "--parallel",
+            "4",
+        ])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Summary: 6 successful, 0 failed"));
+    let outputs = [
+        "chart-0-Bar.png",
+        "chart-1-Line.png",
+        "chart-2-Area.png",
+        "chart-3-Scatter.png",
+        "chart-4-Bar.png",
+        "chart-5-Line.png",
+    ];
+    for name in outputs {
+        assert!(output_dir.join(name).exists(), "{} should exist", name);
+    }
+
+    // Results are reported in spec order, whichever chart finished first
+    let reported: Vec<&str> = stdout
+        .lines()
+        .filter_map(|line| line.strip_prefix("✓ Generated: "))
+        .collect();
+    assert_eq!(reported.len(), outputs.len());
+    for (line, name) in reported.iter().zip(outputs) {
+        assert!(line.ends_with(name), "{} reported out of order", line);
+    }
+}
+
+#[test]
+fn test_cli_render_manifest_to_a_chosen_path() {
+    let test_dir = create_test_dir();
+    let csv_content = "channel,users\norganic,120\ndirect,80";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+    let spec_content = format!(
+        r#"
+data:
+  default: "{}"
+charts:
+  - type: bar
+    title: "Users"
+    x: "channel"
+    y: "users"
+"#,
+        test_dir.path().join("test.csv").display()
+    );
+    create_test_spec(test_dir.path(), "test_spec.yaml", &spec_content);
+    let output_dir = test_dir.path().join("charts");
+    let manifest_path = test_dir.path().join("ci").join("charts.json");
+    fs::create_dir_all(manifest_path.parent().unwrap()).unwrap();
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "render",
+            "--spec",
+            test_dir.path().join("test_spec.yaml").to_str().unwrap(),
+            "--out",
+            output_dir.to_str().unwrap(),
+            "--manifest",
+            manifest_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+    let output = manifest["charts"][0]["output"].as_str().unwrap();
+    assert!(
+        std::path::Path::new(output).exists(),
+        "{} should exist",
+        output
+    );
+    assert!(!output_dir.join("manifest.json").exists());
+}
+
+#[test]
+fn test_cli_render_derived_week_column() {
+    let test_dir = create_test_dir();
+    // Two weeks: Mon 2 Jan to Sun 8 Jan, then Mon 9 Jan
+    let csv_content =
+        "date,users\n2023-01-02,10\n2023-01-04,20\n2023-01-08,30\n2023-01-09,5\n2023-01-11,15";
+    create_test_csv(test_dir.path(), "test.csv", csv_content);
+
+    let spec_content = format!(
+        r#"
+data:
+  default: "{}"
+charts:
+  - type: line
+    title: "Weekly Users"
+    x: "week"
+    y: "users"
+    agg: sum
+    derive:
+      week: "to_week(date)"
+"#,
+        test_dir.path().join("test.csv").display()
+    );
+    create_test_spec(test_dir.path(), "test_spec.yaml", &spec_content);
+    let data_path = test_dir.path().join("weekly.json");
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "render",
+            "--spec",
+            test_dir.path().join("test_spec.yaml").to_str().unwrap(),
+            "--out",
+            test_dir.path().to_str().unwrap(),
+            "--emit-data",
+            data_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(
+        output.status.success(),
+        "CLI failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(test_dir.path().join("weekly-users-Line.png").exists());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&data_path).unwrap()).unwrap();
+    assert_eq!(json["x_axis"]["column"], "week");
+    let series = &json["series"][0];
+    assert_eq!(series["x"], serde_json::json!(["2023-01-02", "2023-01-09"]));
+    assert_eq!(series["y"], serde_json::json!([60, 20]));
+}
+
+#[test]
+fn test_cli_error_format_json() {
+    let test_dir = create_test_dir();
+    let missing = test_dir.path().join("missing.csv");
+
+    let output = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "line",
+            "--input",
+            missing.to_str().unwrap(),
+            "--x",
+            "date",
+            "--y",
+            "users",
+            "--error-format",
+            "json",
+        ])
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(!output.status.success());
+    // cargo's own progress lines come first; the error is the last line
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let last_line = stderr.lines().last().unwrap();
+    let json: serde_json::Value = serde_json::from_str(last_line).expect("stderr is not JSON");
+    assert!(
+        json["error"]
+            .as_str()
+            .unwrap()
+            .contains("Failed to load data")
+    );
+    assert!(
+        json["context"]
+            .as_array()
+            .is_some_and(|context| !context.is_empty())
+    );
+}
+
+#[test]
+fn test_cli_reads_csv_from_stdin() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let test_dir = create_test_dir();
+    let output_path = test_dir.path().join("piped.png");
+
+    // No --input: the CSV arrives on stdin, as in `cat data.csv | graff line ...`
+    let mut child = std::process::Command::new("cargo")
+        .args([
+            "run",
+            "--",
+            "line",
+            "--x",
+            "date",
+            "--y",
+            "users",
+            "--out",
+            output_path.to_str().unwrap(),
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run CLI");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"date,users\n01/15/2023,100\n01/16/2023,150\n01/17/2023,120\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    if !output.status.success() {
+        println!("STDERR: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    assert!(output.status.success(), "CLI command failed");
+    let png = fs::read(&output_path).expect("Output file was not created");
+    assert!(png.starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn test_cli_names_stdin_output_after_the_chart_kind() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let test_dir = create_test_dir();
+
+    // Without --input or --out there is no file name to borrow, so the output is `line.png`
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_graff"))
+        .args(["line", "--x", "date", "--y", "users"])
+        .current_dir(test_dir.path())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run CLI");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"date,users\n01/15/2023,100\n01/16/2023,150\n01/17/2023,120\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    if !output.status.success() {
+        println!("STDERR: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    assert!(output.status.success(), "CLI command failed");
+    let png = fs::read(test_dir.path().join("line.png")).expect("line.png was not created");
+    assert!(png.starts_with(b"\x89PNG"));
+    assert!(!test_dir.path().join("--line.png").exists());
+}